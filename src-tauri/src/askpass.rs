@@ -0,0 +1,559 @@
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Set when the GUI binary is started by sudo as its askpass helper; names
+/// the backend the helper should use
+const HELPER_ENV: &str = "ALFS_ASKPASS_HELPER";
+const SOCKET_ENV: &str = "ALFS_ASKPASS_SOCKET";
+const TOKEN_ENV: &str = "ALFS_ASKPASS_TOKEN";
+
+/// Name prefix of the private socket directories, so leftovers can be found later
+pub const ASKPASS_PREFIX: &str = "anylinuxfs-askpass-";
+
+const DIALOG_TITLE: &str = "Authentication Required";
+const DIALOG_TEXT: &str = "anylinuxfs requires administrator privileges.";
+
+/// How long the app waits for the user to answer a built-in prompt
+const PROMPT_TIMEOUT_SECS: u64 = 120;
+
+const SSH_ASKPASS_PATHS: &[&str] = &[
+    "/usr/bin/ssh-askpass",
+    "/usr/lib/ssh/ssh-askpass",
+    "/usr/libexec/openssh/ssh-askpass",
+    "/usr/lib/openssh/gnome-ssh-askpass",
+    "/usr/local/bin/ssh-askpass",
+    "/opt/homebrew/bin/ssh-askpass",
+];
+
+/// Program sudo runs (via `SUDO_ASKPASS`) when no cached credential or
+/// Touch ID is available
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AskpassBackend {
+    /// First available backend in `DETECTION_ORDER`
+    #[default]
+    Auto,
+    Osascript,
+    Zenity,
+    Kdialog,
+    SshAskpass,
+    /// The app's own password dialog
+    BuiltIn,
+}
+
+const DETECTION_ORDER: &[AskpassBackend] = &[
+    AskpassBackend::Osascript,
+    AskpassBackend::Kdialog,
+    AskpassBackend::Zenity,
+    AskpassBackend::SshAskpass,
+    AskpassBackend::BuiltIn,
+];
+
+impl AskpassBackend {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Osascript => "osascript",
+            Self::Zenity => "zenity",
+            Self::Kdialog => "kdialog",
+            Self::SshAskpass => "ssh_askpass",
+            Self::BuiltIn => "built_in",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        DETECTION_ORDER
+            .iter()
+            .copied()
+            .find(|backend| backend.name() == name)
+    }
+
+    /// Dialog program the backend runs, if it is installed and usable
+    fn program(self) -> Option<PathBuf> {
+        match self {
+            Self::Auto => None,
+            Self::Osascript => {
+                Some(PathBuf::from("/usr/bin/osascript")).filter(|path| path.is_file())
+            }
+            Self::Zenity => find_in_path("zenity").filter(|_| has_display()),
+            // kdialog only makes sense inside a KDE session
+            Self::Kdialog => find_in_path("kdialog")
+                .filter(|_| has_display() && desktop_is("KDE")),
+            Self::SshAskpass => std::env::var_os("SSH_ASKPASS")
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+                .into_iter()
+                .chain(SSH_ASKPASS_PATHS.iter().map(PathBuf::from))
+                .find(|path| path.is_file())
+                .filter(|_| has_display()),
+            Self::BuiltIn => BROKER
+                .get()
+                .and_then(|_| std::env::current_exe().ok()),
+        }
+    }
+
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Auto => DETECTION_ORDER.iter().any(|backend| backend.is_available()),
+            backend => backend.program().is_some(),
+        }
+    }
+
+    /// The concrete backend to use, resolving `Auto`
+    pub fn resolve(self) -> Result<Self, String> {
+        match self {
+            Self::Auto => DETECTION_ORDER
+                .iter()
+                .copied()
+                .find(|backend| backend.is_available())
+                .ok_or_else(|| "No password dialog is available for sudo".to_string()),
+            backend if backend.is_available() => Ok(backend),
+            backend => Err(format!(
+                "The {} password dialog is not available on this system",
+                backend.name()
+            )),
+        }
+    }
+}
+
+/// Concrete backends usable right now, in auto-detection order
+pub fn available_backends() -> Vec<AskpassBackend> {
+    DETECTION_ORDER
+        .iter()
+        .copied()
+        .filter(|backend| backend.is_available())
+        .collect()
+}
+
+pub(crate) fn find_in_path(program: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|path| path.is_file())
+    })
+}
+
+fn has_display() -> bool {
+    cfg!(target_os = "macos")
+        || std::env::var_os("DISPLAY").is_some()
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+fn desktop_is(name: &str) -> bool {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .is_ok_and(|desktop| desktop.split(':').any(|part| part.eq_ignore_ascii_case(name)))
+}
+
+/// Askpass wiring for one sudo invocation. Dialog backends re-execute the
+/// GUI binary, so nothing is written to disk; the built-in backend also
+/// serves the private socket the helper reports back on until dropped.
+pub struct AskpassSession {
+    program: PathBuf,
+    env: Vec<(&'static str, String)>,
+    _server: Option<PromptServer>,
+}
+
+impl AskpassSession {
+    pub fn start(backend: AskpassBackend) -> Result<Self, String> {
+        let backend = backend.resolve()?;
+        match backend {
+            // ssh-askpass takes the prompt as its only argument, just like sudo passes it
+            AskpassBackend::SshAskpass => Ok(Self {
+                program: backend
+                    .program()
+                    .ok_or_else(|| "ssh-askpass not found".to_string())?,
+                env: Vec::new(),
+                _server: None,
+            }),
+            AskpassBackend::BuiltIn => {
+                let server = PromptServer::start(Arc::new(|prompt: &str, stop: &AtomicBool| {
+                    BROKER.get().and_then(|broker| broker.request(prompt, stop))
+                }))?;
+                Ok(Self {
+                    program: current_exe()?,
+                    env: vec![
+                        (HELPER_ENV, backend.name().to_string()),
+                        (SOCKET_ENV, server.socket_path.to_string_lossy().to_string()),
+                        (TOKEN_ENV, server.token.clone()),
+                    ],
+                    _server: Some(server),
+                })
+            }
+            backend => Ok(Self {
+                program: current_exe()?,
+                env: vec![(HELPER_ENV, backend.name().to_string())],
+                _server: None,
+            }),
+        }
+    }
+
+    /// Point `sudo -A` at this session's helper
+    pub fn apply(&self, cmd: &mut Command) {
+        cmd.env("SUDO_ASKPASS", &self.program);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+    }
+}
+
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("Cannot locate the anylinuxfs GUI binary: {}", e))
+}
+
+type Responder = Arc<dyn Fn(&str, &AtomicBool) -> Option<String> + Send + Sync>;
+
+/// Unix socket in a private (0700) directory that answers askpass helpers
+/// presenting the session token.
+struct PromptServer {
+    dir: tempfile::TempDir,
+    socket_path: PathBuf,
+    token: String,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PromptServer {
+    fn start(responder: Responder) -> Result<Self, String> {
+        let dir = tempfile::Builder::new()
+            .prefix(ASKPASS_PREFIX)
+            .tempdir()
+            .map_err(|e| format!("Failed to create askpass directory: {}", e))?;
+        let socket_path = dir.path().join("askpass.sock");
+        let listener = UnixListener::bind(&socket_path)
+            .map_err(|e| format!("Failed to create askpass socket: {}", e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure askpass socket: {}", e))?;
+
+        let mut token_bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut token_bytes);
+        let token = token_bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let token = token.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || serve_prompts(listener, &token, &stop, responder))
+        };
+
+        Ok(Self {
+            dir,
+            socket_path,
+            token,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for PromptServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        log::debug!("askpass: closed prompt socket in {}", self.dir.path().display());
+    }
+}
+
+/// sudo starts a new helper for every attempt, so connections are served
+/// one at a time until the session ends.
+fn serve_prompts(listener: UnixListener, token: &str, stop: &AtomicBool, responder: Responder) {
+    while !stop.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(error) = answer_helper(stream, token, stop, &responder) {
+                    log::warn!("askpass: helper connection failed: {}", error);
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(error) => {
+                log::warn!("askpass: socket accept failed: {}", error);
+                return;
+            }
+        }
+    }
+}
+
+fn answer_helper(
+    stream: UnixStream,
+    token: &str,
+    stop: &AtomicBool,
+    responder: &Responder,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut presented = String::new();
+    reader.read_line(&mut presented)?;
+    if presented.trim_end() != token {
+        log::warn!("askpass: rejected helper with a wrong token");
+        return Ok(());
+    }
+    let mut prompt = String::new();
+    reader.read_line(&mut prompt)?;
+
+    // No answer closes the connection empty, which the helper reports as cancelled
+    if let Some(password) = responder(prompt.trim_end_matches(['\r', '\n']), stop) {
+        let mut stream = stream;
+        stream.write_all(password.as_bytes())?;
+        stream.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Forwards built-in prompts to the frontend and waits for `answer_askpass`
+struct AskpassBroker {
+    app: AppHandle,
+    pending: Mutex<HashMap<u64, mpsc::Sender<Option<String>>>>,
+    next_id: AtomicU64,
+}
+
+static BROKER: OnceLock<AskpassBroker> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AskpassRequest {
+    pub id: u64,
+    pub prompt: String,
+}
+
+impl AskpassBroker {
+    fn request(&self, prompt: &str, stop: &AtomicBool) -> Option<String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(id, sender);
+
+        if let Some(window) = self.app.get_webview_window("main") {
+            #[cfg(target_os = "macos")]
+            crate::set_dock_visible(true);
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        let _ = self.app.emit(
+            "askpass-request",
+            AskpassRequest {
+                id,
+                prompt: prompt.to_string(),
+            },
+        );
+
+        let mut waited = Duration::ZERO;
+        let answer = loop {
+            match receiver.recv_timeout(Duration::from_millis(200)) {
+                Ok(answer) => break answer,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    waited += Duration::from_millis(200);
+                    if stop.load(Ordering::SeqCst)
+                        || waited >= Duration::from_secs(PROMPT_TIMEOUT_SECS)
+                    {
+                        break None;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break None,
+            }
+        };
+        let answered = self
+            .pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&id)
+            .is_none();
+        if !answered {
+            let _ = self.app.emit("askpass-dismissed", id);
+        }
+        answer
+    }
+}
+
+/// Let the built-in backend show prompts in the app window
+pub fn install_broker(app: AppHandle) {
+    let _ = BROKER.set(AskpassBroker {
+        app,
+        pending: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+    });
+}
+
+/// Entry point when sudo runs the GUI binary as `SUDO_ASKPASS`. Returns the
+/// exit code, or `None` for a normal app launch.
+pub fn run_helper() -> Option<i32> {
+    let backend = std::env::var(HELPER_ENV).ok()?;
+    let prompt = std::env::args().nth(1).unwrap_or_else(|| "Password:".to_string());
+
+    let answered = match AskpassBackend::from_name(&backend) {
+        Some(AskpassBackend::BuiltIn) => {
+            let socket = std::env::var_os(SOCKET_ENV).map(PathBuf::from);
+            let token = std::env::var(TOKEN_ENV).ok();
+            match (socket, token) {
+                (Some(socket), Some(token)) => match request_from_app(&socket, &token, &prompt) {
+                    Some(password) => {
+                        println!("{}", password);
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            }
+        }
+        Some(backend) => run_dialog(backend),
+        None => false,
+    };
+    Some(if answered { 0 } else { 1 })
+}
+
+/// Show the backend's password dialog; it prints the password to our
+/// stdout, which sudo reads, and exits non-zero when cancelled.
+fn run_dialog(backend: AskpassBackend) -> bool {
+    let Some(program) = backend.program() else {
+        return false;
+    };
+    let mut cmd = Command::new(program);
+    match backend {
+        AskpassBackend::Osascript => {
+            cmd.args([
+                "-e",
+                &format!(
+                    "Tell application \"System Events\" to display dialog \"{}\" & return & return & \"Enter your password:\" with hidden answer default answer \"\" buttons {{\"Cancel\", \"OK\"}} default button \"OK\" with title \"{}\" with icon caution",
+                    DIALOG_TEXT, DIALOG_TITLE
+                ),
+                "-e",
+                "text returned of result",
+            ]);
+        }
+        AskpassBackend::Zenity => {
+            cmd.args([
+                "--entry",
+                "--hide-text",
+                "--title",
+                DIALOG_TITLE,
+                "--text",
+                &format!("{}\n\nEnter your password:", DIALOG_TEXT),
+            ]);
+        }
+        AskpassBackend::Kdialog => {
+            cmd.args([
+                "--title",
+                DIALOG_TITLE,
+                "--password",
+                &format!("{}\n\nEnter your password:", DIALOG_TEXT),
+            ]);
+        }
+        // sudo runs ssh-askpass directly and the built-in prompt has no program
+        AskpassBackend::Auto | AskpassBackend::SshAskpass | AskpassBackend::BuiltIn => {
+            return false
+        }
+    }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn request_from_app(socket: &Path, token: &str, prompt: &str) -> Option<String> {
+    let mut stream = UnixStream::connect(socket).ok()?;
+    // sudo's prompt is a single line; keep it that way on the wire
+    let prompt = prompt.replace(['\r', '\n'], " ");
+    stream
+        .write_all(format!("{}\n{}\n", token, prompt).as_bytes())
+        .ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    let password = response.strip_suffix('\n')?;
+    Some(password.to_string())
+}
+
+#[tauri::command]
+pub fn answer_askpass(id: u64, password: Option<String>) -> Result<(), String> {
+    let broker = BROKER
+        .get()
+        .ok_or_else(|| "Password prompts are not enabled".to_string())?;
+    let sender = broker
+        .pending
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(&id)
+        .ok_or_else(|| "The password prompt has already closed".to_string())?;
+    let _ = sender.send(password);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helper_receives_the_answer_only_with_the_session_token() {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let server = {
+            let prompts = prompts.clone();
+            PromptServer::start(Arc::new(move |prompt: &str, _stop: &AtomicBool| {
+                prompts.lock().unwrap().push(prompt.to_string());
+                (!prompt.contains("again")).then(|| "hunter2".to_string())
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(
+            request_from_app(&server.socket_path, &server.token, "[sudo] password for alice: "),
+            Some("hunter2".to_string())
+        );
+        // A declined prompt closes the connection without an answer
+        assert_eq!(request_from_app(&server.socket_path, &server.token, "try again"), None);
+        assert_eq!(request_from_app(&server.socket_path, "0000", "password"), None);
+        assert_eq!(
+            prompts.lock().unwrap().as_slice(),
+            ["[sudo] password for alice: ", "try again"]
+        );
+
+        let directory = server.dir.path().to_path_buf();
+        drop(server);
+        assert!(!directory.exists(), "the private socket directory is removed");
+    }
+
+    #[test]
+    fn dropping_the_session_abandons_a_pending_prompt() {
+        // Like the broker, the responder gives up once the session stops
+        let server = PromptServer::start(Arc::new(|_prompt: &str, stop: &AtomicBool| {
+            while !stop.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            None
+        }))
+        .unwrap();
+        let socket = server.socket_path.clone();
+        let token = server.token.clone();
+        let helper = std::thread::spawn(move || request_from_app(&socket, &token, "password"));
+
+        std::thread::sleep(Duration::from_millis(200));
+        drop(server);
+        assert_eq!(helper.join().unwrap(), None);
+    }
+
+    #[test]
+    fn backend_names_round_trip_and_auto_prefers_listed_order() {
+        for backend in DETECTION_ORDER {
+            assert_eq!(AskpassBackend::from_name(backend.name()), Some(*backend));
+        }
+        assert_eq!(AskpassBackend::from_name("auto"), None);
+        if let Ok(resolved) = AskpassBackend::Auto.resolve() {
+            assert_eq!(available_backends().first(), Some(&resolved));
+        }
+    }
+}
@@ -0,0 +1,611 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use crate::elevation::ElevationMode;
+use crate::secrets::SecretsState;
+
+/// `prev_hash` of the first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const REDACTED: &str = "<redacted>";
+
+/// Flags whose value is a secret
+const SECRET_FLAGS: &[&str] = &["--passphrase", "--password", "--recovery-key"];
+
+/// `-o` option keys whose value is a secret
+const SECRET_OPTION_KEYS: &[&str] = &["passphrase", "password", "pass", "key", "recovery_key"];
+
+/// How a privileged command was run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditMethod {
+    /// sudo with cached credentials, Touch ID or a NOPASSWD rule
+    Sudo,
+    /// sudo with a password dialog
+    SudoAskpass,
+    Terminal,
+    Helper,
+    Pkexec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Succeeded,
+    Failed,
+    /// Authentication was needed but not attempted (silent refresh or a
+    /// fallback to the password dialog)
+    AuthenticationRequired,
+    Cancelled,
+    TimedOut,
+}
+
+/// Disk unlock material supplied with the command; never its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSecret {
+    Passphrase,
+    Keyfile,
+}
+
+/// One privileged command. The hash covers every field plus `prev_hash`,
+/// so editing, dropping or reordering lines breaks the chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch when the command started
+    pub timestamp: u64,
+    pub mode: ElevationMode,
+    pub method: AuditMethod,
+    pub program: String,
+    pub subcommand: Option<String>,
+    pub args: Vec<String>,
+    pub secret: Option<AuditSecret>,
+    pub duration_ms: u64,
+    pub exit_status: Option<i32>,
+    pub outcome: AuditOutcome,
+    pub prev_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    fn hash(&self) -> Result<String, String> {
+        let encoded = serde_json::to_vec(self)
+            .map_err(|e| format!("Failed to encode audit entry: {}", e))?;
+        let digest = Sha256::digest(&encoded);
+        Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    /// Milliseconds since the Unix epoch, inclusive
+    #[serde(default)]
+    pub since: Option<u64>,
+    #[serde(default)]
+    pub until: Option<u64>,
+    #[serde(default)]
+    pub subcommand: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<AuditOutcome>,
+    /// Newest entries first, at most this many
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp <= until)
+            && self
+                .subcommand
+                .as_ref()
+                .is_none_or(|subcommand| record.subcommand.as_ref() == Some(subcommand))
+            && self.outcome.is_none_or(|outcome| record.outcome == outcome)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub entries: usize,
+    /// 1-based line of the first entry that does not match the chain
+    pub first_invalid_line: Option<usize>,
+    pub message: Option<String>,
+}
+
+/// Where the `seq` and hash of the newest entry are recorded outside the log
+/// file. The chain alone cannot show entries cut off the end of the log, so
+/// `verify` compares the log's last entry with this anchor.
+pub trait AuditAnchor: Send + Sync {
+    fn load(&self) -> Result<Option<String>, String>;
+
+    fn store(&self, head: &str) -> Result<(), String>;
+}
+
+/// The anchor lives in the secret store: on macOS a Keychain item other
+/// programs cannot change without the user's consent. With the encrypted file
+/// fallback it is only as protected as that file.
+impl AuditAnchor for SecretsState {
+    fn load(&self) -> Result<Option<String>, String> {
+        self.get_audit_anchor()
+    }
+
+    fn store(&self, head: &str) -> Result<(), String> {
+        self.set_audit_anchor(head)
+    }
+}
+
+/// "seq:hash" of the newest entry
+fn format_head(seq: u64, hash: &str) -> String {
+    format!("{}:{}", seq, hash)
+}
+
+fn parse_head(head: &str) -> Option<(u64, String)> {
+    let (seq, hash) = head.split_once(':')?;
+    Some((seq.parse().ok()?, hash.to_string()))
+}
+
+/// Append-only JSONL log. Appends are serialized so the chain stays linear
+/// across concurrent commands.
+pub struct AuditLog {
+    path: PathBuf,
+    /// `seq` and hash of the next entry's predecessor
+    tail: Mutex<(u64, String)>,
+    anchor: Arc<dyn AuditAnchor>,
+}
+
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+impl AuditLog {
+    /// Continue the chain from the anchored head, so entries appended after
+    /// a truncation do not hide it. A log without an anchor (written before
+    /// anchoring existed) is anchored at its current last entry.
+    pub fn open(path: PathBuf, anchor: Arc<dyn AuditAnchor>) -> Self {
+        let last = read_entries(&path).ok().and_then(|entries| {
+            entries
+                .into_iter()
+                .rev()
+                .find_map(|(_, entry)| entry.ok())
+                .map(|entry| (entry.record.seq, entry.hash))
+        });
+        let anchored = match anchor.load() {
+            Ok(head) => head.as_deref().and_then(parse_head),
+            Err(e) => {
+                log::warn!("audit: cannot read anchor: {}", e);
+                None
+            }
+        };
+        let head = match (anchored, last) {
+            (Some(anchored), Some(last)) if last.0 > anchored.0 => Some(last),
+            (Some(anchored), _) => Some(anchored),
+            (None, Some(last)) => {
+                if let Err(e) = anchor.store(&format_head(last.0, &last.1)) {
+                    log::warn!("audit: cannot anchor existing log: {}", e);
+                }
+                Some(last)
+            }
+            (None, None) => None,
+        };
+        let tail = head
+            .map(|(seq, hash)| (seq + 1, hash))
+            .unwrap_or_else(|| (0, GENESIS_HASH.to_string()));
+        Self {
+            path,
+            tail: Mutex::new(tail),
+            anchor,
+        }
+    }
+
+    fn append(&self, mut record: AuditRecord) -> Result<(), String> {
+        let mut tail = self
+            .tail
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        record.seq = tail.0;
+        record.prev_hash = tail.1.clone();
+        let hash = record.hash()?;
+        let mut line = serde_json::to_vec(&AuditEntry {
+            record,
+            hash: hash.clone(),
+        })
+        .map_err(|e| format!("Failed to encode audit entry: {}", e))?;
+        line.push(b'\n');
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create audit log directory: {}", e))?;
+        }
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        file.write_all(&line)
+            .map_err(|e| format!("Failed to write audit log: {}", e))?;
+        file.sync_data()
+            .map_err(|e| format!("Failed to flush audit log: {}", e))?;
+
+        if let Err(e) = self.anchor.store(&format_head(tail.0, &hash)) {
+            log::warn!("audit: cannot update anchor: {}", e);
+        }
+        *tail = (tail.0 + 1, hash);
+        Ok(())
+    }
+
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, String> {
+        let mut entries = read_entries(&self.path)?
+            .into_iter()
+            .filter_map(|(_, entry)| entry.ok())
+            .filter(|entry| query.matches(&entry.record))
+            .collect::<Vec<_>>();
+        entries.reverse();
+        if let Some(limit) = query.limit {
+            entries.truncate(limit);
+        }
+        Ok(entries)
+    }
+
+    pub fn verify(&self) -> Result<AuditVerification, String> {
+        let entries = read_entries(&self.path)?;
+        let count = entries.len();
+        let mut expected_prev = GENESIS_HASH.to_string();
+        let mut last_seq = None;
+
+        for (index, (line_number, entry)) in entries.into_iter().enumerate() {
+            let invalid = |message: String| AuditVerification {
+                valid: false,
+                entries: count,
+                first_invalid_line: Some(line_number),
+                message: Some(message),
+            };
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => return Ok(invalid(format!("Unreadable entry: {}", error))),
+            };
+            if entry.record.seq != index as u64 {
+                return Ok(invalid(format!(
+                    "Entry has sequence number {}, expected {}",
+                    entry.record.seq, index
+                )));
+            }
+            if entry.record.prev_hash != expected_prev {
+                return Ok(invalid("Entry does not follow the previous entry".to_string()));
+            }
+            if entry.record.hash()? != entry.hash {
+                return Ok(invalid("Entry was modified after it was written".to_string()));
+            }
+            expected_prev = entry.hash;
+            last_seq = Some(entry.record.seq);
+        }
+
+        // The chain is intact; it must also end where the anchor says
+        let anchored = self.anchor.load().map_err(|e| format!("Cannot read audit anchor: {}", e))?;
+        let head = last_seq.map(|seq| format_head(seq, &expected_prev));
+        if anchored != head {
+            let message = match anchored.as_deref().and_then(parse_head) {
+                Some((seq, _)) if last_seq.is_none_or(|last| last < seq) => format!(
+                    "Entries up to {} were recorded but the log ends {}; entries were removed",
+                    seq,
+                    last_seq.map_or("before the first entry".to_string(), |last| format!("at {}", last))
+                ),
+                Some(_) => "The log's last entry is not the one last recorded".to_string(),
+                None => "The log has entries but no anchor was recorded".to_string(),
+            };
+            return Ok(AuditVerification {
+                valid: false,
+                entries: count,
+                first_invalid_line: None,
+                message: Some(message),
+            });
+        }
+
+        Ok(AuditVerification {
+            valid: true,
+            entries: count,
+            first_invalid_line: None,
+            message: None,
+        })
+    }
+}
+
+/// Non-empty lines with their 1-based line numbers
+#[allow(clippy::type_complexity)]
+fn read_entries(path: &Path) -> Result<Vec<(usize, Result<AuditEntry, String>)>, String> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("Failed to open audit log: {}", error)),
+    };
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push((
+            index + 1,
+            serde_json::from_str::<AuditEntry>(&line).map_err(|e| e.to_string()),
+        ));
+    }
+    Ok(entries)
+}
+
+/// Start recording privileged commands to `path`, anchored in `anchor`
+pub fn init(path: PathBuf, anchor: Arc<dyn AuditAnchor>) {
+    let _ = AUDIT_LOG.set(AuditLog::open(path, anchor));
+}
+
+/// Replace secret values in `args`: the value after a secret flag, secret
+/// keys inside `-o` option lists, and any argument containing `secret`.
+pub fn redact_args(args: &[&str], secret: Option<&str>) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut hide_next = false;
+    for arg in args {
+        if std::mem::take(&mut hide_next) {
+            redacted.push(REDACTED.to_string());
+            continue;
+        }
+        if secret.is_some_and(|secret| !secret.is_empty() && arg.contains(secret)) {
+            redacted.push(REDACTED.to_string());
+            continue;
+        }
+        if let Some((flag, _)) = arg.split_once('=') {
+            if SECRET_FLAGS.contains(&flag) {
+                redacted.push(format!("{}={}", flag, REDACTED));
+                continue;
+            }
+        }
+        if SECRET_FLAGS.contains(arg) {
+            hide_next = true;
+            redacted.push(arg.to_string());
+            continue;
+        }
+        redacted.push(redact_options(arg));
+    }
+    redacted
+}
+
+fn redact_options(arg: &str) -> String {
+    if !arg.contains('=') {
+        return arg.to_string();
+    }
+    arg.split(',')
+        .map(|option| match option.split_once('=') {
+            Some((key, _)) if SECRET_OPTION_KEYS.contains(&key.trim()) => {
+                format!("{}={}", key, REDACTED)
+            }
+            _ => option.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Records one privileged command when dropped, so every return path of the
+/// caller is logged. Callers report the exit status or outcome as they learn it.
+pub struct AuditScope {
+    record: AuditRecord,
+    started: Instant,
+    outcome: Option<AuditOutcome>,
+}
+
+impl AuditScope {
+    pub fn begin(
+        mode: ElevationMode,
+        method: AuditMethod,
+        program: &Path,
+        args: &[&str],
+        secret: Option<AuditSecret>,
+        passphrase: Option<&str>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        Self {
+            record: AuditRecord {
+                seq: 0,
+                timestamp,
+                mode,
+                method,
+                program: program.to_string_lossy().to_string(),
+                subcommand: args.first().map(|subcommand| subcommand.to_string()),
+                args: redact_args(args, passphrase),
+                secret,
+                duration_ms: 0,
+                exit_status: None,
+                outcome: AuditOutcome::Failed,
+                prev_hash: String::new(),
+            },
+            started: Instant::now(),
+            outcome: None,
+        }
+    }
+
+    pub fn exit_status(&mut self, status: Option<i32>) {
+        self.record.exit_status = status;
+    }
+
+    pub fn outcome(&mut self, outcome: AuditOutcome) {
+        self.outcome = Some(outcome);
+    }
+
+    fn finish(&mut self) -> AuditRecord {
+        let mut record = self.record.clone();
+        record.duration_ms = self.started.elapsed().as_millis() as u64;
+        record.outcome = self.outcome.unwrap_or(match record.exit_status {
+            Some(0) => AuditOutcome::Succeeded,
+            _ => AuditOutcome::Failed,
+        });
+        record
+    }
+}
+
+impl Drop for AuditScope {
+    fn drop(&mut self) {
+        let Some(log) = AUDIT_LOG.get() else {
+            return;
+        };
+        let record = self.finish();
+        if let Err(error) = log.append(record) {
+            log::warn!("audit: {}", error);
+        }
+    }
+}
+
+fn audit_log() -> Result<&'static AuditLog, String> {
+    AUDIT_LOG
+        .get()
+        .ok_or_else(|| "The audit log is not available".to_string())
+}
+
+#[tauri::command]
+pub async fn query_audit_log(query: Option<AuditQuery>) -> Result<Vec<AuditEntry>, String> {
+    tokio::task::spawn_blocking(move || audit_log()?.query(&query.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[tauri::command]
+pub async fn verify_audit_log() -> Result<AuditVerification, String> {
+    tokio::task::spawn_blocking(|| audit_log()?.verify())
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(subcommand: &str, exit_status: i32) -> AuditRecord {
+        let mut scope = AuditScope::begin(
+            ElevationMode::Native,
+            AuditMethod::Sudo,
+            Path::new("/opt/homebrew/bin/anylinuxfs"),
+            &[subcommand, "/dev/disk4s1"],
+            None,
+            None,
+        );
+        scope.exit_status(Some(exit_status));
+        let record = scope.finish();
+        // Only the log appends; keep the scope from recording globally
+        std::mem::forget(scope);
+        record
+    }
+
+    #[test]
+    fn passphrases_are_redacted_from_arguments() {
+        assert_eq!(
+            redact_args(
+                &["mount", "--passphrase", "hunter2", "-o", "ro,password=hunter2,noatime", "/dev/disk4s1"],
+                None
+            ),
+            vec!["mount", "--passphrase", REDACTED, "-o", "ro,password=<redacted>,noatime", "/dev/disk4s1"]
+        );
+        assert_eq!(
+            redact_args(&["mount", "--recovery-key=1234-5678", "label=open sesame"], Some("open sesame")),
+            vec!["mount", "--recovery-key=<redacted>", REDACTED]
+        );
+        assert_eq!(redact_args(&["list", "--json"], None), vec!["list", "--json"]);
+    }
+
+    #[derive(Default)]
+    struct MemoryAnchor(Mutex<Option<String>>);
+
+    impl AuditAnchor for MemoryAnchor {
+        fn load(&self) -> Result<Option<String>, String> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn store(&self, head: &str) -> Result<(), String> {
+            *self.0.lock().unwrap() = Some(head.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn chain_detects_edited_and_removed_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("audit.jsonl");
+        let anchor = Arc::new(MemoryAnchor::default());
+        let log = AuditLog::open(path.clone(), anchor.clone());
+        log.append(record("list", 0)).unwrap();
+        log.append(record("mount", 1)).unwrap();
+        // Reopening continues the chain
+        let log = AuditLog::open(path.clone(), anchor.clone());
+        log.append(record("unmount", 0)).unwrap();
+
+        let verification = log.verify().unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.entries, 3);
+
+        let failed = log
+            .query(&AuditQuery {
+                outcome: Some(AuditOutcome::Failed),
+                ..AuditQuery::default()
+            })
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].record.subcommand.as_deref(), Some("mount"));
+        let newest = log
+            .query(&AuditQuery {
+                limit: Some(1),
+                ..AuditQuery::default()
+            })
+            .unwrap();
+        assert_eq!(newest[0].record.seq, 2);
+
+        let original = fs::read_to_string(&path).unwrap();
+        fs::write(&path, original.replace("\"exit_status\":1", "\"exit_status\":0")).unwrap();
+        let verification = log.verify().unwrap();
+        assert!(!verification.valid);
+        assert_eq!(verification.first_invalid_line, Some(2));
+
+        let without_first = original.lines().skip(1).collect::<Vec<_>>().join("\n");
+        fs::write(&path, without_first).unwrap();
+        assert_eq!(log.verify().unwrap().first_invalid_line, Some(1));
+    }
+
+    #[test]
+    fn anchor_detects_truncation() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("audit.jsonl");
+        let anchor = Arc::new(MemoryAnchor::default());
+        let log = AuditLog::open(path.clone(), anchor.clone());
+        for subcommand in ["list", "mount", "unmount"] {
+            log.append(record(subcommand, 0)).unwrap();
+        }
+        assert!(log.verify().unwrap().valid);
+
+        // Dropping the newest entry leaves a valid chain behind
+        let original = fs::read_to_string(&path).unwrap();
+        let truncated = original.lines().take(2).collect::<Vec<_>>().join("\n") + "\n";
+        fs::write(&path, &truncated).unwrap();
+        let verification = log.verify().unwrap();
+        assert!(!verification.valid);
+        assert!(verification.message.unwrap().contains("entries were removed"));
+
+        // Entries appended after reopening still do not hide the gap
+        let log = AuditLog::open(path.clone(), anchor.clone());
+        log.append(record("stop", 0)).unwrap();
+        assert_eq!(log.verify().unwrap().first_invalid_line, Some(3));
+
+        fs::write(&path, "").unwrap();
+        assert!(!log.verify().unwrap().valid, "an emptied log is detected");
+    }
+}
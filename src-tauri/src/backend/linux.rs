@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde_json::Value;
+use crate::commands::{Disk, DiskListResult, DiskType, Partition};
+use super::{BlockingProcess, DiskBackend, PartitionDetails};
+
+const LSBLK_COLUMNS: &str = "NAME,SIZE,TYPE,FSTYPE,LABEL,UUID,MODEL,TRAN,RM,HOTPLUG";
+
+/// lsblk for enumeration and filesystem identification, /proc/self/mountinfo
+/// for system mounts and /sys/block for the watcher's disk count
+pub struct LinuxBackend;
+
+impl DiskBackend for LinuxBackend {
+    fn enumerate(
+        &self,
+        _cli_list: &dyn Fn() -> Result<DiskListResult, String>,
+    ) -> Result<DiskListResult, String> {
+        parse_lsblk_disks(&run_lsblk()?)
+    }
+
+    fn system_mounts(&self) -> Vec<(String, String)> {
+        std::fs::read_to_string("/proc/self/mountinfo")
+            .map(|text| parse_mountinfo(&text))
+            .unwrap_or_default()
+    }
+
+    fn partition_details(&self) -> HashMap<String, PartitionDetails> {
+        run_lsblk()
+            .ok()
+            .and_then(|text| parse_lsblk_details(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // Desktop automounters mount under /run/media or /media
+        ["/dev", "/run/media", "/media"]
+            .iter()
+            .map(PathBuf::from)
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    fn is_disk_event(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy();
+        if path_str.starts_with("/run/media/") || path_str.starts_with("/media/") {
+            return true;
+        }
+        let name = path_str.strip_prefix("/dev/").unwrap_or("");
+        ["sd", "vd", "nvme", "mmcblk"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    }
+
+    fn disk_count(&self) -> usize {
+        std::fs::read_dir("/sys/block")
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| is_physical_block_device(&e.file_name().to_string_lossy()))
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    fn whole_disk(&self, device_id: &str) -> Option<String> {
+        whole_disk_of(device_id)
+    }
+
+    fn blocking_processes(&self, mount_point: &Path) -> Vec<BlockingProcess> {
+        scan_proc(Path::new("/proc"), mount_point)
+    }
+}
+
+/// Walk /proc/<pid>/{cwd,root,fd/*} for links that resolve under
+/// `mount_point`. Processes owned by other users are skipped silently
+/// because their links are unreadable without privileges.
+fn scan_proc(proc_root: &Path, mount_point: &Path) -> Vec<BlockingProcess> {
+    let Ok(entries) = std::fs::read_dir(proc_root) else {
+        return Vec::new();
+    };
+    let mut processes = Vec::new();
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        let process_dir = entry.path();
+        let mut links = vec![process_dir.join("cwd"), process_dir.join("root")];
+        if let Ok(fds) = std::fs::read_dir(process_dir.join("fd")) {
+            links.extend(fds.filter_map(|fd| fd.ok()).map(|fd| fd.path()));
+        }
+
+        let open_path = links
+            .iter()
+            .filter_map(|link| std::fs::read_link(link).ok())
+            .find(|target| target.starts_with(mount_point));
+        if let Some(path) = open_path {
+            let command = std::fs::read_to_string(process_dir.join("comm"))
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_default();
+            processes.push(BlockingProcess {
+                pid,
+                command,
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+    }
+
+    processes.sort_by_key(|process| process.pid);
+    processes
+}
+
+/// Skip virtual block devices that never hold user filesystems
+fn is_physical_block_device(name: &str) -> bool {
+    !["loop", "ram", "zram", "dm-", "md", "sr", "fd", "nbd"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// "sdb1" -> "sdb", "nvme0n1p2" -> "nvme0n1", "mmcblk0p1" -> "mmcblk0".
+/// Mapper targets, loop devices and the like have no whole disk.
+fn whole_disk_of(device_id: &str) -> Option<String> {
+    if device_id.starts_with("nvme") || device_id.starts_with("mmcblk") {
+        // The whole disk already ends in a digit, so partitions use a "p" separator
+        let (disk, partition) = match device_id.rfind('p') {
+            Some(index) if index > 0 => (&device_id[..index], &device_id[index + 1..]),
+            _ => (device_id, ""),
+        };
+        if !partition.is_empty() && partition.chars().all(|c| c.is_ascii_digit()) {
+            return Some(disk.to_string());
+        }
+        return Some(device_id.to_string());
+    }
+    if ["sd", "vd", "hd", "xvd"].iter().any(|prefix| device_id.starts_with(prefix)) {
+        return Some(device_id.trim_end_matches(|c: char| c.is_ascii_digit()).to_string());
+    }
+    None
+}
+
+fn run_lsblk() -> Result<String, String> {
+    let output = Command::new("lsblk")
+        .args(["--json", "--bytes", "--output", LSBLK_COLUMNS])
+        .output()
+        .map_err(|e| format!("Failed to run lsblk: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "lsblk failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn block_devices(text: &str) -> Result<Vec<Value>, String> {
+    let json: Value = serde_json::from_str(text)
+        .map_err(|e| format!("Failed to parse lsblk output: {}", e))?;
+    Ok(json["blockdevices"].as_array().cloned().unwrap_or_default())
+}
+
+fn string_field(device: &Value, key: &str) -> Option<String> {
+    device[key]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// lsblk reports flags as booleans or "0"/"1" depending on its version
+fn flag_field(device: &Value, key: &str) -> bool {
+    match &device[key] {
+        Value::Bool(b) => *b,
+        Value::String(s) => s == "1",
+        Value::Number(n) => n.as_u64() == Some(1),
+        _ => false,
+    }
+}
+
+/// Sizes are numbers with --bytes, but some versions still emit strings
+fn size_field(device: &Value) -> u64 {
+    match &device["size"] {
+        Value::Number(n) => n.as_u64().unwrap_or(0),
+        Value::String(s) => s.parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Decimal units, matching how diskutil reports sizes
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Map lsblk filesystem identifiers to the names the rest of the app expects
+fn filesystem_name(fstype: Option<&str>) -> String {
+    match fstype {
+        Some("vfat") => "FAT32".to_string(),
+        Some(other) => other.to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+fn partition_from(device: &Value) -> Partition {
+    let name = string_field(device, "name").unwrap_or_default();
+    let fstype = string_field(device, "fstype");
+    let encrypted = matches!(fstype.as_deref(), Some("crypto_LUKS") | Some("BitLocker"));
+    Partition {
+        device: format!("/dev/{}", name),
+        size: format_size(size_field(device)),
+        filesystem: filesystem_name(fstype.as_deref()),
+        label: string_field(device, "label"),
+        uuid: string_field(device, "uuid"),
+        encrypted,
+        mounted_by_system: false, // Will be updated after parsing
+        system_mount_point: None,
+        supported: true, // Will be updated after parsing
+        support_note: None,
+    }
+}
+
+fn parse_lsblk_disks(text: &str) -> Result<DiskListResult, String> {
+    let mut disks = Vec::new();
+
+    for device in block_devices(text)? {
+        let name = string_field(&device, "name").unwrap_or_default();
+        if device["type"].as_str() != Some("disk") || !is_physical_block_device(&name) {
+            continue;
+        }
+
+        let children = device["children"].as_array().cloned().unwrap_or_default();
+        let mut partitions: Vec<Partition> = children
+            .iter()
+            .filter(|child| child["type"].as_str() == Some("part"))
+            .map(partition_from)
+            .collect();
+
+        // Whole-disk filesystem with no partition table (issue #83)
+        if partitions.is_empty() && string_field(&device, "fstype").is_some() {
+            partitions.push(partition_from(&device));
+        }
+        if partitions.is_empty() {
+            continue;
+        }
+
+        let is_external = string_field(&device, "tran").as_deref() == Some("usb")
+            || flag_field(&device, "rm")
+            || flag_field(&device, "hotplug");
+        disks.push(Disk {
+            device: format!("/dev/{}", name),
+            size: format_size(size_field(&device)),
+            model: string_field(&device, "model"),
+            is_external,
+            disk_type: DiskType::Normal,
+            partitions,
+        });
+    }
+
+    Ok(DiskListResult {
+        disks,
+        has_supported_partitions: false, // Will be updated after filesystem check
+        used_admin_mode: false,          // Will be updated by caller
+    })
+}
+
+fn parse_lsblk_details(text: &str) -> Result<HashMap<String, PartitionDetails>, String> {
+    fn collect(devices: &[Value], map: &mut HashMap<String, PartitionDetails>) {
+        for device in devices {
+            if let Some(name) = string_field(device, "name") {
+                map.insert(
+                    name,
+                    PartitionDetails {
+                        filesystem: string_field(device, "fstype")
+                            .map(|fstype| filesystem_name(Some(&fstype))),
+                        uuid: string_field(device, "uuid"),
+                    },
+                );
+            }
+            if let Some(children) = device["children"].as_array() {
+                collect(children, map);
+            }
+        }
+    }
+
+    let mut map = HashMap::new();
+    collect(&block_devices(text)?, &mut map);
+    Ok(map)
+}
+
+/// Undo the octal escaping mountinfo applies to spaces, tabs and backslashes
+fn unescape_mountinfo(field: &str) -> String {
+    let mut result = String::new();
+    let mut chars = field.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let digits: String = chars.clone().take(3).collect();
+            if digits.len() == 3 {
+                if let Ok(code) = u8::from_str_radix(&digits, 8) {
+                    result.push(code as char);
+                    for _ in 0..3 {
+                        chars.next();
+                    }
+                    continue;
+                }
+            }
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Format: "36 35 98:0 / /mnt/data rw,noatime master:1 - ext4 /dev/sdb1 rw"
+fn parse_mountinfo(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (before, after) = line.split_once(" - ")?;
+            let mount_point = before.split_whitespace().nth(4)?;
+            let source = after.split_whitespace().nth(1)?;
+            source
+                .starts_with("/dev/")
+                .then(|| (unescape_mountinfo(source), unescape_mountinfo(mount_point)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LSBLK_OUTPUT: &str = r#"{
+   "blockdevices": [
+      {"name":"loop0", "size":4096, "type":"loop", "fstype":"squashfs", "label":null, "uuid":null, "model":null, "tran":null, "rm":false, "hotplug":false},
+      {"name":"nvme0n1", "size":512110190592, "type":"disk", "fstype":null, "label":null, "uuid":null, "model":"Samsung SSD 980", "tran":"nvme", "rm":false, "hotplug":false,
+         "children": [
+            {"name":"nvme0n1p1", "size":536870912, "type":"part", "fstype":"vfat", "label":null, "uuid":"4A1B-2C3D", "model":null, "tran":null, "rm":false, "hotplug":false},
+            {"name":"nvme0n1p2", "size":511571132416, "type":"part", "fstype":"crypto_LUKS", "label":null, "uuid":"0d5f3a2e-7b41-4c2a-9d11-2f6e8c1a9b70", "model":null, "tran":null, "rm":false, "hotplug":false,
+               "children": [
+                  {"name":"luks-0d5f", "size":511554355200, "type":"crypt", "fstype":"btrfs", "label":"root", "uuid":"8c1e0f2a-0000-4000-8000-000000000001", "model":null, "tran":null, "rm":false, "hotplug":false}
+               ]
+            }
+         ]
+      },
+      {"name":"sdb", "size":"64023257088", "type":"disk", "fstype":"ext4", "label":"backup", "uuid":"6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60", "model":"Flash Disk", "tran":"usb", "rm":"1", "hotplug":"1"}
+   ]
+}"#;
+
+    #[test]
+    fn lsblk_disks_become_partitions_with_uuids() {
+        let result = parse_lsblk_disks(LSBLK_OUTPUT).expect("lsblk output should parse");
+        assert_eq!(result.disks.len(), 2, "loop devices are skipped");
+
+        let nvme = &result.disks[0];
+        assert_eq!(nvme.device, "/dev/nvme0n1");
+        assert!(!nvme.is_external);
+        assert_eq!(nvme.size, "512.1 GB");
+        let devices: Vec<&str> = nvme.partitions.iter().map(|p| p.device.as_str()).collect();
+        assert_eq!(devices, vec!["/dev/nvme0n1p1", "/dev/nvme0n1p2"]);
+        assert_eq!(nvme.partitions[0].filesystem, "FAT32");
+        assert!(nvme.partitions[1].encrypted);
+        assert_eq!(
+            nvme.partitions[1].uuid.as_deref(),
+            Some("0d5f3a2e-7b41-4c2a-9d11-2f6e8c1a9b70")
+        );
+
+        // Whole-disk filesystem with string-typed lsblk fields
+        let usb = &result.disks[1];
+        assert!(usb.is_external);
+        assert_eq!(usb.size, "64.0 GB");
+        assert_eq!(usb.partitions.len(), 1);
+        assert_eq!(usb.partitions[0].device, "/dev/sdb");
+        assert_eq!(usb.partitions[0].label.as_deref(), Some("backup"));
+    }
+
+    #[test]
+    fn lsblk_details_include_nested_devices() {
+        let details = parse_lsblk_details(LSBLK_OUTPUT).unwrap();
+        assert_eq!(details["nvme0n1p1"].filesystem.as_deref(), Some("FAT32"));
+        assert_eq!(details["luks-0d5f"].filesystem.as_deref(), Some("btrfs"));
+        assert_eq!(
+            details["sdb"].uuid.as_deref(),
+            Some("6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60")
+        );
+    }
+
+    #[test]
+    fn mountinfo_reports_device_mounts_with_unescaped_paths() {
+        let mountinfo = "\
+22 1 0:21 / /proc rw,nosuid shared:12 - proc proc rw
+28 1 259:2 / / rw,relatime shared:1 - btrfs /dev/mapper/luks-0d5f rw
+96 28 8:16 / /run/media/user/My\\040Backup rw,nosuid shared:50 - ext4 /dev/sdb rw
+";
+        assert_eq!(
+            parse_mountinfo(mountinfo),
+            vec![
+                ("/dev/mapper/luks-0d5f".to_string(), "/".to_string()),
+                ("/dev/sdb".to_string(), "/run/media/user/My Backup".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn proc_scan_finds_open_files_under_the_mount_point() {
+        let directory = tempfile::tempdir().unwrap();
+        let mount_point = directory.path().canonicalize().unwrap();
+        let open_path = mount_point.join("notes.md");
+        let _file = std::fs::File::create(&open_path).unwrap();
+
+        let processes = scan_proc(Path::new("/proc"), &mount_point);
+        let own = processes
+            .iter()
+            .find(|process| process.pid == std::process::id())
+            .expect("the test process holds a file open");
+        assert_eq!(own.path, open_path.to_string_lossy());
+        assert!(!own.command.is_empty());
+    }
+
+    #[test]
+    fn virtual_block_devices_are_not_counted() {
+        assert!(is_physical_block_device("sda"));
+        assert!(is_physical_block_device("nvme0n1"));
+        assert!(!is_physical_block_device("loop3"));
+        assert!(!is_physical_block_device("dm-0"));
+    }
+
+    #[test]
+    fn partitions_map_to_their_whole_disk() {
+        assert_eq!(whole_disk_of("sdb1").as_deref(), Some("sdb"));
+        assert_eq!(whole_disk_of("sdb").as_deref(), Some("sdb"));
+        assert_eq!(whole_disk_of("nvme0n1p2").as_deref(), Some("nvme0n1"));
+        assert_eq!(whole_disk_of("nvme0n1").as_deref(), Some("nvme0n1"));
+        assert_eq!(whole_disk_of("mmcblk0p1").as_deref(), Some("mmcblk0"));
+        assert_eq!(whole_disk_of("luks-6f1c2a4e").as_deref(), None);
+        assert_eq!(whole_disk_of("loop3").as_deref(), None);
+    }
+}
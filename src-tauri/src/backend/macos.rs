@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::cache;
+use crate::commands::DiskListResult;
+use super::{BlockingProcess, DiskBackend, PartitionDetails};
+
+/// `anylinuxfs list` for enumeration, `mount` for system mounts and a single
+/// `diskutil info -all` for filesystem personalities and UUIDs
+pub struct MacosBackend;
+
+impl DiskBackend for MacosBackend {
+    fn enumerate(
+        &self,
+        cli_list: &dyn Fn() -> Result<DiskListResult, String>,
+    ) -> Result<DiskListResult, String> {
+        cli_list()
+    }
+
+    fn system_mounts(&self) -> Vec<(String, String)> {
+        let mut mounts = Vec::new();
+
+        // Use cached mount output to avoid redundant process spawning
+        if let Some(output) = cache::get_mount_output() {
+            let mount_output = String::from_utf8_lossy(&output.stdout);
+
+            for line in mount_output.lines() {
+                // Format: /dev/disk6s1 on /Volumes/NO NAME (msdos, ...)
+                let parts: Vec<&str> = line.split(" on ").collect();
+                if parts.len() >= 2 {
+                    let device = parts[0].to_string();
+                    // Extract mount point (everything before the parenthesis)
+                    let rest = parts[1..].join(" on ");
+                    if let Some(paren_pos) = rest.find(" (") {
+                        let mount_point = rest[..paren_pos].to_string();
+                        mounts.push((device, mount_point));
+                    }
+                }
+            }
+        }
+
+        mounts
+    }
+
+    fn partition_details(&self) -> HashMap<String, PartitionDetails> {
+        let output = match Command::new("diskutil").args(["info", "-all"]).output() {
+            Ok(o) => o,
+            Err(_) => return HashMap::new(),
+        };
+        parse_diskutil_info(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // /Volumes for mount/unmount events, /dev for physical disk
+        // connect/disconnect of Linux-only disks that never reach /Volumes
+        vec![PathBuf::from("/Volumes"), PathBuf::from("/dev")]
+    }
+
+    fn is_disk_event(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy();
+        // Match /Volumes/* or /dev/disk*
+        path_str.starts_with("/Volumes/")
+            || (path_str.starts_with("/dev/disk") && !path_str.contains("s"))
+    }
+
+    fn disk_count(&self) -> usize {
+        std::fs::read_dir("/dev")
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| {
+                        let name = e.file_name();
+                        let name_str = name.to_string_lossy();
+                        // Match disk0, disk1, etc. but not disk0s1 (partitions)
+                        name_str.starts_with("disk") &&
+                        name_str[4..].chars().all(|c| c.is_ascii_digit())
+                    })
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    fn whole_disk(&self, device_id: &str) -> Option<String> {
+        whole_disk_of(device_id)
+    }
+
+    fn blocking_processes(&self, mount_point: &Path) -> Vec<BlockingProcess> {
+        // Given a mount point, lsof reports every open file on that filesystem.
+        // -b avoids kernel calls that can hang on an unresponsive NFS server.
+        let output = match Command::new("lsof")
+            .args(["-n", "-b", "-w", "-F", "pcn", "--"])
+            .arg(mount_point)
+            .output()
+        {
+            Ok(o) => o,
+            Err(e) => {
+                log::warn!("Failed to run lsof: {}", e);
+                return Vec::new();
+            }
+        };
+        parse_lsof_output(&String::from_utf8_lossy(&output.stdout), mount_point)
+    }
+}
+
+/// "disk4s1" and APFS volumes like "disk3s1s1" belong to "disk4" / "disk3"
+fn whole_disk_of(device_id: &str) -> Option<String> {
+    let rest = device_id.strip_prefix("disk")?;
+    let number_len = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    if number_len == 0 {
+        return None;
+    }
+    let suffix = &rest[number_len..];
+    if !suffix.is_empty() && !suffix.starts_with('s') {
+        return None;
+    }
+    Some(format!("disk{}", &rest[..number_len]))
+}
+
+/// Parse `lsof -F pcn` output: a `p<pid>` line starts each process, followed
+/// by `c<command>` and one `n<path>` line per open file. Keeps the first path
+/// under `mount_point` for each process.
+fn parse_lsof_output(text: &str, mount_point: &Path) -> Vec<BlockingProcess> {
+    let mut processes: Vec<BlockingProcess> = Vec::new();
+    let mut pid = None;
+    let mut command = String::new();
+
+    for line in text.lines() {
+        let (tag, value) = match line.char_indices().nth(1) {
+            Some((index, _)) => line.split_at(index),
+            None => continue,
+        };
+        match tag {
+            "p" => {
+                pid = value.parse::<u32>().ok();
+                command.clear();
+            }
+            "c" => command = value.to_string(),
+            "n" => {
+                let Some(pid) = pid else { continue };
+                if !Path::new(value).starts_with(mount_point)
+                    || processes.iter().any(|p| p.pid == pid)
+                {
+                    continue;
+                }
+                processes.push(BlockingProcess {
+                    pid,
+                    command: command.clone(),
+                    path: value.to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+    processes
+}
+
+fn parse_diskutil_info(text: &str) -> HashMap<String, PartitionDetails> {
+    let mut map = HashMap::new();
+    for block in text.split("**********") {
+        let mut device_id = None;
+        let mut filesystem = None;
+        let mut uuid = None;
+        for line in block.lines() {
+            if line.contains("Device Identifier:") {
+                device_id = line.split(':').nth(1).map(|s| s.trim().to_string());
+            } else if line.contains("File System Personality:") {
+                filesystem = line.split(':').nth(1).map(|s| s.trim().to_string());
+            } else if line.contains("Disk / Partition UUID:") {
+                uuid = line.split(':').nth(1).map(|s| s.trim().to_string());
+            }
+        }
+        if let Some(id) = device_id {
+            map.insert(id, PartitionDetails { filesystem, uuid });
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitions_and_apfs_volumes_map_to_their_whole_disk() {
+        assert_eq!(whole_disk_of("disk4").as_deref(), Some("disk4"));
+        assert_eq!(whole_disk_of("disk4s1").as_deref(), Some("disk4"));
+        assert_eq!(whole_disk_of("disk12s3s1").as_deref(), Some("disk12"));
+        assert_eq!(whole_disk_of("disk").as_deref(), None);
+        assert_eq!(whole_disk_of("vg0").as_deref(), None);
+    }
+
+    #[test]
+    fn lsof_output_yields_one_entry_per_process() {
+        let output = "\
+p812
+cFinder
+n/Volumes/backup
+p4410
+czsh
+n/Volumes/backup/projects
+p4502
+cvim
+n/dev/ttys003
+n/Volumes/backup/projects/.notes.md.swp
+n/Volumes/backup/projects/notes.md
+";
+        let processes = parse_lsof_output(output, Path::new("/Volumes/backup"));
+        assert_eq!(
+            processes,
+            vec![
+                BlockingProcess {
+                    pid: 812,
+                    command: "Finder".to_string(),
+                    path: "/Volumes/backup".to_string(),
+                },
+                BlockingProcess {
+                    pid: 4410,
+                    command: "zsh".to_string(),
+                    path: "/Volumes/backup/projects".to_string(),
+                },
+                BlockingProcess {
+                    pid: 4502,
+                    command: "vim".to_string(),
+                    path: "/Volumes/backup/projects/.notes.md.swp".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::commands::DiskListResult;
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod linux;
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+mod macos;
+
+/// Filesystem and UUID the host reports for a partition
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartitionDetails {
+    pub filesystem: Option<String>,
+    pub uuid: Option<String>,
+}
+
+/// A process holding a file or working directory open on a mounted volume
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockingProcess {
+    pub pid: u32,
+    pub command: String,
+    pub path: String,
+}
+
+/// Host platform disk discovery. The macOS backend combines `anylinuxfs list`
+/// with `diskutil` and `mount`; the Linux backend reads lsblk, sysfs and
+/// mountinfo so the rest of the app can run on Linux workstations and CI.
+pub trait DiskBackend: Send + Sync {
+    /// Disks and their partitions. `cli_list` runs and parses `anylinuxfs list`
+    /// (with the caller's elevation policy) for backends that rely on the CLI's view.
+    fn enumerate(
+        &self,
+        cli_list: &dyn Fn() -> Result<DiskListResult, String>,
+    ) -> Result<DiskListResult, String>;
+
+    /// (device, mount point) pairs for filesystems mounted by the host OS
+    fn system_mounts(&self) -> Vec<(String, String)>;
+
+    /// Filesystem identification and UUIDs keyed by device identifier
+    /// without the /dev/ prefix (e.g. "disk4s1", "sdb1")
+    fn partition_details(&self) -> HashMap<String, PartitionDetails>;
+
+    /// Directories the disk watcher observes for attach/detach events
+    fn watch_paths(&self) -> Vec<PathBuf>;
+
+    /// Whether a created/removed path in a watched directory is a disk change
+    fn is_disk_event(&self, path: &Path) -> bool;
+
+    /// Number of whole disks, used by the watcher's polling fallback
+    fn disk_count(&self) -> usize;
+
+    /// Whole disk a device identifier belongs to ("disk4s1" -> "disk4"), or
+    /// None for devices the watcher ignores (loop devices, mapper targets)
+    fn whole_disk(&self, device_id: &str) -> Option<String>;
+
+    /// Processes with open files under `mount_point`, one entry per process
+    fn blocking_processes(&self, mount_point: &Path) -> Vec<BlockingProcess>;
+}
+
+/// Map of partition UUIDs (uppercased) to device paths
+pub fn uuids_by_device(details: &HashMap<String, PartitionDetails>) -> HashMap<String, String> {
+    details
+        .iter()
+        .filter_map(|(device_id, details)| {
+            details
+                .uuid
+                .as_ref()
+                .filter(|uuid| !uuid.is_empty())
+                .map(|uuid| (uuid.to_uppercase(), format!("/dev/{}", device_id)))
+        })
+        .collect()
+}
+
+/// Backend for the platform the app is running on
+pub fn current() -> &'static dyn DiskBackend {
+    #[cfg(target_os = "linux")]
+    {
+        &linux::LinuxBackend
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        &macos::MacosBackend
+    }
+}
@@ -1,13 +1,77 @@
-use std::process::{Command, Stdio};
-use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::io::{Read, Write};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use std::sync::OnceLock;
+use crate::askpass::{AskpassBackend, AskpassSession, ASKPASS_PREFIX};
+use crate::audit::{AuditMethod, AuditOutcome, AuditScope, AuditSecret};
 use crate::elevation::{
     execute_in_terminal, ElevationMode, ElevationState, TerminalExecutionError,
     TerminalInteraction,
 };
+use crate::polkit::{self, PKEXEC_DISMISSED, PKEXEC_NOT_AUTHORIZED};
+use crate::timeouts::{is_transient_error, Operation, OperationPolicies, OperationPolicy};
+
+/// Disk unlock material for the CLI. Passphrases travel in `ALFS_PASSPHRASE`;
+/// keyfile bytes are written to the CLI's stdin so they never touch disk.
+#[derive(Clone, Copy)]
+pub enum CliSecret<'a> {
+    Passphrase(&'a str),
+    Keyfile(&'a [u8]),
+}
+
+impl<'a> CliSecret<'a> {
+    fn passphrase(&self) -> Option<&'a str> {
+        match *self {
+            Self::Passphrase(passphrase) => Some(passphrase),
+            Self::Keyfile(_) => None,
+        }
+    }
+
+    fn audit(&self) -> AuditSecret {
+        match self {
+            Self::Passphrase(_) => AuditSecret::Passphrase,
+            Self::Keyfile(_) => AuditSecret::Keyfile,
+        }
+    }
+
+    /// Set up `cmd` for this secret; call `feed` on the spawned child
+    fn apply(&self, cmd: &mut Command) {
+        if let Some(pass) = self.passphrase() {
+            cmd.env("ALFS_PASSPHRASE", pass);
+        }
+    }
+
+    /// Stream keyfile bytes into the child's stdin from a thread, so a large
+    /// key cannot deadlock against unread output. Closing the pipe marks the
+    /// end of the key.
+    fn feed(&self, child: &mut Child) {
+        if let Self::Keyfile(bytes) = self {
+            if let Some(mut stdin) = child.stdin.take() {
+                let bytes = bytes.to_vec();
+                std::thread::spawn(move || {
+                    let _ = stdin.write_all(&bytes);
+                });
+            }
+        }
+    }
+}
+
+/// Stands in for a missing passphrase so the CLI fails on an encrypted
+/// partition instead of waiting for one on stdin
+const PROBE_PASSPHRASE: &str = "##PROBE##";
+
+/// The passphrase a mount hands the CLI in `mode`. Interactive Terminal
+/// prompts there, and pkexec would turn the probe into a key file, so
+/// neither is given one the user did not enter.
+pub fn mount_passphrase(mode: ElevationMode, passphrase: Option<String>) -> Option<String> {
+    match mode {
+        ElevationMode::InteractiveTerminal => None,
+        ElevationMode::Polkit => passphrase,
+        _ => Some(passphrase.unwrap_or_else(|| PROBE_PASSPHRASE.to_string())),
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum CommandExecutionError {
@@ -18,17 +82,55 @@ pub enum CommandExecutionError {
 }
 
 impl CommandExecutionError {
+    /// Whether retrying may succeed; never true once the user was involved
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Failed(message) if is_transient_error(message))
+    }
+
     pub fn message(&self) -> String {
         match self {
             Self::InteractionRequired => "ALFS_SILENT_AUTH_EXPIRED".to_string(),
-            Self::Cancelled => "Interactive Terminal operation was cancelled".to_string(),
-            Self::TimedOut => "Interactive Terminal operation timed out".to_string(),
+            Self::Cancelled => "Privileged operation was cancelled".to_string(),
+            Self::TimedOut => "Privileged operation timed out".to_string(),
             Self::Failed(message) => message.clone(),
         }
     }
 }
 
 /// Sanitize error output to avoid exposing sensitive system details
+impl std::fmt::Display for CommandExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+/// How a sudo run authenticates and how long each stage may take
+#[derive(Clone, Copy)]
+struct SudoPolicy {
+    mode: ElevationMode,
+    askpass: AskpassBackend,
+    /// Limit for sudo with cached credentials or Touch ID
+    native_timeout: Duration,
+    /// Limit for the password dialog and the command together
+    timeout: Duration,
+}
+
+impl SudoPolicy {
+    fn new(
+        mode: ElevationMode,
+        askpass: AskpassBackend,
+        operations: &OperationPolicies,
+        policy: OperationPolicy,
+    ) -> Self {
+        Self {
+            mode,
+            askpass,
+            native_timeout: operations.native_authentication(),
+            timeout: policy.timeout(),
+        }
+    }
+}
+
 /// Logs the full error for debugging but returns a user-friendly message
 fn sanitize_error(stdout: &str, stderr: &str) -> String {
     // Log full details for debugging
@@ -162,55 +264,110 @@ pub fn get_version() -> Option<String> {
     }
 }
 
+/// A result type of a read-only CLI query, parsed from the CLI's
+/// human-oriented text output. The CLI has no documented machine-readable
+/// output; a JSON parser belongs next to each `from_text` once it publishes a
+/// schema.
+pub trait CliOutput: Sized {
+    fn from_text(output: &str) -> Result<Self, String>;
+}
+
+/// Run an unprivileged query and parse its output
+pub fn query<T: CliOutput>(args: &[&str]) -> Result<T, String> {
+    T::from_text(&execute_command(args, None, None, false)?)
+}
+
+/// Cached `anylinuxfs --help` output, used to detect optional subcommands
+static HELP_OUTPUT: OnceLock<String> = OnceLock::new();
+
+/// Cached `anylinuxfs mount --help` output, used to detect optional flags
+static MOUNT_HELP_OUTPUT: OnceLock<String> = OnceLock::new();
+
+/// Whether the installed CLI's `mount` subcommand accepts `flag`
+pub fn supports_mount_flag(flag: &str) -> bool {
+    let help = MOUNT_HELP_OUTPUT.get_or_init(|| {
+        get_anylinuxfs_path()
+            .and_then(|cli_path| Command::new(cli_path).args(["mount", "--help"]).output().ok())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    });
+    help.split(|c: char| c.is_whitespace() || c == ',' || c == '=')
+        .any(|word| word == flag)
+}
+
+/// Whether the installed CLI lists `name` among its subcommands
+pub fn supports_subcommand(name: &str) -> bool {
+    let help = HELP_OUTPUT.get_or_init(|| {
+        get_anylinuxfs_path()
+            .and_then(|cli_path| Command::new(cli_path).arg("--help").output().ok())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    });
+    help.lines()
+        .any(|line| line.split_whitespace().next() == Some(name))
+}
+
 /// Get the path to the anylinuxfs CLI
 pub fn get_path() -> Option<&'static Path> {
     get_anylinuxfs_path().map(|p| p.as_path())
 }
 
-/// Run `anylinuxfs status` (no sudo) and return the raw output.
-/// Returns empty string when nothing is mounted, one line per mount otherwise.
-/// Format: "/dev/disk4s1 on /Volumes/ntfs-test (ntfs, uid=501, ...) VM[cpus: 1, ram: 512 MiB]"
-pub fn get_status() -> Option<String> {
-    let cli_path = get_anylinuxfs_path()?;
-    let output = Command::new(cli_path)
-        .arg("status")
-        .stdin(Stdio::piped())
-        .output()
-        .ok()?;
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
-    }
-}
-
-/// Execute an anylinuxfs command with optional sudo elevation
+/// Execute an anylinuxfs command, elevated with sudo when `elevation` is given
 ///
+/// The elevated path follows the configured askpass backend and the Setup
+/// operation policy rather than built-in defaults.
 /// When `silent` is true and sudo credentials have expired, returns an
 /// `AUTH_EXPIRED` error instead of showing an interactive password dialog.
 /// This is used for automatic background refreshes (e.g. disk-watcher events)
 /// so the user isn't bombarded with auth dialogs while away from the computer.
-pub fn execute_command(args: &[&str], needs_sudo: bool, passphrase: Option<&str>, silent: bool) -> Result<String, String> {
-    if needs_sudo {
-        execute_with_sudo(args, passphrase, silent)
-    } else {
-        execute_direct(args, passphrase)
+pub fn execute_command(
+    args: &[&str],
+    elevation: Option<&ElevationState>,
+    passphrase: Option<&str>,
+    silent: bool,
+) -> Result<String, String> {
+    let secret = passphrase.map(CliSecret::Passphrase);
+    match elevation {
+        Some(elevation_state) => {
+            let operations = elevation_state.operation_policies();
+            let sudo = SudoPolicy::new(
+                ElevationMode::Native,
+                elevation_state.askpass_backend(),
+                &operations,
+                operations.get(Operation::Setup),
+            );
+            execute_with_sudo(args, secret, silent, sudo)
+        }
+        None => execute_direct(args, secret),
     }
 }
 
 /// Execute an anylinuxfs command using the Rust-owned elevation policy.
+/// `policy` limits a single privileged attempt; retries are up to the caller.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_command_with_elevation(
     args: &[&str],
     needs_sudo: bool,
-    passphrase: Option<&str>,
+    secret: Option<CliSecret<'_>>,
     silent: bool,
     elevation_mode: ElevationMode,
     elevation_state: &ElevationState,
     terminal_interaction: TerminalInteraction,
+    policy: OperationPolicy,
 ) -> Result<String, CommandExecutionError> {
     if needs_sudo {
         match elevation_mode {
-            ElevationMode::Native => execute_with_sudo(args, passphrase, silent)
+            ElevationMode::Native => execute_with_sudo(
+                args,
+                secret,
+                silent,
+                SudoPolicy::new(
+                    ElevationMode::Native,
+                    elevation_state.askpass_backend(),
+                    &elevation_state.operation_policies(),
+                    policy,
+                ),
+            )
                 .map_err(|error| {
                     if error == "ALFS_SILENT_AUTH_EXPIRED" {
                         CommandExecutionError::InteractionRequired
@@ -218,6 +375,66 @@ pub fn execute_command_with_elevation(
                         CommandExecutionError::Failed(error)
                     }
                 }),
+            ElevationMode::Helper => {
+                let cli_path = get_anylinuxfs_path().ok_or_else(|| {
+                    CommandExecutionError::Failed(
+                        "anylinuxfs CLI not found in PATH or standard locations".to_string(),
+                    )
+                })?;
+                let (passphrase, keyfile) = match secret {
+                    Some(CliSecret::Passphrase(passphrase)) => (Some(passphrase), None),
+                    Some(CliSecret::Keyfile(bytes)) => (None, Some(bytes)),
+                    None => (None, None),
+                };
+                let mut audit = AuditScope::begin(
+                    ElevationMode::Helper,
+                    AuditMethod::Helper,
+                    cli_path,
+                    args,
+                    secret.map(|secret| secret.audit()),
+                    passphrase,
+                );
+                let output = elevation_state
+                    .helper()
+                    .run(
+                        cli_path,
+                        args,
+                        passphrase,
+                        keyfile,
+                        elevation_state.askpass_backend(),
+                        elevation_state.helper_ownership_risk_accepted(),
+                        silent,
+                        policy.timeout(),
+                    )
+                    .map_err(|error| {
+                        if error == "ALFS_SILENT_AUTH_EXPIRED" {
+                            audit.outcome(AuditOutcome::AuthenticationRequired);
+                            CommandExecutionError::InteractionRequired
+                        } else {
+                            CommandExecutionError::Failed(error)
+                        }
+                    })?;
+                audit.exit_status(Some(output.status));
+                if output.status == 0 {
+                    Ok(output.stdout)
+                } else {
+                    Err(CommandExecutionError::Failed(sanitize_error(
+                        &output.stdout,
+                        &output.stderr,
+                    )))
+                }
+            }
+            ElevationMode::Polkit => execute_with_pkexec(
+                get_anylinuxfs_path().ok_or_else(|| {
+                    CommandExecutionError::Failed(
+                        "anylinuxfs CLI not found in PATH or standard locations".to_string(),
+                    )
+                })?,
+                args,
+                secret,
+                silent,
+                policy.timeout(),
+            ),
             ElevationMode::InteractiveTerminal => execute_in_terminal(
                 elevation_state,
                 get_anylinuxfs_path().ok_or_else(|| {
@@ -228,6 +445,7 @@ pub fn execute_command_with_elevation(
                 args,
                 silent,
                 terminal_interaction,
+                policy.timeout(),
             )
             .map_err(|error| match error {
                 TerminalExecutionError::InteractionRequired => {
@@ -242,11 +460,11 @@ pub fn execute_command_with_elevation(
             }),
         }
     } else {
-        execute_direct(args, passphrase).map_err(CommandExecutionError::Failed)
+        execute_direct(args, secret).map_err(CommandExecutionError::Failed)
     }
 }
 
-fn execute_direct(args: &[&str], passphrase: Option<&str>) -> Result<String, String> {
+fn execute_direct(args: &[&str], secret: Option<CliSecret<'_>>) -> Result<String, String> {
     let cli_path = get_anylinuxfs_path()
         .ok_or_else(|| "anylinuxfs CLI not found in PATH or standard locations".to_string())?;
 
@@ -255,11 +473,19 @@ fn execute_direct(args: &[&str], passphrase: Option<&str>) -> Result<String, Str
     // Use piped stdin instead of null - libkrun's epoll fails with /dev/null
     cmd.stdin(Stdio::piped());
 
-    if let Some(pass) = passphrase {
-        cmd.env("ALFS_PASSPHRASE", pass);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    if let Some(ref secret) = secret {
+        secret.apply(&mut cmd);
     }
 
-    let output = cmd.output().map_err(|e| format!("Failed to execute command: {}", e))?;
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to execute command: {}", e))?;
+    if let Some(ref secret) = secret {
+        secret.feed(&mut child);
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -272,9 +498,22 @@ fn execute_direct(args: &[&str], passphrase: Option<&str>) -> Result<String, Str
 
 /// Try sudo via native PAM auth (handles cached credentials, Touch ID, Apple Watch)
 /// Returns None if auth fails/unavailable, falling back to askpass dialog
-fn try_sudo_native(cli_path: &Path, args: &[&str], passphrase: Option<&str>) -> Option<Result<String, String>> {
+fn try_sudo_native(
+    cli_path: &Path,
+    args: &[&str],
+    secret: Option<CliSecret<'_>>,
+    sudo: &SudoPolicy,
+) -> Option<Result<String, String>> {
+    let mut audit = AuditScope::begin(
+        sudo.mode,
+        AuditMethod::Sudo,
+        cli_path,
+        args,
+        secret.map(|secret| secret.audit()),
+        secret.and_then(|secret| secret.passphrase()),
+    );
     let cli_path_str = cli_path.to_string_lossy();
-    let mut sudo_args: Vec<&str> = if passphrase.is_some() {
+    let mut sudo_args: Vec<&str> = if secret.and_then(|secret| secret.passphrase()).is_some() {
         vec!["--preserve-env=ALFS_PASSPHRASE", "--", &*cli_path_str]
     } else {
         vec!["--", &*cli_path_str]
@@ -287,14 +526,17 @@ fn try_sudo_native(cli_path: &Path, args: &[&str], passphrase: Option<&str>) ->
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    if let Some(pass) = passphrase {
-        cmd.env("ALFS_PASSPHRASE", pass);
+    if let Some(ref secret) = secret {
+        secret.apply(&mut cmd);
     }
 
     let mut child = cmd.spawn().ok()?;
+    if let Some(ref secret) = secret {
+        secret.feed(&mut child);
+    }
 
-    // Native auth (cached creds, biometric) is fast — give it 10 seconds
-    let timeout = Duration::from_secs(10);
+    // Native auth (cached creds, biometric) is fast
+    let timeout = sudo.native_timeout;
     let start = Instant::now();
 
     loop {
@@ -308,6 +550,7 @@ fn try_sudo_native(cli_path: &Path, args: &[&str], passphrase: Option<&str>) ->
                 if let Some(ref mut err) = child.stderr {
                     let _ = err.read_to_string(&mut stderr);
                 }
+                audit.exit_status(status.code());
 
                 if status.success() {
                     return Some(Ok(stdout));
@@ -318,6 +561,7 @@ fn try_sudo_native(cli_path: &Path, args: &[&str], passphrase: Option<&str>) ->
                     || stderr.contains("no askpass")
                     || stderr.contains("a terminal is required")
                 {
+                    audit.outcome(AuditOutcome::AuthenticationRequired);
                     return None;
                 }
                 // Real error — return it
@@ -327,6 +571,7 @@ fn try_sudo_native(cli_path: &Path, args: &[&str], passphrase: Option<&str>) ->
                 if start.elapsed() > timeout {
                     let _ = child.kill();
                     let _ = child.wait();
+                    audit.outcome(AuditOutcome::TimedOut);
                     return None; // Timeout — fall back to askpass
                 }
                 std::thread::sleep(Duration::from_millis(100));
@@ -336,13 +581,54 @@ fn try_sudo_native(cli_path: &Path, args: &[&str], passphrase: Option<&str>) ->
     }
 }
 
-fn execute_with_sudo(args: &[&str], passphrase: Option<&str>, silent: bool) -> Result<String, String> {
+fn execute_with_sudo(
+    args: &[&str],
+    secret: Option<CliSecret<'_>>,
+    silent: bool,
+    sudo: SudoPolicy,
+) -> Result<String, String> {
     let cli_path = get_anylinuxfs_path()
         .ok_or_else(|| "anylinuxfs CLI not found in PATH or standard locations".to_string())?;
+    sudo_with_fallback(cli_path, args, secret, silent, sudo)
+}
+
+/// Run a system tool (not the anylinuxfs CLI) as root, e.g. to install the
+/// sudoers rule. Uses the same native-then-askpass flow as CLI commands.
+pub fn execute_privileged(
+    program: &Path,
+    args: &[&str],
+    elevation_state: &ElevationState,
+) -> Result<String, String> {
+    let operations = elevation_state.operation_policies();
+    if elevation_state.mode() == ElevationMode::Polkit {
+        return execute_with_pkexec(
+            program,
+            args,
+            None,
+            false,
+            operations.get(Operation::Setup).timeout(),
+        )
+        .map_err(|error| error.message());
+    }
+    let sudo = SudoPolicy::new(
+        elevation_state.mode(),
+        elevation_state.askpass_backend(),
+        &operations,
+        operations.get(Operation::Setup),
+    );
+    sudo_with_fallback(program, args, None, false, sudo)
+}
 
+fn sudo_with_fallback(
+    cli_path: &Path,
+    args: &[&str],
+    secret: Option<CliSecret<'_>>,
+    silent: bool,
+    sudo: SudoPolicy,
+) -> Result<String, String> {
     // Try native PAM auth first (handles cached credentials, Touch ID, Apple Watch)
     // If it fails or is unavailable, fall back to askpass password dialog
-    match try_sudo_native(cli_path, args, passphrase) {
+    match try_sudo_native(cli_path, args, secret, &sudo) {
         Some(Ok(stdout)) => return Ok(stdout),
         Some(Err(e)) => return Err(e),
         None => {
@@ -354,12 +640,20 @@ fn execute_with_sudo(args: &[&str], passphrase: Option<&str>, silent: bool) -> R
         }
     }
 
-    // Fall back to askpass dialog
-    let askpass_script = create_askpass_script()?;
+    // Fall back to the selected password dialog
+    let mut audit = AuditScope::begin(
+        sudo.mode,
+        AuditMethod::SudoAskpass,
+        cli_path,
+        args,
+        secret.map(|secret| secret.audit()),
+        secret.and_then(|secret| secret.passphrase()),
+    );
+    let askpass = AskpassSession::start(sudo.askpass)?;
 
     // Preserve ALFS_PASSPHRASE through sudo — env_reset strips it otherwise
     let cli_path_str = cli_path.to_string_lossy();
-    let mut sudo_args: Vec<&str> = if passphrase.is_some() {
+    let mut sudo_args: Vec<&str> = if secret.and_then(|secret| secret.passphrase()).is_some() {
         vec!["-A", "--preserve-env=ALFS_PASSPHRASE", "--", &cli_path_str]
     } else {
         vec!["-A", "--", &cli_path_str]
@@ -368,30 +662,31 @@ fn execute_with_sudo(args: &[&str], passphrase: Option<&str>, silent: bool) -> R
 
     let mut cmd = Command::new("sudo");
     cmd.args(&sudo_args);
-    cmd.env("SUDO_ASKPASS", &askpass_script);
+    askpass.apply(&mut cmd);
     // Use piped stdin instead of null - libkrun's epoll fails with /dev/null
     cmd.stdin(Stdio::piped());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    if let Some(pass) = passphrase {
-        cmd.env("ALFS_PASSPHRASE", pass);
+    if let Some(ref secret) = secret {
+        secret.apply(&mut cmd);
     }
 
     // Spawn the process so we can handle it with timeout
     let mut child = cmd.spawn()
         .map_err(|e| format!("Failed to execute sudo: {}", e))?;
+    if let Some(ref secret) = secret {
+        secret.feed(&mut child);
+    }
 
-    // Wait for process with timeout (30 seconds for mount operations)
-    let timeout = Duration::from_secs(30);
+    // Wait for process with the operation's timeout
+    let timeout = sudo.timeout;
     let start = Instant::now();
 
     loop {
         match child.try_wait() {
             Ok(Some(status)) => {
                 // Process finished
-                let _ = fs::remove_file(&askpass_script);
-
                 let mut stdout = String::new();
                 let mut stderr = String::new();
 
@@ -401,6 +696,7 @@ fn execute_with_sudo(args: &[&str], passphrase: Option<&str>, silent: bool) -> R
                 if let Some(ref mut err) = child.stderr {
                     let _ = err.read_to_string(&mut stderr);
                 }
+                audit.exit_status(status.code());
 
                 if status.success() {
                     return Ok(stdout);
@@ -409,6 +705,7 @@ fn execute_with_sudo(args: &[&str], passphrase: Option<&str>, silent: bool) -> R
                     if stderr.contains("incorrect password") {
                         return Err("Incorrect password".to_string());
                     } else if stderr.contains("no askpass program") || stderr.contains("no password was provided") {
+                        audit.outcome(AuditOutcome::Cancelled);
                         return Err("Authentication cancelled".to_string());
                     } else {
                         return Err(sanitize_error(&stdout, &stderr));
@@ -421,49 +718,170 @@ fn execute_with_sudo(args: &[&str], passphrase: Option<&str>, silent: bool) -> R
                     // Timeout - kill the process
                     let _ = child.kill();
                     let _ = child.wait();
-                    let _ = fs::remove_file(&askpass_script);
+                    audit.outcome(AuditOutcome::TimedOut);
                     return Err("Command timed out".to_string());
                 }
                 std::thread::sleep(Duration::from_millis(100));
             }
             Err(e) => {
-                let _ = fs::remove_file(&askpass_script);
                 return Err(format!("Error waiting for process: {}", e));
             }
         }
     }
 }
 
-fn create_askpass_script() -> Result<String, String> {
-    use std::io::Write;
-    use std::os::unix::fs::PermissionsExt;
+/// Run `program args` as root through pkexec. pkexec resets the environment,
+/// so a passphrase reaches the CLI on stdin as a key file instead of in
+/// `ALFS_PASSPHRASE`.
+fn execute_with_pkexec(
+    program: &Path,
+    args: &[&str],
+    secret: Option<CliSecret<'_>>,
+    silent: bool,
+    timeout: Duration,
+) -> Result<String, CommandExecutionError> {
+    let pkexec = polkit::find_pkexec().ok_or_else(|| {
+        CommandExecutionError::Failed(
+            "pkexec not found; install polkit or choose another elevation method".to_string(),
+        )
+    })?;
+    run_pkexec(&pkexec, program, args, secret, silent, timeout)
+}
 
-    // Create askpass script that uses osascript to prompt for password
-    // The password goes directly from osascript to sudo, never through our app
-    let script_content = r#"#!/bin/bash
-osascript -e 'Tell application "System Events" to display dialog "anylinuxfs requires administrator privileges." & return & return & "Enter your password:" with hidden answer default answer "" buttons {"Cancel", "OK"} default button "OK" with title "Authentication Required" with icon caution' -e 'text returned of result' 2>/dev/null
-"#;
+fn run_pkexec(
+    pkexec: &Path,
+    program: &Path,
+    args: &[&str],
+    secret: Option<CliSecret<'_>>,
+    silent: bool,
+    timeout: Duration,
+) -> Result<String, CommandExecutionError> {
+    let mut command_args = args.to_vec();
+    let stdin_secret = match secret {
+        // Not a real passphrase: the CLI needs no key file for it
+        Some(CliSecret::Passphrase(PROBE_PASSPHRASE)) => None,
+        Some(CliSecret::Passphrase(passphrase)) => {
+            if args.first() != Some(&"mount") || !supports_mount_flag("--key-file") {
+                return Err(CommandExecutionError::Failed(
+                    "The installed anylinuxfs cannot receive a passphrase through pkexec"
+                        .to_string(),
+                ));
+            }
+            command_args.splice(1..1, ["--key-file", "-"]);
+            Some(CliSecret::Keyfile(passphrase.as_bytes()))
+        }
+        keyfile => keyfile,
+    };
+    if silent && !polkit::authorized_without_interaction(program) {
+        log::debug!("pkexec: authorization expired, silent mode — skipping polkit dialog");
+        return Err(CommandExecutionError::InteractionRequired);
+    }
 
-    // Use tempfile for a cryptographically random filename, preventing symlink attacks
-    let mut file = tempfile::Builder::new()
-        .prefix("anylinuxfs-askpass-")
-        .suffix(".sh")
-        .tempfile()
-        .map_err(|e| format!("Failed to create askpass script: {}", e))?;
+    let mut audit = AuditScope::begin(
+        ElevationMode::Polkit,
+        AuditMethod::Pkexec,
+        program,
+        args,
+        secret.map(|secret| secret.audit()),
+        secret.and_then(|secret| secret.passphrase()),
+    );
+    let mut cmd = Command::new(pkexec);
+    // Fail instead of prompting on a terminal the app does not have
+    cmd.arg("--disable-internal-agent");
+    cmd.arg(program);
+    cmd.args(&command_args);
+    // Use piped stdin instead of null - libkrun's epoll fails with /dev/null
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
 
-    // Set restrictive permissions (owner-only executable)
-    file.as_file().set_permissions(fs::Permissions::from_mode(0o700))
-        .map_err(|e| format!("Failed to set askpass script permissions: {}", e))?;
+    let mut child = cmd.spawn().map_err(|e| {
+        CommandExecutionError::Failed(format!("Failed to execute pkexec: {}", e))
+    })?;
+    if let Some(ref secret) = stdin_secret {
+        secret.feed(&mut child);
+    }
 
-    file.write_all(script_content.as_bytes())
-        .map_err(|e| format!("Failed to write askpass script: {}", e))?;
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                let mut stdout = String::new();
+                let mut stderr = String::new();
+                if let Some(ref mut out) = child.stdout {
+                    let _ = out.read_to_string(&mut stdout);
+                }
+                if let Some(ref mut err) = child.stderr {
+                    let _ = err.read_to_string(&mut stderr);
+                }
+                audit.exit_status(status.code());
 
-    // Persist the file (disables auto-delete) so sudo can read it;
-    // the caller is responsible for removing it after use
-    let (_, path) = file.keep()
-        .map_err(|e| format!("Failed to persist askpass script: {}", e))?;
+                return match status.code() {
+                    Some(0) => Ok(stdout),
+                    Some(PKEXEC_DISMISSED) => {
+                        audit.outcome(AuditOutcome::Cancelled);
+                        Err(CommandExecutionError::Cancelled)
+                    }
+                    Some(PKEXEC_NOT_AUTHORIZED) => {
+                        log::debug!("pkexec: not authorized: {}", stderr.trim());
+                        Err(CommandExecutionError::Failed(
+                            if stderr.contains("authentication agent") {
+                                "Permission denied - no polkit authentication agent is running"
+                            } else {
+                                "Permission denied - polkit did not authorize this command"
+                            }
+                            .to_string(),
+                        ))
+                    }
+                    _ => Err(CommandExecutionError::Failed(sanitize_error(&stdout, &stderr))),
+                };
+            }
+            Ok(None) => {
+                if start.elapsed() > timeout {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    audit.outcome(AuditOutcome::TimedOut);
+                    return Err(CommandExecutionError::TimedOut);
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => {
+                return Err(CommandExecutionError::Failed(format!(
+                    "Error waiting for process: {}",
+                    e
+                )));
+            }
+        }
+    }
+}
 
-    Ok(path.to_string_lossy().to_string())
+/// Remove askpass socket directories (and scripts from older versions)
+/// created before `before` that a crashed session never cleaned up. Those of
+/// commands still running are newer than that.
+pub fn remove_orphaned_askpass_scripts(before: SystemTime) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(ASKPASS_PREFIX)
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if !metadata.modified().is_ok_and(|modified| modified < before) {
+                return None;
+            }
+            let removed = if metadata.is_dir() {
+                fs::remove_dir_all(entry.path())
+            } else {
+                fs::remove_file(entry.path())
+            };
+            removed.ok().map(|_| entry.path())
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -478,4 +896,127 @@ mod tests {
         );
         assert_eq!(message, "Administrator elevation was blocked by system policy");
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn pkexec_dismissal_and_denial_are_reported_distinctly() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for pkexec: exits with the status named by the subcommand
+        let directory = tempfile::tempdir().unwrap();
+        let pkexec = directory.path().join("pkexec");
+        fs::write(
+            &pkexec,
+            "#!/bin/sh\n\
+             [ \"$1\" = --disable-internal-agent ] || exit 2\n\
+             case \"$3\" in\n\
+             dismiss) exit 126 ;;\n\
+             deny) echo 'Error executing command as another user: Not authorized' >&2; exit 127 ;;\n\
+             esac\n\
+             echo \"ran $2 $3\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&pkexec, fs::Permissions::from_mode(0o755)).unwrap();
+        let cli = Path::new("/usr/bin/anylinuxfs");
+        let run = |subcommand| {
+            run_pkexec(&pkexec, cli, &[subcommand], None, false, Duration::from_secs(5))
+        };
+
+        assert_eq!(run("list").unwrap(), "ran /usr/bin/anylinuxfs list\n");
+        assert!(matches!(run("dismiss"), Err(CommandExecutionError::Cancelled)));
+        match run("deny") {
+            Err(CommandExecutionError::Failed(message)) => {
+                assert!(message.starts_with("Permission denied"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn polkit_mount_without_passphrase_passes_no_key_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for pkexec: echoes the command line it would run
+        let directory = tempfile::tempdir().unwrap();
+        let pkexec = directory.path().join("pkexec");
+        fs::write(&pkexec, "#!/bin/sh\nshift\necho \"$@\"\n").unwrap();
+        fs::set_permissions(&pkexec, fs::Permissions::from_mode(0o755)).unwrap();
+        let cli = Path::new("/usr/bin/anylinuxfs");
+        let args = ["mount", "/dev/sdb1"];
+
+        // An unencrypted partition on a CLI that may lack --key-file mounts
+        // with no secret at all, probe or otherwise
+        let passphrase = mount_passphrase(ElevationMode::Polkit, None);
+        assert_eq!(passphrase, None);
+        let output = run_pkexec(
+            &pkexec,
+            cli,
+            &args,
+            passphrase.as_deref().map(CliSecret::Passphrase),
+            false,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(output, "/usr/bin/anylinuxfs mount /dev/sdb1\n");
+
+        let probe = Some(CliSecret::Passphrase(PROBE_PASSPHRASE));
+        let output = run_pkexec(&pkexec, cli, &args, probe, false, Duration::from_secs(5)).unwrap();
+        assert_eq!(output, "/usr/bin/anylinuxfs mount /dev/sdb1\n");
+    }
+
+    use crate::commands::status::MountInfo;
+    use crate::commands::{AppConfig, DiskListResult, VmImage};
+
+    macro_rules! golden {
+        ($name:literal) => {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/cli/", $name))
+        };
+    }
+
+    #[test]
+    fn disk_list_text_parses() {
+        let result = DiskListResult::from_text(golden!("list.txt")).unwrap();
+        let devices: Vec<&str> = result
+            .disks
+            .iter()
+            .flat_map(|disk| disk.partitions.iter().map(|p| p.device.as_str()))
+            .collect();
+        assert_eq!(
+            devices,
+            vec![
+                "/dev/disk4",
+                "/dev/disk6s1",
+                "/dev/disk6s2",
+                "/dev/disk6s5",
+                "raid:disk7:disk8",
+                "lvm:vg0:home",
+            ]
+        );
+        assert_eq!(result.disks[1].partitions[1].label.as_deref(), Some("NO NAME"));
+        assert!(result.disks[0].partitions[0].encrypted);
+    }
+
+    #[test]
+    fn mount_status_text_parses() {
+        let mounts = Vec::<MountInfo>::from_text(golden!("status.txt")).unwrap();
+        assert_eq!(mounts.len(), 2);
+        assert!(mounts[0].read_only);
+        assert_eq!((mounts[1].vcpus, mounts[1].ram_mb), (Some(1), Some(512)));
+        assert!(Vec::<MountInfo>::from_text("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn image_list_text_parses() {
+        let images = Vec::<VmImage>::from_text(golden!("image_list.txt")).unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images[0].installed && !images[1].installed);
+    }
+
+    #[test]
+    fn config_text_parses() {
+        let config = AppConfig::from_text(golden!("config.txt")).unwrap();
+        assert_eq!(config.ram_mb, Some(1024));
+        assert_eq!(config.log_level.as_deref(), Some("off"));
+    }
 }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomAction {
@@ -58,7 +58,9 @@ struct ConfigFile {
     custom_actions: HashMap<String, ActionConfig>,
 }
 
-fn get_user_config_path() -> PathBuf {
+/// The user's anylinuxfs config, `~/.anylinuxfs/config.toml`. Custom
+/// actions created in the GUI and the actions behind shared mounts live here.
+pub fn get_user_config_path() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("/tmp"))
         .join(".anylinuxfs/config.toml")
@@ -111,6 +113,13 @@ fn parse_actions_from_file(path: &PathBuf, is_upstream: bool) -> Vec<CustomActio
     actions
 }
 
+/// Prefix of the actions written for shared mounts; hidden from the action list
+const SHARE_ACTION_PREFIX: &str = "gui-share-";
+
+fn share_action_name(uuid: &str) -> String {
+    format!("{}{}", SHARE_ACTION_PREFIX, uuid.to_lowercase())
+}
+
 #[tauri::command]
 pub fn list_custom_actions() -> Result<Vec<CustomAction>, String> {
     let mut all_actions = Vec::new();
@@ -119,9 +128,13 @@ pub fn list_custom_actions() -> Result<Vec<CustomAction>, String> {
     let upstream_path = get_upstream_config_path();
     all_actions.extend(parse_actions_from_file(&upstream_path, true));
 
-    // Load user actions
+    // Load user actions, except the ones the GUI manages for shared mounts
     let user_path = get_user_config_path();
-    all_actions.extend(parse_actions_from_file(&user_path, false));
+    all_actions.extend(
+        parse_actions_from_file(&user_path, false)
+            .into_iter()
+            .filter(|action| !action.name.starts_with(SHARE_ACTION_PREFIX)),
+    );
 
     // Sort by name
     all_actions.sort_by(|a, b| a.name.cmp(&b.name));
@@ -129,6 +142,146 @@ pub fn list_custom_actions() -> Result<Vec<CustomAction>, String> {
     Ok(all_actions)
 }
 
+/// Validate an action name before it is passed to the CLI
+fn validate_action_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Action name cannot be empty".to_string());
+    }
+    let valid = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid || name.starts_with('-') {
+        return Err(format!("Invalid action name '{}'", name));
+    }
+    Ok(())
+}
+
+/// Operating system a VM image runs. Images are named after their OS
+/// (e.g. "alpine-latest", "freebsd-15.0"); Alpine is the default.
+pub fn image_os(image: Option<&str>) -> &'static str {
+    match image {
+        Some(name) if name.to_lowercase().starts_with("freebsd") => "freebsd",
+        _ => "linux",
+    }
+}
+
+/// Look up a custom action for a mount and check it can run on the selected image
+pub fn resolve_mount_action(name: &str, image: Option<&str>) -> Result<CustomAction, String> {
+    validate_action_name(name)?;
+    let action = list_custom_actions()?
+        .into_iter()
+        .find(|action| action.name == name)
+        .ok_or_else(|| format!("Action '{}' not found", name))?;
+
+    let required_os = action.required_os.trim().to_lowercase();
+    let selected_os = image_os(image);
+    if !required_os.is_empty() && required_os != selected_os {
+        return Err(format!(
+            "Action '{}' requires {} but the selected image runs {}",
+            name, required_os, selected_os
+        ));
+    }
+    Ok(action)
+}
+
+/// Write the action used to mount partition `uuid` as a share: a copy of the
+/// selected action (if any) whose `override_nfs_export` is `export`. Returns
+/// the action name to pass to `mount -a`.
+///
+/// `mount -a` only resolves actions from the anylinuxfs config files, so this
+/// writes a `gui-share-<uuid>` entry into the user's
+/// `~/.anylinuxfs/config.toml`, next to any actions the user wrote. The entry
+/// is replaced on every shared mount and deleted with the share settings
+/// (`remove_share_action`); the GUI's action list hides it.
+pub fn write_share_action(
+    uuid: &str,
+    base: Option<&CustomAction>,
+    export: &str,
+) -> Result<String, String> {
+    if let Some(base) = base {
+        if !base.override_nfs_export.trim().is_empty() {
+            return Err(format!(
+                "Action '{}' already overrides the NFS export and cannot be combined with sharing",
+                base.name
+            ));
+        }
+    }
+    let name = share_action_name(uuid);
+    validate_action_name(&name)?;
+
+    let config_path = get_user_config_path();
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        fs::set_permissions(parent, fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to set config directory permissions: {}", e))?;
+    }
+
+    let content = fs::read_to_string(&config_path).unwrap_or_default();
+    let mut doc: toml::Table = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    let custom_actions = doc
+        .entry("custom_actions")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .ok_or("Invalid config format")?;
+
+    let input = match base {
+        Some(base) => CustomActionInput {
+            name: name.clone(),
+            description: format!("Shared mount based on '{}' (managed by the GUI)", base.name),
+            before_mount: base.before_mount.clone(),
+            after_mount: base.after_mount.clone(),
+            before_unmount: base.before_unmount.clone(),
+            environment: base.environment.clone(),
+            capture_environment: base.capture_environment.clone(),
+            override_nfs_export: export.to_string(),
+            required_os: base.required_os.clone(),
+        },
+        None => CustomActionInput {
+            name: name.clone(),
+            description: "Shared mount (managed by the GUI)".to_string(),
+            before_mount: String::new(),
+            after_mount: String::new(),
+            before_unmount: String::new(),
+            environment: Vec::new(),
+            capture_environment: Vec::new(),
+            override_nfs_export: export.to_string(),
+            required_os: String::new(),
+        },
+    };
+    let (name, action_table) = build_action_entry(input);
+    custom_actions.insert(name.clone(), toml::Value::Table(action_table));
+
+    let new_content = toml::to_string_pretty(&doc)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write_config_secure(&config_path, &new_content)?;
+
+    Ok(name)
+}
+
+/// Delete the action written for sharing partition `uuid` from the user
+/// config at `config_path`. Nothing is written when there is no such action.
+pub fn remove_share_action(config_path: &Path, uuid: &str) -> Result<(), String> {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to read config: {}", e)),
+    };
+    let mut doc: toml::Table = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    let removed = doc
+        .get_mut("custom_actions")
+        .and_then(|actions| actions.as_table_mut())
+        .and_then(|actions| actions.remove(&share_action_name(uuid)))
+        .is_some();
+    if !removed {
+        return Ok(());
+    }
+
+    let new_content = toml::to_string_pretty(&doc)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write_config_secure(&config_path.to_path_buf(), &new_content)
+}
+
 #[tauri::command]
 pub fn create_custom_action(action: CustomActionInput) -> Result<(), String> {
     let config_path = get_user_config_path();
@@ -1,9 +1,26 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
 use crate::cli::execute_command;
 
+/// Packages already confirmed present, per image, so a mount does not list
+/// the image's packages every time
+static PRESENT_PACKAGES: OnceLock<Mutex<HashMap<String, HashSet<String>>>> = OnceLock::new();
+
+fn present_packages() -> &'static Mutex<HashMap<String, HashSet<String>>> {
+    PRESENT_PACKAGES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Forget every confirmed package, e.g. after some were removed
+fn forget_present_packages() {
+    if let Ok(mut present) = present_packages().lock() {
+        present.clear();
+    }
+}
+
 /// Validate package name to prevent command injection
 /// Package names must contain only alphanumeric characters, dots, underscores, hyphens,
 /// and optionally a version specifier like @edge
-fn validate_package_name(name: &str) -> Result<(), String> {
+pub fn validate_package_name(name: &str) -> Result<(), String> {
     if name.is_empty() {
         return Err("Package name cannot be empty".to_string());
     }
@@ -27,7 +44,7 @@ fn validate_package_name(name: &str) -> Result<(), String> {
 
 #[tauri::command]
 pub fn list_packages() -> Result<Vec<String>, String> {
-    let output = execute_command(&["apk", "info"], false, None, false)?;
+    let output = execute_command(&["apk", "info"], None, None, false)?;
 
     let packages: Vec<String> = output
         .lines()
@@ -38,6 +55,43 @@ pub fn list_packages() -> Result<Vec<String>, String> {
     Ok(packages)
 }
 
+/// Install whichever of `required` `image` does not have yet, so a
+/// filesystem's packages are present before it is mounted. Packages found
+/// or installed once are remembered for the image.
+pub fn ensure_packages(image: &str, required: &[String]) -> Result<(), String> {
+    for pkg in required {
+        validate_package_name(pkg)?;
+    }
+    let known = present_packages()
+        .lock()
+        .map(|present| present.get(image).cloned().unwrap_or_default())
+        .unwrap_or_default();
+    if required.iter().all(|pkg| known.contains(pkg)) {
+        return Ok(());
+    }
+
+    let installed = list_packages()?;
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|pkg| !installed.contains(pkg))
+        .map(|pkg| pkg.as_str())
+        .collect();
+    if !missing.is_empty() {
+        log::info!("Installing packages needed to mount: {}", missing.join(" "));
+        let mut args = vec!["apk", "add"];
+        args.extend(missing);
+        execute_command(&args, None, None, false)?;
+    }
+
+    if let Ok(mut present) = present_packages().lock() {
+        present
+            .entry(image.to_string())
+            .or_default()
+            .extend(required.iter().cloned());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn add_packages(packages: Vec<String>) -> Result<(), String> {
     if packages.is_empty() {
@@ -53,7 +107,7 @@ pub async fn add_packages(packages: Vec<String>) -> Result<(), String> {
         let mut args = vec!["apk", "add"];
         let pkg_refs: Vec<&str> = packages.iter().map(|s| s.as_str()).collect();
         args.extend(pkg_refs);
-        execute_command(&args, false, None, false)?;
+        execute_command(&args, None, None, false)?;
         Ok(())
     })
     .await
@@ -75,7 +129,9 @@ pub async fn remove_packages(packages: Vec<String>) -> Result<(), String> {
         let mut args = vec!["apk", "del"];
         let pkg_refs: Vec<&str> = packages.iter().map(|s| s.as_str()).collect();
         args.extend(pkg_refs);
-        execute_command(&args, false, None, false)?;
+        let result = execute_command(&args, None, None, false);
+        forget_present_packages();
+        result?;
         Ok(())
     })
     .await
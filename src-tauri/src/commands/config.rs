@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::cli::execute_command;
+use crate::cli::{self, execute_command, CliOutput};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppConfig {
@@ -49,22 +49,32 @@ where
     }
 }
 
-#[tauri::command]
-pub fn get_config() -> Result<AppConfig, String> {
-    // Run `anylinuxfs config` to get full config with defaults
-    let output = execute_command(&["config"], false, None, false)?;
+impl From<TomlConfig> for AppConfig {
+    fn from(config: TomlConfig) -> Self {
+        let krun = config.krun;
+        AppConfig {
+            ram_mb: krun.as_ref().and_then(|k| k.ram_size_mib),
+            vcpus: krun.as_ref().and_then(|k| k.num_vcpus),
+            log_level: krun.and_then(|k| k.log_level),
+        }
+    }
+}
 
-    // Fix unquoted string values (CLI outputs `log_level = off` instead of `log_level = "off"`)
-    let fixed_output = fix_unquoted_strings(&output);
+impl CliOutput for AppConfig {
+    fn from_text(output: &str) -> Result<Self, String> {
+        // Fix unquoted string values (CLI outputs `log_level = off` instead of `log_level = "off"`)
+        let fixed_output = fix_unquoted_strings(output);
 
-    let toml_config: TomlConfig = toml::from_str(&fixed_output)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+        toml::from_str::<TomlConfig>(&fixed_output)
+            .map(AppConfig::from)
+            .map_err(|e| format!("Failed to parse config: {}", e))
+    }
+}
 
-    Ok(AppConfig {
-        ram_mb: toml_config.krun.as_ref().and_then(|k| k.ram_size_mib),
-        vcpus: toml_config.krun.as_ref().and_then(|k| k.num_vcpus),
-        log_level: toml_config.krun.as_ref().and_then(|k| k.log_level.clone()),
-    })
+#[tauri::command]
+pub fn get_config() -> Result<AppConfig, String> {
+    // Run `anylinuxfs config` to get full config with defaults
+    cli::query(&["config"])
 }
 
 fn fix_unquoted_strings(input: &str) -> String {
@@ -113,9 +123,8 @@ const MIN_VCPUS: u32 = 1;
 const MAX_VCPUS: u32 = 32;
 const VALID_LOG_LEVELS: &[&str] = &["off", "error", "warn", "info", "debug", "trace"];
 
-#[tauri::command]
-pub async fn update_config(ram_mb: Option<u32>, vcpus: Option<u32>, log_level: Option<String>) -> Result<(), String> {
-    // Validate inputs before running commands
+/// Bounds shared by the global config and per-mount overrides
+pub fn validate_vm_resources(ram_mb: Option<u32>, vcpus: Option<u32>) -> Result<(), String> {
     if let Some(ram) = ram_mb {
         if !(MIN_RAM_MB..=MAX_RAM_MB).contains(&ram) {
             return Err(format!("Invalid RAM value: {}MB. Must be between {} and {} MB.", ram, MIN_RAM_MB, MAX_RAM_MB));
@@ -127,6 +136,13 @@ pub async fn update_config(ram_mb: Option<u32>, vcpus: Option<u32>, log_level: O
             return Err(format!("Invalid vCPU value: {}. Must be between {} and {}.", cpus, MIN_VCPUS, MAX_VCPUS));
         }
     }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_config(ram_mb: Option<u32>, vcpus: Option<u32>, log_level: Option<String>) -> Result<(), String> {
+    // Validate inputs before running commands
+    validate_vm_resources(ram_mb, vcpus)?;
 
     if let Some(ref level) = log_level {
         if !VALID_LOG_LEVELS.contains(&level.as_str()) {
@@ -138,15 +154,15 @@ pub async fn update_config(ram_mb: Option<u32>, vcpus: Option<u32>, log_level: O
     tokio::task::spawn_blocking(move || {
         // Use the CLI to update config values
         if let Some(ram) = ram_mb {
-            execute_command(&["config", "-r", &ram.to_string()], false, None, false)?;
+            execute_command(&["config", "-r", &ram.to_string()], None, None, false)?;
         }
 
         if let Some(cpus) = vcpus {
-            execute_command(&["config", "-n", &cpus.to_string()], false, None, false)?;
+            execute_command(&["config", "-n", &cpus.to_string()], None, None, false)?;
         }
 
         if let Some(level) = log_level {
-            execute_command(&["config", "-l", &level], false, None, false)?;
+            execute_command(&["config", "-l", &level], None, None, false)?;
         }
 
         Ok(())
@@ -1,23 +1,36 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::process::Command;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::time::timeout;
+use crate::backend::{self, BlockingProcess, PartitionDetails};
 use crate::cache;
 use crate::cli::{
-    execute_command, execute_command_with_elevation, CommandExecutionError,
+    self, execute_command, execute_command_with_elevation, CliOutput, CliSecret,
+    CommandExecutionError,
 };
-use crate::elevation::{
-    ElevationMode, ElevationState, TerminalInteraction,
-    INTERACTIVE_ELEVATION_TIMEOUT_SECS,
-};
-use crate::paths::{COMMAND_TIMEOUT_SECS, MOUNT_TIMEOUT_SECS};
+use crate::elevation::{ElevationMode, ElevationState, TerminalInteraction};
+use crate::secrets::SecretsState;
+use crate::timeouts::{is_transient_error, Operation, OperationPolicy};
+use super::action::{image_os, resolve_mount_action, write_share_action};
+use super::filesystem::{merge_mount_options, FilesystemSupportState, FilesystemTable};
+use super::apk::ensure_packages;
+use super::image::{image_for_family, validate_image_name};
+use super::mount_options::{MountOptionError, MountOptions};
+use super::progress::MountProgressTracker;
+use super::reconcile::{MountParameters, SessionJournal};
+use super::resources::{partition_details, resolve_vm_resources, VmResources, VmResourcesState};
+use super::share::SharesState;
+use super::status::get_mount_status_sync;
+use super::unlock::{classify_encryption_error, EncryptionFailure, UnlockKey, UnlockMethod};
 
 /// Validate device path to prevent command injection
 /// Device must start with /dev/, raid:, or lvm: and contain only safe characters
-fn validate_device_path(device: &str) -> Result<(), String> {
+pub fn validate_device_path(device: &str) -> Result<(), String> {
     if device.is_empty() {
         return Err("Device path is required".to_string());
     }
@@ -47,6 +60,17 @@ fn validate_device_path(device: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Sanitize extra mount options with a whitelist to prevent command injection
+pub fn validate_extra_options(opts: &str) -> Result<(), String> {
+    let valid = opts.chars().all(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, ',' | '.' | '_' | '-' | '=' | '/' | ':')
+    });
+    if !valid {
+        return Err("Mount options contain invalid characters".to_string());
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum DiskType {
@@ -89,41 +113,64 @@ pub struct DiskListResult {
 #[tauri::command]
 pub async fn list_disks(
     elevation_state: tauri::State<'_, Arc<ElevationState>>,
+    fs_state: tauri::State<'_, Arc<FilesystemSupportState>>,
     use_sudo: bool,
     silent: bool,
 ) -> Result<DiskListResult, String> {
     let elevation_state = elevation_state.inner().clone();
     let operation_guard = elevation_state.begin_operation("list")?;
     let elevation_mode = operation_guard.mode();
-    let timeout_secs = if use_sudo && elevation_mode == ElevationMode::InteractiveTerminal {
-        INTERACTIVE_ELEVATION_TIMEOUT_SECS
+    let operations = elevation_state.operation_policies();
+    let policy = if use_sudo {
+        operations.for_mode(Operation::List, elevation_mode)
     } else {
-        COMMAND_TIMEOUT_SECS
+        operations.get(Operation::List)
     };
     let list_elevation_state = elevation_state.clone();
+    let fs_state = fs_state.inner().clone();
 
     // Run in blocking task with timeout to avoid freezing UI
     let list_future = tokio::task::spawn_blocking(move || {
         // Run list command (now shows all volumes by default, including broken SD cards)
-        let output = execute_command_with_elevation(
-            &["list"],
-            use_sudo,
-            None,
-            silent,
-            elevation_mode,
-            &list_elevation_state,
-            TerminalInteraction::CaptureOutput {
-                operation: "list".to_string(),
-            },
-        )
-        .map_err(|error| error.message())?;
-        let mut result = parse_disk_list_output(&output)?;
+        let list_output = || {
+            let output = policy
+                .run(
+                    "list",
+                    |_| {
+                        execute_command_with_elevation(
+                            &["list"],
+                            use_sudo,
+                            None,
+                            silent,
+                            elevation_mode,
+                            &list_elevation_state,
+                            TerminalInteraction::CaptureOutput {
+                                operation: "list".to_string(),
+                            },
+                            policy,
+                        )
+                    },
+                    CommandExecutionError::is_transient,
+                )
+                .map_err(|error| error.message())?;
+            DiskListResult::from_text(&output)
+        };
+        let backend = backend::current();
+        let mut result = backend.enumerate(&list_output)?;
 
         // Check which partitions are already mounted by the system
-        update_mount_status(&mut result);
-
-        // Check filesystem support using diskutil
-        update_filesystem_support(&mut result);
+        update_mount_status(&mut result, backend.system_mounts());
+
+        // Check filesystem support using the host's filesystem identification
+        update_filesystem_support(&mut result, backend.partition_details(), &fs_state.table());
+        fs_state.remember_listed(
+            result
+                .disks
+                .iter()
+                .flat_map(|disk| disk.partitions.iter())
+                .map(|partition| (partition.device.clone(), partition.filesystem.clone()))
+                .collect(),
+        );
 
         // Check if there are any supported, mountable partitions
         result.has_supported_partitions = result.disks.iter().any(|d| {
@@ -134,20 +181,20 @@ pub async fn list_disks(
         Ok(result)
     });
 
-    match timeout(Duration::from_secs(timeout_secs), list_future).await {
+    match timeout(policy.budget(), list_future).await {
         Ok(result) => result
             .map_err(|error| format!("Task error: {}", error))?,
         Err(_) => {
             elevation_state.cancel_pending_operation("list");
-            Err(format!("List disks timed out after {} seconds", timeout_secs))
+            Err(format!(
+                "List disks timed out after {} seconds",
+                policy.budget().as_secs()
+            ))
         }
     }
 }
 
-fn update_mount_status(result: &mut DiskListResult) {
-    // Get current mounts
-    let mounts = get_system_mounts();
-
+fn update_mount_status(result: &mut DiskListResult, mounts: Vec<(String, String)>) {
     for disk in &mut result.disks {
         for partition in &mut disk.partitions {
             // Check if this partition is mounted
@@ -165,78 +212,56 @@ fn update_mount_status(result: &mut DiskListResult) {
     }
 }
 
-fn get_system_mounts() -> Vec<(String, String)> {
-    let mut mounts = Vec::new();
-
-    // Use cached mount output to avoid redundant process spawning
-    if let Some(output) = cache::get_mount_output() {
-        let mount_output = String::from_utf8_lossy(&output.stdout);
-
-        for line in mount_output.lines() {
-            // Format: /dev/disk6s1 on /Volumes/NO NAME (msdos, ...)
-            let parts: Vec<&str> = line.split(" on ").collect();
-            if parts.len() >= 2 {
-                let device = parts[0].to_string();
-                // Extract mount point (everything before the parenthesis)
-                let rest = parts[1..].join(" on ");
-                if let Some(paren_pos) = rest.find(" (") {
-                    let mount_point = rest[..paren_pos].to_string();
-                    mounts.push((device, mount_point));
-                }
-            }
-        }
-    }
-
-    mounts
-}
-
-fn update_filesystem_support(result: &mut DiskListResult) {
-    // Run a single diskutil info -all call and parse the combined output
-    let diskutil_info = get_all_diskutil_info();
-
+fn update_filesystem_support(
+    result: &mut DiskListResult,
+    partition_details: HashMap<String, PartitionDetails>,
+    table: &FilesystemTable,
+) {
     for disk in &mut result.disks {
         for partition in &mut disk.partitions {
-            // Look up diskutil entry for UUID (applies to all partition types)
+            // Look up host details for UUID (applies to all partition types)
             let device_id = partition.device.trim_start_matches("/dev/");
-            let entry = diskutil_info.get(device_id);
+            let entry = partition_details.get(device_id);
 
-            // Set UUID from diskutil if available
+            // Set UUID from the host if available
             if let Some(e) = entry {
-                partition.uuid = e.uuid.clone();
+                if e.uuid.is_some() {
+                    partition.uuid = e.uuid.clone();
+                }
             }
 
             // For RAID/LVM partitions, use filesystem info from list output directly
             if disk.disk_type != DiskType::Normal {
-                let (supported, note) = check_filesystem_support(&partition.filesystem);
+                let (supported, note) = table.support(&partition.filesystem);
                 partition.supported = supported;
                 partition.support_note = note;
                 continue;
             }
 
             // If anylinuxfs detected a known Linux-native filesystem type, use that directly
-            if is_linux_native_fs(&partition.filesystem) {
-                let (supported, note) = check_filesystem_support(&partition.filesystem);
+            if table.is_native(&partition.filesystem) {
+                let (supported, note) = table.support(&partition.filesystem);
                 partition.supported = supported;
                 partition.support_note = note;
                 continue;
             }
 
-            // Look up diskutil results if available
+            // Look up host filesystem identification if available
             if let Some(e) = entry {
-                if let Some(ref fs_personality) = e.fs_personality {
-                    let (supported, note) = check_filesystem_support(fs_personality);
-                    if !fs_personality.is_empty() && !is_linux_native_fs(&partition.filesystem) {
+                if let Some(ref fs_personality) = e.filesystem {
+                    let (supported, note) = table.support(fs_personality);
+                    if !fs_personality.is_empty() && !table.is_native(&partition.filesystem) {
                         partition.filesystem = fs_personality.clone();
                     }
                     partition.supported = supported;
                     partition.support_note = note;
                 } else {
-                    let (supported, note) = check_filesystem_support(&partition.filesystem);
+                    let (supported, note) = table.support(&partition.filesystem);
                     partition.supported = supported;
                     partition.support_note = note;
                 }
             } else {
-                let (supported, note) = check_filesystem_support(&partition.filesystem);
+                let (supported, note) = table.support(&partition.filesystem);
                 partition.supported = supported;
                 partition.support_note = note;
             }
@@ -244,112 +269,6 @@ fn update_filesystem_support(result: &mut DiskListResult) {
     }
 }
 
-struct DiskutilEntry {
-    fs_personality: Option<String>,
-    uuid: Option<String>,
-}
-
-fn get_all_diskutil_info() -> std::collections::HashMap<String, DiskutilEntry> {
-    use std::collections::HashMap;
-
-    let mut map = HashMap::new();
-    let output = match Command::new("diskutil").args(["info", "-all"]).output() {
-        Ok(o) => o,
-        Err(_) => return map,
-    };
-    let text = String::from_utf8_lossy(&output.stdout);
-    for block in text.split("**********") {
-        let mut device_id = None;
-        let mut fs_personality = None;
-        let mut uuid = None;
-        for line in block.lines() {
-            if line.contains("Device Identifier:") {
-                device_id = line.split(':').nth(1).map(|s| s.trim().to_string());
-            } else if line.contains("File System Personality:") {
-                fs_personality = line.split(':').nth(1).map(|s| s.trim().to_string());
-            } else if line.contains("Disk / Partition UUID:") {
-                uuid = line.split(':').nth(1).map(|s| s.trim().to_string());
-            }
-        }
-        if let Some(id) = device_id {
-            map.insert(id, DiskutilEntry { fs_personality, uuid });
-        }
-    }
-    map
-}
-
-fn is_linux_native_fs(fs: &str) -> bool {
-    let fs_lower = fs.to_lowercase();
-    fs_lower.contains("ext4") || fs_lower.contains("ext3") || fs_lower.contains("ext2")
-        || fs_lower.contains("btrfs") || fs_lower.contains("xfs") || fs_lower.contains("f2fs")
-        || fs_lower.contains("reiserfs") || fs_lower.contains("zfs")
-        || fs_lower.contains("ntfs") || fs_lower.contains("exfat")
-        || fs_lower.contains("luks") || fs_lower.contains("bitlocker")
-        || fs_lower.contains("lvm") || fs_lower.contains("raid")
-        || fs_lower == "linux filesystem"
-}
-
-fn check_filesystem_support(fs: &str) -> (bool, Option<String>) {
-    let fs_lower = fs.to_lowercase();
-
-    // Fully supported Linux-native filesystems
-    if fs_lower.contains("ext4") || fs_lower.contains("ext3") || fs_lower.contains("ext2")
-        || fs_lower.contains("btrfs") || fs_lower.contains("xfs") || fs_lower.contains("f2fs")
-        || fs_lower.contains("reiserfs")
-    {
-        return (true, None);
-    }
-
-    // Encrypted partitions are supported; an unlock key is requested at mount time.
-    if fs_lower.contains("luks") || fs_lower.contains("bitlocker") {
-        return (true, Some("Encrypted (unlock key required)".to_string()));
-    }
-
-    // RAID/LVM member partitions — not directly mountable, use admin mode for actual volumes
-    if fs_lower.contains("raid") {
-        return (false, Some("RAID member (use admin mode for volumes)".to_string()));
-    }
-    if fs_lower.contains("lvm") {
-        return (false, Some("LVM member (use admin mode for volumes)".to_string()));
-    }
-
-    // Generic "Linux Filesystem" from GPT partition type (native anylinuxfs detection)
-    if fs_lower == "linux filesystem" {
-        return (true, Some("Linux partition (use admin mode for exact fs type)".to_string()));
-    }
-
-    // FAT filesystems - well supported
-    if fs_lower.contains("fat32") || fs_lower.contains("fat16") || fs_lower.contains("exfat") {
-        return (true, None);
-    }
-
-    // NTFS - supported via ntfs-3g
-    if fs_lower.contains("ntfs") {
-        return (true, Some("NTFS via ntfs-3g".to_string()));
-    }
-
-    // Generic MS-DOS without FAT32/FAT16 specification - might be problematic
-    if fs_lower == "ms-dos" {
-        return (false, Some("Unknown FAT variant - may not mount".to_string()));
-    }
-
-    // Apple filesystems - not supported
-    if fs_lower.contains("apfs") {
-        return (false, Some("APFS not supported by Linux".to_string()));
-    }
-    if fs_lower.contains("hfs") || fs_lower.contains("mac os") {
-        return (false, Some("HFS/HFS+ has limited Linux support".to_string()));
-    }
-
-    // Unknown filesystem
-    if fs.is_empty() || fs_lower == "unknown" {
-        return (false, Some("Unknown filesystem".to_string()));
-    }
-
-    // Default: assume supported but note it's unverified
-    (true, Some(format!("Unverified: {}", fs)))
-}
-
 /// Extract model and is_external from parenthesized info in a disk header line
 fn extract_parenthesized_info(line: &str) -> (Option<String>, bool) {
     if let Some(start) = line.find('(') {
@@ -362,7 +281,7 @@ fn extract_parenthesized_info(line: &str) -> (Option<String>, bool) {
     (None, false)
 }
 
-fn parse_disk_list_output(output: &str) -> Result<DiskListResult, String> {
+pub fn parse_disk_list_output(output: &str) -> Result<DiskListResult, String> {
     let mut disks: Vec<Disk> = Vec::new();
     let mut current_disk: Option<Disk> = None;
 
@@ -430,27 +349,7 @@ fn parse_disk_list_output(output: &str) -> Result<DiskListResult, String> {
                     let rest = trimmed[colon_pos+1..].trim();
 
                     if let Some(partition) = parse_partition_line(rest, &disk.device, partition_num, &disk.disk_type) {
-                        if partition_num == 0 {
-                            // Partition 0: always use its size for the disk
-                            disk.size = partition.size.clone();
-                            // For RAID, partition 0 IS the mountable volume
-                            // For LVM, partition 0 is the VG scheme (not mountable)
-                            if disk.disk_type == DiskType::Raid {
-                                disk.partitions.push(partition);
-                            } else if disk.disk_type == DiskType::Normal
-                                && !partition.filesystem.to_lowercase().contains("partition_scheme")
-                            {
-                                // Whole-disk filesystem with no partition table: the
-                                // index-0 entry IS the filesystem (e.g. whole-disk LUKS,
-                                // a "superfloppy" ext4/FAT), not a scheme container like
-                                // GUID_partition_scheme. Surface it as a mountable
-                                // partition instead of dropping the disk for having an
-                                // empty partition list. (issue #83)
-                                disk.partitions.push(partition);
-                            }
-                        } else {
-                            disk.partitions.push(partition);
-                        }
+                        add_listed_partition(disk, partition_num, partition);
                     }
                 }
             }
@@ -471,6 +370,38 @@ fn parse_disk_list_output(output: &str) -> Result<DiskListResult, String> {
     })
 }
 
+/// Add a `list` entry to its disk. Index 0 is the whole disk: it sets the
+/// disk size and is only mountable for RAID volumes and whole-disk filesystems.
+fn add_listed_partition(disk: &mut Disk, partition_num: u32, partition: Partition) {
+    if partition_num == 0 {
+        // Partition 0: always use its size for the disk
+        disk.size = partition.size.clone();
+        // For RAID, partition 0 IS the mountable volume
+        // For LVM, partition 0 is the VG scheme (not mountable)
+        if disk.disk_type == DiskType::Raid {
+            disk.partitions.push(partition);
+        } else if disk.disk_type == DiskType::Normal
+            && !partition.filesystem.to_lowercase().contains("partition_scheme")
+        {
+            // Whole-disk filesystem with no partition table: the
+            // index-0 entry IS the filesystem (e.g. whole-disk LUKS,
+            // a "superfloppy" ext4/FAT), not a scheme container like
+            // GUID_partition_scheme. Surface it as a mountable
+            // partition instead of dropping the disk for having an
+            // empty partition list. (issue #83)
+            disk.partitions.push(partition);
+        }
+    } else {
+        disk.partitions.push(partition);
+    }
+}
+
+impl CliOutput for DiskListResult {
+    fn from_text(output: &str) -> Result<Self, String> {
+        parse_disk_list_output(output)
+    }
+}
+
 fn parse_partition_line(line: &str, _disk_device: &str, _partition_num: u32, disk_type: &DiskType) -> Option<Partition> {
     // Format: "Microsoft Basic Data NO NAME                 47.2 GB    disk6s1"
     // Or:     "ext4 linuxrootfs             7.5 GB     disk6s5"
@@ -496,10 +427,24 @@ fn parse_partition_line(line: &str, _disk_device: &str, _partition_num: u32, dis
     // Determine filesystem type and label
     let (filesystem, label) = parse_type_and_name(&type_and_name);
 
+    let encrypted = line.to_lowercase().contains("encrypted");
+
+    Some(listed_partition(identifier, size, filesystem, label, encrypted, disk_type))
+}
+
+/// Build a partition from a `list` entry; host details are filled in later
+fn listed_partition(
+    identifier: &str,
+    size: String,
+    filesystem: String,
+    label: Option<String>,
+    marked_encrypted: bool,
+    disk_type: &DiskType,
+) -> Partition {
     // Check for encryption markers
-    let encrypted = filesystem.to_lowercase().contains("luks")
-        || filesystem.to_lowercase().contains("bitlocker")
-        || line.to_lowercase().contains("encrypted");
+    let encrypted = marked_encrypted
+        || filesystem.to_lowercase().contains("luks")
+        || filesystem.to_lowercase().contains("bitlocker");
 
     // Build the device path based on disk type
     let device = match disk_type {
@@ -508,7 +453,7 @@ fn parse_partition_line(line: &str, _disk_device: &str, _partition_num: u32, dis
         DiskType::Lvm => format!("lvm:{}", identifier),
     };
 
-    Some(Partition {
+    Partition {
         device,
         size,
         filesystem,
@@ -519,7 +464,7 @@ fn parse_partition_line(line: &str, _disk_device: &str, _partition_num: u32, dis
         system_mount_point: None,
         supported: true,  // Will be updated after parsing
         support_note: None,
-    })
+    }
 }
 
 fn parse_type_and_name(parts: &[&str]) -> (String, Option<String>) {
@@ -566,8 +511,11 @@ fn parse_type_and_name(parts: &[&str]) -> (String, Option<String>) {
 pub enum MountOutcome {
     Mounted,
     EncryptionRequired,
+    /// The passphrase, recovery key or keyfile was rejected
+    WrongKey,
     Cancelled,
     TimedOut,
+    InvalidOptions,
     Failed,
 }
 
@@ -575,6 +523,14 @@ pub enum MountOutcome {
 pub struct MountCommandResult {
     pub outcome: MountOutcome,
     pub message: Option<String>,
+    pub action: Option<String>,
+    pub captured_environment: HashMap<String, String>,
+    /// Per-option problems when the outcome is `InvalidOptions`
+    pub option_errors: Vec<MountOptionError>,
+    /// A passphrase from the secret store was supplied for this mount
+    pub used_saved_passphrase: bool,
+    /// The mount is exported to the networks in its share settings
+    pub shared: bool,
 }
 
 impl MountCommandResult {
@@ -582,45 +538,270 @@ impl MountCommandResult {
         Self {
             outcome,
             message: message.into(),
+            action: None,
+            captured_environment: HashMap::new(),
+            option_errors: Vec::new(),
+            used_saved_passphrase: false,
+            shared: false,
         }
     }
 }
 
+/// How long to wait for the mount command to exit after the volume appears,
+/// so values captured by a custom action can be reported back
+const ACTION_CAPTURE_WAIT_SECS: u64 = 10;
+
+/// Extract `NAME=value` lines for the variables a custom action captures.
+/// Accepts an optional `export ` prefix and strips surrounding quotes.
+fn parse_captured_environment(output: &str, names: &[String]) -> HashMap<String, String> {
+    let mut captured = HashMap::new();
+    for line in output.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            if names.iter().any(|name| name == key) {
+                let value = value.trim().trim_matches('"').trim_matches('\'');
+                captured.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+    captured
+}
+
+/// What to mount and how. Unset fields fall back to the filesystem's
+/// defaults, the VM size remembered for the partition and saved passphrases.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MountRequest {
+    pub device: String,
+    pub passphrase: Option<String>,
+    pub read_only: Option<bool>,
+    pub extra_options: Option<String>,
+    pub ignore_permissions: Option<bool>,
+    pub action: Option<String>,
+    pub image: Option<String>,
+    pub raw_options: Option<String>,
+    pub ram_mb: Option<u32>,
+    pub vcpus: Option<u32>,
+    pub remember_passphrase: Option<bool>,
+    pub unlock: Option<UnlockMethod>,
+}
+
 #[tauri::command]
-pub async fn mount_disk(
-    app: AppHandle,
-    elevation_state: tauri::State<'_, Arc<ElevationState>>,
-    device: String,
-    passphrase: Option<String>,
-    read_only: Option<bool>,
-    extra_options: Option<String>,
-    ignore_permissions: Option<bool>,
-) -> Result<MountCommandResult, String> {
+pub async fn mount_disk(app: AppHandle, request: MountRequest) -> Result<MountCommandResult, String> {
+    mount(app, request).await
+}
+
+/// Mount a partition; shared by the command, favorite auto-mount and the
+/// remount fallback
+pub async fn mount(app: AppHandle, request: MountRequest) -> Result<MountCommandResult, String> {
+    let MountRequest {
+        device,
+        passphrase,
+        read_only,
+        extra_options,
+        ignore_permissions,
+        action,
+        image,
+        raw_options,
+        ram_mb,
+        vcpus,
+        remember_passphrase,
+        unlock,
+    } = request;
+    let elevation_state = app.state::<Arc<ElevationState>>();
+    let fs_state = app.state::<Arc<FilesystemSupportState>>();
+    let vm_state = app.state::<Arc<VmResourcesState>>();
+    let secrets = app.state::<Arc<SecretsState>>();
+    let shares = app.state::<Arc<SharesState>>();
+
     // Validate device path before use
     validate_device_path(&device)?;
+    if let Some(ref img) = image {
+        validate_image_name(img)?;
+    }
+    // The filesystem the host reports for the partition selects its support
+    // table entry: option checks, default options, image and packages
+    let status_device = device.clone();
+    let details = tokio::task::spawn_blocking(move || partition_details(&status_device))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?;
+    let filesystem = fs_state.entry_for(&device, details.filesystem.as_deref());
+    let partition_uuid = details.uuid.filter(|uuid| !uuid.is_empty());
+    // Without an explicit image, mount with the one the filesystem needs
+    let image = match (image, filesystem.as_ref().and_then(|entry| entry.image.clone())) {
+        (None, Some(family)) => tokio::task::spawn_blocking(move || image_for_family(&family))
+            .await
+            .map_err(|e| format!("Task error: {}", e))??,
+        (image, _) => image,
+    };
+    // Resolve the custom action up front so an unknown name or an OS mismatch
+    // fails before a VM is started
+    let selected_action = match action {
+        Some(ref name) => Some(resolve_mount_action(name, image.as_deref())?),
+        None => None,
+    };
+    // Keyfiles and recovery keys are checked before a VM is started
+    let recovery_key = matches!(unlock, Some(UnlockMethod::RecoveryKey { .. }));
+    let (passphrase, keyfile) = match unlock.map(|method| method.resolve()).transpose()? {
+        Some(_) if passphrase.is_some() => {
+            return Err("Give either a passphrase or another unlock method, not both".to_string());
+        }
+        Some(UnlockKey::Passphrase(key)) => (Some(key), None),
+        Some(UnlockKey::Keyfile(bytes)) => {
+            if !cli::supports_mount_flag("--key-file") {
+                return Err("The installed anylinuxfs does not support keyfiles".to_string());
+            }
+            (None, Some(bytes))
+        }
+        None => (passphrase, None),
+    };
     let elevation_state = elevation_state.inner().clone();
     let operation = format!("mount:{}", device);
     let operation_guard = elevation_state.begin_operation(operation.clone())?;
     let elevation_mode = operation_guard.mode();
+    if keyfile.is_some() && elevation_mode == ElevationMode::InteractiveTerminal {
+        return Err(
+            "Keyfiles cannot be used with Interactive Terminal elevation; enter the passphrase in Terminal instead"
+                .to_string(),
+        );
+    }
+    // Terminal prompts for the key itself, so a recovery key given here
+    // would never reach the CLI
+    if recovery_key && elevation_mode == ElevationMode::InteractiveTerminal {
+        return Err(
+            "Recovery keys cannot be passed on with Interactive Terminal elevation; enter the key in Terminal instead"
+                .to_string(),
+        );
+    }
 
-    // Sanitize extra_options with a whitelist to prevent command injection
     if let Some(ref opts) = extra_options {
-        let valid = opts.chars().all(|c| {
-            c.is_ascii_alphanumeric() || matches!(c, ',' | '.' | '_' | '-' | '=' | '/' | ':')
+        validate_extra_options(opts)?;
+    }
+    if let Some(ref raw) = raw_options {
+        validate_extra_options(raw)?;
+    }
+
+    // Check typed options against the partition's filesystem so typos and
+    // conflicts are reported before a VM is started
+    let mut typed_options = MountOptions::parse(extra_options.as_deref().unwrap_or(""));
+    if read_only.unwrap_or(false) {
+        typed_options.push_flag("ro");
+    }
+    let filesystem_id = filesystem.as_ref().map(|entry| entry.id.as_str());
+    let option_errors = typed_options.validate(filesystem_id);
+    if !option_errors.is_empty() {
+        let message = option_errors
+            .iter()
+            .map(|error| error.message.clone())
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Ok(MountCommandResult {
+            option_errors,
+            ..MountCommandResult::new(MountOutcome::InvalidOptions, message)
         });
-        if !valid {
-            return Err("Mount options contain invalid characters".to_string());
+    }
+
+    // Per-partition VM size: explicit values win over the ones remembered
+    // for this UUID, anything unset uses the global config
+    let explicit_resources = VmResources { ram_mb, vcpus };
+    let resources =
+        resolve_vm_resources(&vm_state, partition_uuid.as_deref(), explicit_resources)?;
+    let ram_arg = resources.ram_mb.map(|ram| ram.to_string());
+    let vcpus_arg = resources.vcpus.map(|cpus| cpus.to_string());
+
+    // Shared partitions mount through a GUI-managed copy of the selected
+    // action that carries the export override, with NFS served beyond localhost.
+    // A CLI without `--bind-addr` still mounts the partition, just not shared.
+    let mut share_skipped = false;
+    let share_action = match partition_uuid.as_deref().and_then(|uuid| shares.get(uuid)) {
+        Some(_) if !cli::supports_mount_flag("--bind-addr") => {
+            log::warn!("{} has share settings but the CLI has no --bind-addr; mounting unshared", device);
+            share_skipped = true;
+            None
+        }
+        Some(settings) => {
+            Some(write_share_action(
+                &settings.uuid,
+                selected_action.as_ref(),
+                &settings.export_override(),
+            )?)
+        }
+        None => None,
+    };
+    let shared = share_action.is_some();
+    let action = share_action.or(action);
+
+    // Packages the filesystem needs are installed into the Linux image first
+    let packages = filesystem
+        .as_ref()
+        .map(|entry| entry.packages.clone())
+        .unwrap_or_default();
+    // A failed check is logged rather than fatal: the mount reports a
+    // missing tool more precisely than the package check can.
+    if !packages.is_empty() && image_os(image.as_deref()) == "linux" {
+        let package_image = image.clone().unwrap_or_else(|| "alpine-latest".to_string());
+        let checked = tokio::task::spawn_blocking(move || ensure_packages(&package_image, &packages))
+            .await
+            .map_err(|e| format!("Task error: {}", e))?;
+        if let Err(e) = checked {
+            log::warn!("Could not install packages for {}, mounting anyway: {}", device, e);
         }
     }
 
+    // Supply a saved passphrase when none was given. Interactive Terminal
+    // prompts there, and saved secrets must never reach its script.
+    let saved_passphrase = match (&passphrase, &partition_uuid) {
+        (None, Some(uuid))
+            if keyfile.is_none() && elevation_mode != ElevationMode::InteractiveTerminal =>
+        {
+            secrets.get(uuid).unwrap_or_else(|e| {
+                log::warn!("Failed to read saved passphrase for {}: {}", uuid, e);
+                None
+            })
+        }
+        _ => None,
+    };
+    let used_saved_passphrase = saved_passphrase.is_some();
+    let passphrase_to_save = passphrase.clone().filter(|_| remember_passphrase.unwrap_or(false));
+    let passphrase = passphrase.or(saved_passphrase);
+    let key_supplied = passphrase.is_some() || keyfile.is_some();
+    let using_keyfile = keyfile.is_some();
+
+    // Report mount stages from the per-mount log while the command runs;
+    // tailing stops when this guard is dropped on return
+    let _progress = MountProgressTracker::start(app.clone(), device.clone());
+    // Journal the mount so a later session can adopt or clean it up; the
+    // entry is dropped again unless the mount succeeds
+    let journal_entry = app
+        .state::<Arc<SessionJournal>>()
+        .begin_mount(
+            &device,
+            elevation_mode,
+            MountParameters {
+                action: selected_action.as_ref().map(|selected| selected.name.clone()),
+                image: image.clone(),
+                ignore_permissions: ignore_permissions.unwrap_or(false),
+                raw_options: raw_options.clone(),
+                ram_mb,
+                vcpus,
+                unlocked: key_supplied,
+            },
+        );
+
     // Build combined mount options string
-    let ro = read_only.unwrap_or(false);
     let mut opts = Vec::new();
-    if ro {
-        opts.push("ro".to_string());
+    if !typed_options.is_empty() {
+        opts.push(typed_options.to_string());
     }
-    if let Some(ref extra) = extra_options {
-        let trimmed = extra.trim();
+    // Per-filesystem defaults from the support table; explicit options win
+    if let Some(defaults) = filesystem.as_ref().and_then(|entry| entry.mount_options.as_deref()) {
+        opts = merge_mount_options(defaults, &opts);
+    }
+    // Raw options are passed through unchecked for anything the typed model
+    // does not know about
+    if let Some(ref raw) = raw_options {
+        let trimmed = raw.trim();
         if !trimmed.is_empty() {
             opts.push(trimmed.to_string());
         }
@@ -632,6 +813,10 @@ pub async fn mount_disk(
     let mount_device = device.clone();
     let mount_operation = operation.clone();
     let mount_elevation_state = elevation_state.clone();
+    // Interactive elevation gets a longer window for approval and a disk passphrase
+    let mount_policy = elevation_state
+        .operation_policies()
+        .for_mode(Operation::Mount, elevation_mode);
     let mount_result: std::sync::Arc<
         std::sync::Mutex<Option<Result<String, CommandExecutionError>>>,
     > =
@@ -641,55 +826,71 @@ pub async fn mount_disk(
     let _mount_thread = tokio::task::spawn_blocking(move || {
         // Interactive Terminal elevation prompts there. Never place a disk
         // passphrase in a generated command file or process environment.
-        let effective_passphrase = if elevation_mode == ElevationMode::InteractiveTerminal {
-            None
-        } else {
-            Some(passphrase.unwrap_or_else(|| "##PROBE##".to_string()))
+        let effective_passphrase = cli::mount_passphrase(elevation_mode, passphrase);
+        let secret = match (&keyfile, &effective_passphrase) {
+            (Some(bytes), _) => Some(CliSecret::Keyfile(bytes)),
+            (None, Some(pass)) => Some(CliSecret::Passphrase(pass)),
+            (None, None) => None,
         };
-        let pass_ref = effective_passphrase.as_deref();
 
         let result = {
             let mut args: Vec<&str> = vec!["mount"];
             if ignore_permissions.unwrap_or(false) {
                 args.push("--ignore-permissions");
             }
+            if let Some(ref name) = action {
+                args.extend_from_slice(&["-a", name]);
+            }
+            if let Some(ref img) = image {
+                args.extend_from_slice(&["-i", img]);
+            }
             if let Some(ref combined) = combined_options {
                 args.extend_from_slice(&["-o", combined]);
             }
+            if let Some(ref ram) = ram_arg {
+                args.extend_from_slice(&["-r", ram]);
+            }
+            if let Some(ref cpus) = vcpus_arg {
+                args.extend_from_slice(&["-n", cpus]);
+            }
+            if keyfile.is_some() {
+                args.extend_from_slice(&["--key-file", "-"]);
+            }
+            if shared {
+                args.extend_from_slice(&["--bind-addr", "0.0.0.0"]);
+            }
             args.push(&mount_device);
-            execute_command_with_elevation(
-                &args,
-                true,
-                pass_ref,
-                false,
-                elevation_mode,
-                &mount_elevation_state,
-                TerminalInteraction::SecretPrompt {
-                    operation: mount_operation,
+            mount_policy.run(
+                "mount",
+                |attempt| {
+                    if attempt > 0 {
+                        // Release the VM the failed attempt may have left
+                        let _ = execute_command(&["stop", &mount_device], None, None, false);
+                    }
+                    execute_command_with_elevation(
+                        &args,
+                        true,
+                        secret,
+                        false,
+                        elevation_mode,
+                        &mount_elevation_state,
+                        TerminalInteraction::SecretPrompt {
+                            operation: mount_operation.clone(),
+                        },
+                        mount_policy,
+                    )
                 },
+                CommandExecutionError::is_transient,
             )
         };
 
         *mount_result_bg.lock().unwrap() = Some(result);
     });
 
-    // Helper to detect encryption-related output
-    let is_encryption_error = |text: &str| -> bool {
-        let lower = text.to_lowercase();
-        lower.contains("luks") || lower.contains("decrypt")
-            || lower.contains("passphrase") || lower.contains("password")
-            || lower.contains("encrypted") || lower.contains("wrong key")
-    };
-
-    // Poll `anylinuxfs status` concurrently while mount command runs. Interactive
-    // elevation gets a longer window for approval and a disk passphrase.
-    let mount_timeout_secs = if elevation_mode == ElevationMode::InteractiveTerminal {
-        INTERACTIVE_ELEVATION_TIMEOUT_SECS
-    } else {
-        MOUNT_TIMEOUT_SECS
-    };
-    let retries = mount_timeout_secs * 2;
-    for i in 0..retries {
+    // Poll `anylinuxfs status` concurrently while mount command runs, for
+    // as long as every attempt may take
+    let polls = mount_policy.budget().as_millis() / 500;
+    for i in 0..polls {
         if i > 0 {
             tokio::time::sleep(Duration::from_millis(500)).await;
         }
@@ -700,9 +901,9 @@ pub async fn mount_disk(
                 Ok(out) => out.clone(),
                 Err(error) => error.message(),
             };
-            if is_encryption_error(&output_text) {
+            if let Some(failure) = classify_encryption_error(&output_text, key_supplied) {
                 // Clean up leftover VM from the failed probe attempt
-                let _ = execute_command(&["stop", &device], false, None, false);
+                let _ = execute_command(&["stop", &device], None, None, false);
                 let _ = app.emit("status-changed", ());
                 if elevation_mode == ElevationMode::InteractiveTerminal {
                     return Ok(MountCommandResult::new(
@@ -710,10 +911,36 @@ pub async fn mount_disk(
                         output_text,
                     ));
                 }
+                if failure == EncryptionFailure::KeyRequired {
+                    return Ok(MountCommandResult::new(
+                        MountOutcome::EncryptionRequired,
+                        "This partition is encrypted. A passphrase is needed to mount it."
+                            .to_string(),
+                    ));
+                }
+                if used_saved_passphrase {
+                    // The volume's passphrase changed; drop the stale one so
+                    // the next attempt asks again
+                    if let Some(ref uuid) = partition_uuid {
+                        let _ = secrets.delete(uuid);
+                    }
+                    return Ok(MountCommandResult {
+                        used_saved_passphrase,
+                        ..MountCommandResult::new(
+                            MountOutcome::WrongKey,
+                            "The saved passphrase was rejected and has been forgotten. Enter the passphrase to mount this partition."
+                                .to_string(),
+                        )
+                    });
+                }
                 return Ok(MountCommandResult::new(
-                    MountOutcome::EncryptionRequired,
-                    "This partition is encrypted. A passphrase is needed to mount it."
-                        .to_string(),
+                    MountOutcome::WrongKey,
+                    if using_keyfile {
+                        "The keyfile was rejected. Check that it belongs to this partition."
+                    } else {
+                        "The passphrase or recovery key was rejected. Please try again."
+                    }
+                    .to_string(),
                 ));
             }
             if let Err(error) = result {
@@ -741,10 +968,42 @@ pub async fn mount_disk(
             && (mount_command_succeeded || elevation_state.mark_mount_persistent(&device))
         {
             let _ = app.emit("status-changed", ());
-            return Ok(MountCommandResult::new(
-                MountOutcome::Mounted,
-                Some("Mounted successfully".to_string()),
-            ));
+            journal_entry.commit(
+                get_mount_status_sync()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|mount| mount.device == device)
+                    .map(|mount| mount.mount_point),
+            );
+            if let Some(ref uuid) = partition_uuid {
+                if !explicit_resources.is_empty() {
+                    if let Err(e) = vm_state.remember(uuid, resources) {
+                        log::warn!("Failed to remember VM resources for {}: {}", uuid, e);
+                    }
+                }
+                if let Some(ref secret) = passphrase_to_save {
+                    if let Err(e) = secrets.set(uuid, secret) {
+                        log::warn!("Failed to save passphrase for {}: {}", uuid, e);
+                    }
+                }
+            }
+            let message = if share_skipped {
+                "Mounted without sharing: the installed anylinuxfs cannot share mounts with other machines"
+            } else {
+                "Mounted successfully"
+            };
+            let mut result = MountCommandResult::new(MountOutcome::Mounted, Some(message.to_string()));
+            result.used_saved_passphrase = used_saved_passphrase;
+            result.shared = shared;
+            if let Some(selected) = selected_action {
+                if !selected.capture_environment.is_empty() {
+                    result.captured_environment =
+                        wait_for_captured_environment(&mount_result, &selected.capture_environment)
+                            .await;
+                }
+                result.action = Some(selected.name);
+            }
+            return Ok(result);
         }
     }
 
@@ -754,36 +1013,245 @@ pub async fn mount_disk(
         MountOutcome::TimedOut,
         format!(
             "Mount operation timed out after {} seconds and cleanup was requested.",
-            mount_timeout_secs
+            mount_policy.budget().as_secs()
         ),
     ))
 }
 
-fn check_device_mounted(device: &str) -> bool {
-    crate::cli::get_status()
-        .map(|s| s.lines().any(|line| line.starts_with(device)))
+/// The CLI prints captured variables once the action's hooks have run, which
+/// can be after the volume is already visible. Wait briefly for it to exit.
+async fn wait_for_captured_environment(
+    mount_result: &std::sync::Mutex<Option<Result<String, CommandExecutionError>>>,
+    names: &[String],
+) -> HashMap<String, String> {
+    for i in 0..ACTION_CAPTURE_WAIT_SECS * 2 {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        if let Some(ref result) = *mount_result.lock().unwrap() {
+            return match result {
+                Ok(output) => parse_captured_environment(output, names),
+                Err(_) => HashMap::new(),
+            };
+        }
+    }
+    log::warn!("Mount command did not exit in time to report captured environment");
+    HashMap::new()
+}
+
+pub fn check_device_mounted(device: &str) -> bool {
+    get_mount_status_sync()
+        .map(|mounts| mounts.iter().any(|mount| mount.device == device))
         .unwrap_or(false)
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmountOutcome {
+    Unmounted,
+    Ejected,
+    Busy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnmountCommandResult {
+    pub outcome: UnmountOutcome,
+    pub message: Option<String>,
+    /// Processes holding the volume open when the outcome is `Busy`
+    pub blocking_processes: Vec<BlockingProcess>,
+}
+
+impl UnmountCommandResult {
+    fn new(outcome: UnmountOutcome, message: impl Into<Option<String>>) -> Self {
+        Self {
+            outcome,
+            message: message.into(),
+            blocking_processes: Vec::new(),
+        }
+    }
+}
+
+/// How long terminated processes get to release the volume before retrying
+const TERMINATE_WAIT_SECS: u64 = 5;
+
+/// Busy failures from `anylinuxfs unmount` (sanitized) and `diskutil eject`
+/// ("Dissenter" lines name the process that refused the unmount)
+fn is_busy_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("busy") || lower.contains("dissent")
+}
+
+/// Whether `candidate` is `device` itself or one of its partitions
+/// (disk4 -> disk4s1, sdb -> sdb1, nvme0n1 -> nvme0n1p2)
+fn is_device_or_partition(candidate: &str, device: &str) -> bool {
+    let Some(rest) = candidate.strip_prefix(device) else {
+        return false;
+    };
+    if rest.is_empty() {
+        return true;
+    }
+    let digits = if device.ends_with(|c: char| c.is_ascii_digit()) {
+        match rest.strip_prefix('s').or_else(|| rest.strip_prefix('p')) {
+            Some(digits) => digits,
+            None => return false,
+        }
+    } else {
+        rest
+    };
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Mount points released by unmounting `device` (all anylinuxfs mounts when
+/// None), including host mounts of the device's partitions
+fn affected_mount_points(device: Option<&str>) -> Vec<String> {
+    let mut mount_points: Vec<String> = get_mount_status_sync()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|mount| device.map(|d| is_device_or_partition(&mount.device, d)).unwrap_or(true))
+        .map(|mount| mount.mount_point)
+        .collect();
+
+    if let Some(device) = device {
+        for (mount_device, mount_point) in backend::current().system_mounts() {
+            if is_device_or_partition(&mount_device, device) && !mount_points.contains(&mount_point) {
+                mount_points.push(mount_point);
+            }
+        }
+    }
+    mount_points
+}
+
+fn find_blocking_processes(mount_points: &[String]) -> Vec<BlockingProcess> {
+    let backend = backend::current();
+    let mut processes: Vec<BlockingProcess> = Vec::new();
+    for mount_point in mount_points {
+        for process in backend.blocking_processes(Path::new(mount_point)) {
+            // Never offer to terminate the GUI itself
+            if process.pid == std::process::id() || processes.iter().any(|p| p.pid == process.pid) {
+                continue;
+            }
+            processes.push(process);
+        }
+    }
+    processes
+}
+
+fn busy_result(message: String, mount_points: &[String]) -> UnmountCommandResult {
+    let blocking_processes = find_blocking_processes(mount_points);
+    log::info!(
+        "Volume busy, {} blocking process(es) found: {:?}",
+        blocking_processes.len(),
+        blocking_processes
+    );
+    UnmountCommandResult {
+        blocking_processes,
+        ..UnmountCommandResult::new(UnmountOutcome::Busy, message)
+    }
+}
+
+/// Unmount `device`, or everything when None. A busy volume is retried as
+/// `policy` allows before its blocking processes are reported.
+pub fn run_unmount(
+    device: Option<&str>,
+    policy: &OperationPolicy,
+) -> Result<UnmountCommandResult, String> {
+    let mount_points = affected_mount_points(device);
+    let result = policy.run(
+        "unmount",
+        |_| match device {
+            Some(dev) => execute_command(&["unmount", dev], None, None, false),
+            None => execute_command(&["unmount"], None, None, false),
+        },
+        |message| is_transient_error(message),
+    );
+    match result {
+        Ok(output) => Ok(UnmountCommandResult::new(UnmountOutcome::Unmounted, output)),
+        Err(message) if is_busy_error(&message) => Ok(busy_result(message, &mount_points)),
+        Err(message) => Err(message),
+    }
+}
+
+fn run_eject(device: &str, policy: &OperationPolicy) -> Result<UnmountCommandResult, String> {
+    let mount_points = affected_mount_points(Some(device));
+
+    // Check if this device is mounted by anylinuxfs and unmount it first
+    if check_device_mounted(device) {
+        let unmounted = policy.run(
+            "unmount before eject",
+            |_| execute_command(&["unmount", device], None, None, false),
+            |message| is_transient_error(message),
+        );
+        if let Err(message) = unmounted {
+            if is_busy_error(&message) {
+                return Ok(busy_result(message, &mount_points));
+            }
+        }
+
+        // Wait for this device to be unmounted (up to 5 seconds)
+        for _ in 0..10 {
+            thread::sleep(Duration::from_millis(500));
+            if !check_device_mounted(device) {
+                break;
+            }
+        }
+    }
+
+    // Now safe to eject the disk
+    let ejected = policy.run(
+        "eject",
+        |_| {
+            let output = Command::new("diskutil")
+                .args(["eject", device])
+                .output()
+                .map_err(|e| format!("Failed to run diskutil: {}", e))?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(String::from_utf8_lossy(&output.stderr).to_string())
+            }
+        },
+        |stderr| is_transient_error(stderr),
+    );
+
+    match ejected {
+        Ok(()) => Ok(UnmountCommandResult::new(
+            UnmountOutcome::Ejected,
+            format!("Ejected {}", device),
+        )),
+        Err(stderr) if is_busy_error(&stderr) => Ok(busy_result(
+            "Disk is busy - it could not be ejected".to_string(),
+            &mount_points,
+        )),
+        Err(stderr) => Err(format!("Failed to eject: {}", stderr)),
+    }
+}
+
 #[tauri::command]
-pub async fn unmount_disk(app: AppHandle, device: Option<String>) -> Result<String, String> {
+pub async fn unmount_disk(
+    app: AppHandle,
+    device: Option<String>,
+) -> Result<UnmountCommandResult, String> {
     // Validate device path if provided
     if let Some(ref dev) = device {
         validate_device_path(dev)?;
     }
 
     // Run in blocking task with timeout
-    let unmount_future = tokio::task::spawn_blocking(move || {
-        match device {
-            Some(ref dev) => execute_command(&["unmount", dev], false, None, false),
-            None => execute_command(&["unmount"], false, None, false),
-        }
-    });
-
-    let result = timeout(Duration::from_secs(COMMAND_TIMEOUT_SECS), unmount_future)
+    let policy = app
+        .state::<Arc<ElevationState>>()
+        .operation_policies()
+        .get(Operation::Unmount);
+    let unmount_device = device.clone();
+    let unmount_future =
+        tokio::task::spawn_blocking(move || run_unmount(unmount_device.as_deref(), &policy));
+
+    let result = timeout(policy.budget(), unmount_future)
         .await
-        .map_err(|_| format!("Unmount timed out after {} seconds", COMMAND_TIMEOUT_SECS))?
+        .map_err(|_| format!("Unmount timed out after {} seconds", policy.budget().as_secs()))?
         .map_err(|e| format!("Task error: {}", e))?;
+    if matches!(result, Ok(UnmountCommandResult { outcome: UnmountOutcome::Unmounted, .. })) {
+        app.state::<Arc<SessionJournal>>().forget(device.as_deref());
+    }
 
     // Invalidate caches after unmount
     cache::invalidate_all();
@@ -796,54 +1264,111 @@ pub async fn unmount_disk(app: AppHandle, device: Option<String>) -> Result<Stri
 
 
 #[tauri::command]
-pub async fn eject_disk(device: String) -> Result<String, String> {
+pub async fn eject_disk(
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+    device: String,
+) -> Result<UnmountCommandResult, String> {
     // Validate device path before use
     validate_device_path(&device)?;
 
     // Eject (power down) a disk using diskutil
     // First unmount anylinuxfs if it has anything mounted, then eject
-    let eject_future = tokio::task::spawn_blocking(move || {
-        // Check if this device is mounted by anylinuxfs and unmount it first
-        if check_device_mounted(&device) {
-            let _ = execute_command(&["unmount", &device], false, None, false);
-
-            // Wait for this device to be unmounted (up to 5 seconds)
-            for _ in 0..10 {
-                thread::sleep(Duration::from_millis(500));
-                if !check_device_mounted(&device) {
-                    break;
-                }
+    let policy = elevation_state.operation_policies().get(Operation::Eject);
+    let eject_future = tokio::task::spawn_blocking(move || run_eject(&device, &policy));
+
+    timeout(policy.budget(), eject_future)
+        .await
+        .map_err(|_| format!("Eject timed out after {} seconds", policy.budget().as_secs()))?
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// Follow-up to a `Busy` unmount or eject: send SIGTERM to the selected
+/// processes and retry. Only processes that still hold the volume open are
+/// signalled, so the frontend cannot use this to terminate arbitrary pids.
+#[tauri::command]
+pub async fn terminate_blocking_processes(
+    app: AppHandle,
+    device: Option<String>,
+    pids: Vec<u32>,
+    eject: Option<bool>,
+) -> Result<UnmountCommandResult, String> {
+    if let Some(ref dev) = device {
+        validate_device_path(dev)?;
+    }
+    let eject = eject.unwrap_or(false);
+    if eject && device.is_none() {
+        return Err("A device is required to eject".to_string());
+    }
+
+    let policy = app
+        .state::<Arc<ElevationState>>()
+        .operation_policies()
+        .get(if eject { Operation::Eject } else { Operation::Unmount });
+    let retry_future = tokio::task::spawn_blocking(move || {
+        let mount_points = affected_mount_points(device.as_deref());
+        let targets: Vec<BlockingProcess> = find_blocking_processes(&mount_points)
+            .into_iter()
+            .filter(|process| pids.contains(&process.pid))
+            .collect();
+
+        for process in &targets {
+            log::info!("Terminating {} ({}) holding {}", process.command, process.pid, process.path);
+            let terminated = Command::new("kill")
+                .args(["-TERM", &process.pid.to_string()])
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false);
+            if !terminated {
+                log::warn!("Failed to terminate {} ({})", process.command, process.pid);
             }
         }
 
-        // Now safe to eject the disk
-        let output = Command::new("diskutil")
-            .args(["eject", &device])
-            .output()
-            .map_err(|e| format!("Failed to run diskutil: {}", e))?;
+        // Give the processes a moment to exit and release the volume
+        for _ in 0..TERMINATE_WAIT_SECS * 2 {
+            let still_blocking = find_blocking_processes(&mount_points)
+                .iter()
+                .any(|process| targets.iter().any(|t| t.pid == process.pid));
+            if !still_blocking {
+                break;
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
 
-        if output.status.success() {
-            Ok(format!("Ejected {}", device))
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(format!("Failed to eject: {}", stderr))
+        match device {
+            Some(ref dev) if eject => run_eject(dev, &policy),
+            _ => run_unmount(device.as_deref(), &policy),
         }
     });
 
-    timeout(Duration::from_secs(COMMAND_TIMEOUT_SECS), eject_future)
+    let timeout_secs = policy.budget().as_secs() + TERMINATE_WAIT_SECS;
+    let result = timeout(Duration::from_secs(timeout_secs), retry_future)
         .await
-        .map_err(|_| format!("Eject timed out after {} seconds", COMMAND_TIMEOUT_SECS))?
-        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|_| format!("Unmount timed out after {} seconds", timeout_secs))?
+        .map_err(|e| format!("Task error: {}", e))?;
+
+    cache::invalidate_all();
+    let _ = app.emit("status-changed", ());
+
+    result
 }
 
 #[tauri::command]
-pub async fn force_cleanup() -> Result<String, String> {
+pub async fn force_cleanup(
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+) -> Result<String, String> {
     // Use `anylinuxfs stop` to cleanly stop all instances
-    tokio::task::spawn_blocking(|| {
-        execute_command(&["stop"], false, None, false)
-    })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    let policy = elevation_state.operation_policies().get(Operation::Stop);
+    let stop_future = tokio::task::spawn_blocking(move || {
+        policy.run(
+            "stop",
+            |_| execute_command(&["stop"], None, None, false),
+            |message| is_transient_error(message),
+        )
+    });
+    timeout(policy.budget(), stop_future)
+        .await
+        .map_err(|_| format!("Stop timed out after {} seconds", policy.budget().as_secs()))?
+        .map_err(|e| format!("Task error: {}", e))?
 }
 
 #[cfg(test)]
@@ -910,6 +1435,35 @@ mod tests {
         assert_eq!(json["message"], "Cleanup was requested");
     }
 
+    #[test]
+    fn mount_requests_leave_unsent_fields_unset() {
+        let request: MountRequest = serde_json::from_value(serde_json::json!({
+            "device": "/dev/disk5s1",
+            "read_only": true,
+            "unlock": { "method": "keyfile", "path": "/Users/me/disk.key" },
+        }))
+        .expect("mount request should deserialize");
+        assert_eq!(request.device, "/dev/disk5s1");
+        assert_eq!(request.read_only, Some(true));
+        assert!(matches!(request.unlock, Some(UnlockMethod::Keyfile { .. })));
+        assert!(request.passphrase.is_none() && request.ram_mb.is_none());
+    }
+
+    #[test]
+    fn captured_environment_only_includes_requested_variables() {
+        let output = "\
+Mounting /dev/disk5s1...
+export SHARE_URL=\"nfs://192.168.64.2/mnt/disk5s1\"
+VM_IP=192.168.64.2
+UNRELATED=value
+";
+        let names = vec!["SHARE_URL".to_string(), "VM_IP".to_string(), "MISSING".to_string()];
+        let captured = parse_captured_environment(output, &names);
+        assert_eq!(captured.len(), 2);
+        assert_eq!(captured["SHARE_URL"], "nfs://192.168.64.2/mnt/disk5s1");
+        assert_eq!(captured["VM_IP"], "192.168.64.2");
+    }
+
     /// Issue #133: the CLI identifies BitLocker directly, so diskutil's
     /// misleading MS-DOS personality must not replace or disable it.
     #[test]
@@ -931,10 +1485,29 @@ mod tests {
 
         assert_eq!(bitlocker.len(), 2);
         assert!(bitlocker.iter().all(|partition| partition.encrypted));
-        assert!(is_linux_native_fs("BitLocker"));
+        let table = FilesystemTable::shipped();
+        assert!(table.is_native("BitLocker"));
         assert_eq!(
-            check_filesystem_support("BitLocker"),
+            table.support("BitLocker"),
             (true, Some("Encrypted (unlock key required)".to_string()))
         );
     }
+
+    #[test]
+    fn busy_detection_matches_unmount_and_eject_failures() {
+        assert!(is_busy_error("Device is busy - close any applications using it and try again"));
+        assert!(is_busy_error("Volume disk4s1 on disk4 failed to unmount: dissented by PID 4410 (zsh)"));
+        assert!(!is_busy_error("Filesystem is not mounted"));
+    }
+
+    #[test]
+    fn partitions_match_their_whole_disk_only() {
+        assert!(is_device_or_partition("/dev/disk4s1", "/dev/disk4"));
+        assert!(is_device_or_partition("/dev/disk4", "/dev/disk4"));
+        assert!(!is_device_or_partition("/dev/disk41", "/dev/disk4"));
+        assert!(!is_device_or_partition("/dev/disk41s1", "/dev/disk4"));
+        assert!(is_device_or_partition("/dev/sdb1", "/dev/sdb"));
+        assert!(is_device_or_partition("/dev/nvme0n1p2", "/dev/nvme0n1"));
+        assert!(!is_device_or_partition("/dev/disk4s2", "/dev/disk4s1"));
+    }
 }
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::backend;
+use crate::storage::write_private_toml;
+use super::disk::{check_device_mounted, mount, validate_extra_options, MountOutcome, MountRequest};
+use super::mount_options::MountOptions;
+
+/// A partition the user wants mounted automatically whenever it is attached.
+/// Keyed by partition UUID because device nodes change between plugs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Favorite {
+    pub uuid: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub extra_options: Option<String>,
+    #[serde(default)]
+    pub ignore_permissions: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteAutoMountEvent {
+    pub uuid: String,
+    pub device: String,
+    pub label: Option<String>,
+    pub outcome: MountOutcome,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredFavorites {
+    #[serde(default)]
+    favorites: Vec<Favorite>,
+}
+
+pub struct FavoritesState {
+    path: PathBuf,
+    favorites: Mutex<Vec<Favorite>>,
+}
+
+impl FavoritesState {
+    pub fn load(path: PathBuf) -> Self {
+        let favorites = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<StoredFavorites>(&contents).ok())
+            .map(|stored| stored.favorites)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            favorites: Mutex::new(favorites),
+        }
+    }
+
+    pub fn list(&self) -> Vec<Favorite> {
+        self.favorites
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Insert or replace the favorite with the same UUID.
+    pub fn upsert(&self, favorite: Favorite) -> Result<Vec<Favorite>, String> {
+        let mut favorites = self
+            .favorites
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = favorites.clone();
+        match updated
+            .iter_mut()
+            .find(|existing| existing.uuid.eq_ignore_ascii_case(&favorite.uuid))
+        {
+            Some(existing) => *existing = favorite,
+            None => updated.push(favorite),
+        }
+        write_favorites(&self.path, &updated)?;
+        *favorites = updated.clone();
+        Ok(updated)
+    }
+
+    pub fn remove(&self, uuid: &str) -> Result<Vec<Favorite>, String> {
+        let mut favorites = self
+            .favorites
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = favorites.clone();
+        let before = updated.len();
+        updated.retain(|favorite| !favorite.uuid.eq_ignore_ascii_case(uuid));
+        if updated.len() == before {
+            return Err(format!("Favorite '{}' not found", uuid));
+        }
+        write_favorites(&self.path, &updated)?;
+        *favorites = updated.clone();
+        Ok(updated)
+    }
+}
+
+fn write_favorites(path: &Path, favorites: &[Favorite]) -> Result<(), String> {
+    let stored = StoredFavorites {
+        favorites: favorites.to_vec(),
+    };
+    write_private_toml(path, &stored, "favorites")
+}
+
+/// Validate a partition UUID as reported by the host (hex digits and dashes)
+pub fn validate_partition_uuid(uuid: &str) -> Result<(), String> {
+    if uuid.is_empty() {
+        return Err("Partition UUID is required".to_string());
+    }
+    if uuid.len() > 64 || !uuid.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Err(format!("Invalid partition UUID '{}'", uuid));
+    }
+    Ok(())
+}
+
+/// Favorites whose UUID is attached now but was not attached at the previous check.
+fn newly_attached_favorites(
+    favorites: &[Favorite],
+    previously_attached: &HashSet<String>,
+    attached: &HashMap<String, String>,
+) -> Vec<(Favorite, String)> {
+    favorites
+        .iter()
+        .filter_map(|favorite| {
+            let uuid = favorite.uuid.to_uppercase();
+            if previously_attached.contains(&uuid) {
+                return None;
+            }
+            attached
+                .get(&uuid)
+                .map(|device| (favorite.clone(), device.clone()))
+        })
+        .collect()
+}
+
+/// Called by the disk watcher after disk changes settle with the attached
+/// partition UUIDs. `known` holds the UUIDs seen at the previous check so a
+/// drive that stays connected is only auto-mounted once per plug.
+pub fn auto_mount_attached_favorites(
+    app: &AppHandle,
+    known: &mut HashSet<String>,
+    attached: HashMap<String, String>,
+) {
+    let favorites = app.state::<Arc<FavoritesState>>().list();
+    let candidates = newly_attached_favorites(&favorites, known, &attached);
+    *known = attached.keys().cloned().collect();
+
+    if candidates.is_empty() {
+        return;
+    }
+    let system_mounts = backend::current().system_mounts();
+
+    for (favorite, device) in candidates {
+        let device_short = device.trim_start_matches("/dev/");
+        let mounted_by_system = system_mounts
+            .iter()
+            .any(|(mount_device, _)| mount_device.ends_with(device_short));
+        if mounted_by_system || check_device_mounted(&device) {
+            log::info!(
+                "Favorite {} attached as {} but is already mounted, skipping auto-mount",
+                favorite.uuid,
+                device
+            );
+            continue;
+        }
+
+        log::info!("Favorite {} attached as {}, auto-mounting", favorite.uuid, device);
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = mount(
+                app.clone(),
+                MountRequest {
+                    device: device.clone(),
+                    read_only: Some(favorite.read_only),
+                    extra_options: favorite.extra_options.clone(),
+                    ignore_permissions: Some(favorite.ignore_permissions),
+                    ..MountRequest::default()
+                },
+            )
+            .await;
+            let (outcome, message) = match result {
+                Ok(result) => (result.outcome, result.message),
+                Err(error) => (MountOutcome::Failed, Some(error)),
+            };
+            let _ = app.emit(
+                "favorite-auto-mount",
+                FavoriteAutoMountEvent {
+                    uuid: favorite.uuid,
+                    device,
+                    label: favorite.label,
+                    outcome,
+                    message,
+                },
+            );
+        });
+    }
+}
+
+#[tauri::command]
+pub fn list_favorites(state: tauri::State<'_, Arc<FavoritesState>>) -> Vec<Favorite> {
+    state.list()
+}
+
+#[tauri::command]
+pub fn add_favorite(
+    state: tauri::State<'_, Arc<FavoritesState>>,
+    favorite: Favorite,
+) -> Result<Vec<Favorite>, String> {
+    validate_partition_uuid(&favorite.uuid)?;
+    if let Some(ref opts) = favorite.extra_options {
+        validate_extra_options(opts)?;
+        let mut options = MountOptions::parse(opts);
+        if favorite.read_only {
+            options.push_flag("ro");
+        }
+        if let Some(error) = options.validate(None).into_iter().next() {
+            return Err(error.message);
+        }
+    }
+    state.upsert(Favorite {
+        uuid: favorite.uuid.to_uppercase(),
+        ..favorite
+    })
+}
+
+#[tauri::command]
+pub fn remove_favorite(
+    state: tauri::State<'_, Arc<FavoritesState>>,
+    uuid: String,
+) -> Result<Vec<Favorite>, String> {
+    validate_partition_uuid(&uuid)?;
+    state.remove(&uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn favorite(uuid: &str) -> Favorite {
+        Favorite {
+            uuid: uuid.to_string(),
+            label: Some("backup".to_string()),
+            read_only: true,
+            extra_options: Some("noatime".to_string()),
+            ignore_permissions: false,
+        }
+    }
+
+    #[test]
+    fn favorites_round_trip_and_replace_by_uuid() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("favorites.toml");
+        let state = FavoritesState::load(path.clone());
+        assert!(state.list().is_empty());
+
+        state.upsert(favorite("0A1B-2C3D")).unwrap();
+        state
+            .upsert(Favorite {
+                read_only: false,
+                ..favorite("0a1b-2c3d")
+            })
+            .unwrap();
+
+        let reloaded = FavoritesState::load(path).list();
+        assert_eq!(reloaded.len(), 1, "same UUID must replace, not duplicate");
+        assert!(!reloaded[0].read_only);
+        assert!(state.remove("0A1B-2C3D").unwrap().is_empty());
+        assert!(state.remove("0A1B-2C3D").is_err());
+    }
+
+    #[test]
+    fn only_reappearing_favorites_are_auto_mounted() {
+        let favorites = vec![favorite("AAAA-1111"), favorite("BBBB-2222"), favorite("cccc-3333")];
+        let previously_attached = HashSet::from(["AAAA-1111".to_string()]);
+        let attached = HashMap::from([
+            ("AAAA-1111".to_string(), "/dev/disk4s1".to_string()),
+            ("CCCC-3333".to_string(), "/dev/disk5s2".to_string()),
+        ]);
+
+        let candidates = newly_attached_favorites(&favorites, &previously_attached, &attached);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0.uuid, "cccc-3333");
+        assert_eq!(candidates[0].1, "/dev/disk5s2");
+    }
+
+    #[test]
+    fn partition_uuid_validation_rejects_shell_metacharacters() {
+        assert!(validate_partition_uuid("6F1C2A4E-8D2B-4C7E-9A3F-1B2C3D4E5F60").is_ok());
+        assert!(validate_partition_uuid("").is_err());
+        assert!(validate_partition_uuid("abc;reboot").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use super::apk::validate_package_name;
+use super::disk::validate_extra_options;
+
+/// Support table shipped with the app; see the file for the format
+const SHIPPED_TABLE: &str = include_str!("filesystems.toml");
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupportLevel {
+    Supported,
+    Limited,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilesystemEntry {
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub exact: bool,
+    pub support: SupportLevel,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub native: bool,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub packages: Vec<String>,
+    #[serde(default)]
+    pub mount_options: Option<String>,
+}
+
+impl FilesystemEntry {
+    fn matches(&self, fs_lower: &str) -> bool {
+        self.aliases.iter().any(|alias| {
+            let alias = alias.to_lowercase();
+            if self.exact {
+                fs_lower == alias
+            } else {
+                !alias.is_empty() && fs_lower.contains(&alias)
+            }
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Filesystem entry is missing an id".to_string());
+        }
+        if let Some(ref image) = self.image {
+            if image != "alpine" && image != "freebsd" {
+                return Err(format!(
+                    "Filesystem '{}': image must be \"alpine\" or \"freebsd\"",
+                    self.id
+                ));
+            }
+        }
+        for package in &self.packages {
+            validate_package_name(package)
+                .map_err(|e| format!("Filesystem '{}': {}", self.id, e))?;
+        }
+        if let Some(ref options) = self.mount_options {
+            validate_extra_options(options)
+                .map_err(|e| format!("Filesystem '{}': {}", self.id, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TableFile {
+    #[serde(default)]
+    filesystem: Vec<FilesystemEntry>,
+}
+
+fn parse_table(contents: &str) -> Result<Vec<FilesystemEntry>, String> {
+    let file: TableFile = toml::from_str(contents)
+        .map_err(|e| format!("Failed to parse filesystem table: {}", e))?;
+    for entry in &file.filesystem {
+        entry.validate()?;
+    }
+    Ok(file.filesystem)
+}
+
+/// Ordered filesystem support table; the first entry with a matching alias wins
+#[derive(Debug, Clone)]
+pub struct FilesystemTable {
+    entries: Vec<FilesystemEntry>,
+}
+
+impl FilesystemTable {
+    pub fn shipped() -> Self {
+        Self {
+            entries: parse_table(SHIPPED_TABLE).expect("shipped filesystem table is valid"),
+        }
+    }
+
+    /// Overlay user entries: same id replaces the shipped entry in place,
+    /// new ids are checked first so site-specific aliases take precedence
+    pub fn with_overrides(mut self, overrides: Vec<FilesystemEntry>) -> Self {
+        let mut added = Vec::new();
+        for entry in overrides {
+            match self.entries.iter_mut().find(|existing| existing.id == entry.id) {
+                Some(existing) => *existing = entry,
+                None => added.push(entry),
+            }
+        }
+        added.append(&mut self.entries);
+        Self { entries: added }
+    }
+
+    pub fn entries(&self) -> &[FilesystemEntry] {
+        &self.entries
+    }
+
+    pub fn lookup(&self, fs: &str) -> Option<&FilesystemEntry> {
+        let fs_lower = fs.trim().to_lowercase();
+        self.entries.iter().find(|entry| entry.matches(&fs_lower))
+    }
+
+    /// Whether anylinuxfs identified the filesystem itself, so the host's
+    /// personality must not replace it
+    pub fn is_native(&self, fs: &str) -> bool {
+        self.lookup(fs).map(|entry| entry.native).unwrap_or(false)
+    }
+
+    /// (mountable, note) for the partition list
+    pub fn support(&self, fs: &str) -> (bool, Option<String>) {
+        match self.lookup(fs) {
+            Some(entry) => (entry.support != SupportLevel::Unsupported, entry.note.clone()),
+            // Default: assume supported but note it's unverified
+            None => (true, Some(format!("Unverified: {}", fs))),
+        }
+    }
+}
+
+/// Effective support table plus the filesystem of each partition from the
+/// last listing, so mount_disk can apply per-filesystem defaults
+pub struct FilesystemSupportState {
+    override_path: PathBuf,
+    table: Mutex<FilesystemTable>,
+    listed: Mutex<HashMap<String, String>>,
+}
+
+impl FilesystemSupportState {
+    pub fn load(override_path: PathBuf) -> Self {
+        let table = match read_table(&override_path) {
+            Ok(table) => table,
+            Err(e) => {
+                log::error!("{}; using the shipped filesystem table", e);
+                FilesystemTable::shipped()
+            }
+        };
+        Self {
+            override_path,
+            table: Mutex::new(table),
+            listed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn table(&self) -> FilesystemTable {
+        self.table
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn reload(&self) -> Result<FilesystemTable, String> {
+        let table = read_table(&self.override_path)?;
+        *self
+            .table
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = table.clone();
+        Ok(table)
+    }
+
+    pub fn remember_listed(&self, filesystems: HashMap<String, String>) {
+        *self
+            .listed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = filesystems;
+    }
+
+    /// Support table entry for `device`. `filesystem` is the type the host
+    /// reports for the partition now; the last listing is only consulted when
+    /// the host does not identify it.
+    pub fn entry_for(&self, device: &str, filesystem: Option<&str>) -> Option<FilesystemEntry> {
+        let table = self.table();
+        if let Some(entry) = filesystem.and_then(|filesystem| table.lookup(filesystem)) {
+            return Some(entry.clone());
+        }
+        let listed = self
+            .listed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        table.lookup(listed.get(device)?).cloned()
+    }
+}
+
+fn read_table(override_path: &Path) -> Result<FilesystemTable, String> {
+    let table = FilesystemTable::shipped();
+    if !override_path.exists() {
+        return Ok(table);
+    }
+    let contents = fs::read_to_string(override_path)
+        .map_err(|e| format!("Failed to read {}: {}", override_path.display(), e))?;
+    Ok(table.with_overrides(parse_table(&contents)?))
+}
+
+/// Combine per-filesystem defaults with user options. User options win: a
+/// default is dropped when the user sets the same option, or its opposite
+/// for ro/rw.
+pub fn merge_mount_options(defaults: &str, user: &[String]) -> Vec<String> {
+    fn key(option: &str) -> &str {
+        match option.split('=').next().unwrap_or(option).trim() {
+            "rw" => "ro",
+            other => other,
+        }
+    }
+    let user_keys: Vec<&str> = user
+        .iter()
+        .flat_map(|options| options.split(','))
+        .map(key)
+        .collect();
+
+    defaults
+        .split(',')
+        .map(str::trim)
+        .filter(|option| !option.is_empty() && !user_keys.contains(&key(option)))
+        .map(str::to_string)
+        .chain(user.iter().cloned())
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_filesystem_support(
+    state: tauri::State<'_, Arc<FilesystemSupportState>>,
+) -> Vec<FilesystemEntry> {
+    state.table().entries().to_vec()
+}
+
+/// Re-read the user override file after editing it
+#[tauri::command]
+pub fn reload_filesystem_support(
+    state: tauri::State<'_, Arc<FilesystemSupportState>>,
+) -> Result<Vec<FilesystemEntry>, String> {
+    Ok(state.reload()?.entries().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_table_classifies_known_filesystems() {
+        let table = FilesystemTable::shipped();
+        assert_eq!(table.support("ext4"), (true, None));
+        assert_eq!(table.support("MS-DOS FAT32"), (true, None));
+        assert_eq!(table.support("NTFS"), (true, Some("NTFS via ntfs-3g".to_string())));
+        assert_eq!(
+            table.support("MS-DOS"),
+            (false, Some("Unknown FAT variant - may not mount".to_string()))
+        );
+        assert_eq!(table.support(""), (false, Some("Unknown filesystem".to_string())));
+        assert_eq!(table.support("udf"), (true, Some("Unverified: udf".to_string())));
+        assert!(table.is_native("Linux Filesystem"));
+        assert!(!table.is_native("Linux Filesystem Data"));
+        assert_eq!(table.lookup("zfs_member").unwrap().image.as_deref(), Some("freebsd"));
+    }
+
+    #[test]
+    fn overrides_replace_by_id_and_new_entries_take_precedence() {
+        let overrides = parse_table(
+            r#"
+[[filesystem]]
+id = "ntfs"
+aliases = ["ntfs"]
+support = "limited"
+note = "Site policy: read-only"
+native = true
+mount_options = "ro"
+
+[[filesystem]]
+id = "ocfs2"
+aliases = ["ocfs2", "ext"]
+support = "supported"
+packages = ["ocfs2-tools"]
+"#,
+        )
+        .unwrap();
+        let table = FilesystemTable::shipped().with_overrides(overrides);
+
+        let ntfs = table.lookup("ntfs").unwrap();
+        assert_eq!(ntfs.mount_options.as_deref(), Some("ro"));
+        assert_eq!(table.support("ntfs"), (true, Some("Site policy: read-only".to_string())));
+        assert_eq!(table.lookup("ocfs2").unwrap().packages, vec!["ocfs2-tools"]);
+        assert_eq!(table.lookup("ext4").unwrap().id, "ocfs2", "new entries are checked first");
+        assert_eq!(
+            table.entries().len(),
+            FilesystemTable::shipped().entries().len() + 1
+        );
+    }
+
+    #[test]
+    fn override_entries_are_validated() {
+        let unsafe_options = r#"
+[[filesystem]]
+id = "evil"
+aliases = ["evil"]
+support = "supported"
+mount_options = "noatime;reboot"
+"#;
+        assert!(parse_table(unsafe_options).is_err());
+        assert!(parse_table("[[filesystem]]\nid = \"x\"\nsupport = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn entry_follows_the_reported_filesystem_over_the_last_listing() {
+        let state = FilesystemSupportState::load(PathBuf::from("/nonexistent/filesystems.toml"));
+        state.remember_listed(HashMap::from([(
+            "/dev/disk4s1".to_string(),
+            "ext4".to_string(),
+        )]));
+        let entry = |device, filesystem| state.entry_for(device, filesystem).map(|e| e.id);
+        assert_eq!(entry("/dev/disk4s1", Some("zfs_member")).as_deref(), Some("zfs"));
+        assert_eq!(entry("/dev/disk4s1", None).as_deref(), Some("ext"));
+        assert_eq!(entry("/dev/disk5s1", Some("udf")), None);
+    }
+
+    #[test]
+    fn user_options_override_filesystem_defaults() {
+        let merged = merge_mount_options(
+            "ro,noatime,compress=zstd",
+            &["rw".to_string(), "compress=lzo".to_string()],
+        );
+        assert_eq!(merged, vec!["noatime", "rw", "compress=lzo"]);
+        assert_eq!(merge_mount_options("", &["ro".to_string()]), vec!["ro"]);
+    }
+}
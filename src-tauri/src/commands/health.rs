@@ -0,0 +1,477 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::cli::execute_command;
+use crate::elevation::ElevationState;
+use crate::timeouts::{is_transient_error, Operation};
+use super::disk::{check_device_mounted, validate_device_path};
+use super::log::WatcherState;
+use super::status::{get_mount_status_sync, MountInfo};
+
+/// Time between health checks of all mounts
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A stat on a healthy NFS mount returns in milliseconds; a hung VM blocks it
+/// until the NFS client gives up, which can take minutes
+const STAT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Run `f` on a worker thread and wait at most `timeout` for its result. A
+/// thread stuck in the kernel on a dead mount is left behind rather than
+/// blocking the caller.
+pub fn bounded<T: Send + 'static>(
+    timeout: Duration,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    rx.recv_timeout(timeout).ok()
+}
+
+/// Outcome of a bounded stat of a mount point
+#[derive(Debug, Clone, PartialEq)]
+enum Probe {
+    Responsive,
+    /// The stat did not return in time, or an earlier one is still stuck
+    TimedOut,
+    Failed(String),
+}
+
+/// Stat `mount_point` in a worker thread. While a previous stat of the same
+/// path is still blocked, no further thread is started for it.
+fn probe_mount_point(mount_point: &str, in_flight: &Arc<Mutex<HashSet<String>>>) -> Probe {
+    if !in_flight
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(mount_point.to_string())
+    {
+        return Probe::TimedOut;
+    }
+
+    let path = PathBuf::from(mount_point);
+    let key = mount_point.to_string();
+    let pending = in_flight.clone();
+    let result = bounded(STAT_TIMEOUT, move || {
+        let result = fs::metadata(&path);
+        pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&key);
+        result.map(|_| ()).map_err(|e| e.to_string())
+    });
+    match result {
+        Some(Ok(())) => Probe::Responsive,
+        Some(Err(error)) => Probe::Failed(error),
+        None => Probe::TimedOut,
+    }
+}
+
+/// `ps` listing of every process with its full command line
+fn process_listing() -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-ax", "-o", "pid=,command="])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Whether an `anylinuxfs mount` of `device` is in the `ps` listing. The CLI
+/// keeps running for the lifetime of the mount and hosts the VM. The program
+/// itself must be `anylinuxfs` (possibly run through sudo), so other commands
+/// naming the device or the GUI's own process do not count.
+fn vm_process_running(listing: &str, device: &str) -> bool {
+    listing.lines().any(|line| {
+        // Skip the pid, then a sudo wrapper with its options
+        let mut words = line.split_whitespace().skip(1).peekable();
+        if words.peek() == Some(&"sudo") {
+            words.next();
+            while words.peek().is_some_and(|word| word.starts_with('-')) {
+                words.next();
+            }
+        }
+        let is_cli = words
+            .next()
+            .and_then(|program| Path::new(program).file_name())
+            .is_some_and(|name| name == "anylinuxfs");
+        let args: Vec<&str> = words.collect();
+        let subcommand = args.iter().find(|arg| !arg.starts_with('-'));
+        is_cli && subcommand == Some(&"mount") && args.contains(&device)
+    })
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnhealthyReason {
+    /// The mount point did not answer a stat in time
+    Unresponsive,
+    /// The mount point answered with an error (stale handle, I/O error)
+    Inaccessible,
+    /// No anylinuxfs process is serving the device any more
+    VmNotRunning,
+}
+
+/// Payload of `mount-unhealthy`
+#[derive(Debug, Clone, Serialize)]
+pub struct MountUnhealthyEvent {
+    pub device: String,
+    pub mount_point: String,
+    pub reason: UnhealthyReason,
+    pub message: String,
+    /// Command to invoke with `device` and `mount_point` to recover
+    pub recovery_command: &'static str,
+}
+
+fn assess(probe: &Probe, vm_running: bool) -> Option<(UnhealthyReason, String)> {
+    if !vm_running {
+        return Some((
+            UnhealthyReason::VmNotRunning,
+            "The VM serving this mount is no longer running".to_string(),
+        ));
+    }
+    match probe {
+        Probe::Responsive => None,
+        Probe::TimedOut => Some((
+            UnhealthyReason::Unresponsive,
+            format!(
+                "The mount did not respond within {} seconds",
+                STAT_TIMEOUT.as_secs()
+            ),
+        )),
+        Probe::Failed(error) => Some((
+            UnhealthyReason::Inaccessible,
+            format!("The mount cannot be accessed: {}", error),
+        )),
+    }
+}
+
+/// Check every mount once. `reported` holds mount points already reported
+/// so each incident produces a single event.
+fn check_mounts(
+    mounts: &[MountInfo],
+    listing: Option<&str>,
+    in_flight: &Arc<Mutex<HashSet<String>>>,
+    reported: &mut HashSet<String>,
+) -> Vec<MountUnhealthyEvent> {
+    reported.retain(|mount_point| mounts.iter().any(|m| &m.mount_point == mount_point));
+
+    let mut events = Vec::new();
+    for mount in mounts {
+        // Without a process listing the VM is assumed to be running
+        let vm_running = listing.is_none_or(|listing| vm_process_running(listing, &mount.device));
+        let probe = probe_mount_point(&mount.mount_point, in_flight);
+        match assess(&probe, vm_running) {
+            Some((reason, message)) => {
+                if reported.insert(mount.mount_point.clone()) {
+                    events.push(MountUnhealthyEvent {
+                        device: mount.device.clone(),
+                        mount_point: mount.mount_point.clone(),
+                        reason,
+                        message,
+                        recovery_command: "recover_mount",
+                    });
+                }
+            }
+            None => {
+                reported.remove(&mount.mount_point);
+            }
+        }
+    }
+    events
+}
+
+#[tauri::command]
+pub fn start_health_monitor(app: AppHandle) -> Result<(), String> {
+    let state = app.state::<Arc<WatcherState>>().inner().clone();
+    if !state.health_monitor.begin() {
+        return Ok(()); // Already running
+    }
+
+    std::thread::spawn(move || {
+        let in_flight = Arc::new(Mutex::new(HashSet::new()));
+        let mut reported = HashSet::new();
+        let mut last_check: Option<Instant> = None;
+
+        while !state.health_monitor.stopping() {
+            if last_check.is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL) {
+                last_check = Some(Instant::now());
+                let mounts = match get_mount_status_sync() {
+                    Ok(mounts) => mounts,
+                    Err(e) => {
+                        log::warn!("Skipping health check: {}", e);
+                        continue;
+                    }
+                };
+                if !mounts.is_empty() {
+                    let listing = process_listing();
+                    for event in check_mounts(&mounts, listing.as_deref(), &in_flight, &mut reported)
+                    {
+                        log::warn!("{} is unhealthy: {}", event.mount_point, event.message);
+                        let _ = app.emit("mount-unhealthy", event);
+                    }
+                } else {
+                    reported.clear();
+                }
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+
+        state.health_monitor.end();
+    });
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryOutcome {
+    /// The device is no longer mounted and its mount point is gone
+    Recovered,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryStep {
+    pub name: &'static str,
+    pub ok: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryResult {
+    pub outcome: RecoveryOutcome,
+    pub message: Option<String>,
+    pub steps: Vec<RecoveryStep>,
+}
+
+/// Only mount points the CLI reports for `device`, or a direct child of
+/// /Volumes once the CLI has lost track of it, may be recovered
+fn validate_recovery_target(device: &str, mount_point: &str) -> Result<(), String> {
+    let path = Path::new(mount_point);
+    if !path.is_absolute() || mount_point.split('/').any(|part| part == "..") {
+        return Err(format!("Invalid mount point '{}'", mount_point));
+    }
+    let listed = get_mount_status_sync()
+        .unwrap_or_default()
+        .iter()
+        .any(|mount| mount.device == device && mount.mount_point == mount_point);
+    let under_volumes = path.parent() == Some(Path::new("/Volumes"));
+    if !listed && !under_volumes {
+        return Err(format!("{} is not a mount point of {}", mount_point, device));
+    }
+    Ok(())
+}
+
+fn force_unmount_host(mount_point: &str, timeout: Duration) -> Result<String, String> {
+    let mount_point = mount_point.to_string();
+    let output = bounded(timeout, move || {
+        #[cfg(target_os = "macos")]
+        let mut command = {
+            let mut command = Command::new("diskutil");
+            command.args(["unmount", "force"]);
+            command
+        };
+        #[cfg(not(target_os = "macos"))]
+        let mut command = {
+            let mut command = Command::new("umount");
+            command.args(["-f", "-l"]);
+            command
+        };
+        command.arg(&mount_point).output()
+    })
+    .ok_or_else(|| "Force unmount did not finish in time".to_string())?
+    .map_err(|e| format!("Failed to run force unmount: {}", e))?;
+
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+    .trim()
+    .to_string();
+    if output.status.success() {
+        Ok(text)
+    } else {
+        Err(text)
+    }
+}
+
+/// Remove `mount_point` if it is an empty directory that is no longer a mount
+/// (same device as its parent). Returns whether anything is left behind.
+pub fn remove_stale_mount_point(mount_point: &str) -> Result<String, String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = PathBuf::from(mount_point);
+    bounded(STAT_TIMEOUT, move || {
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok("Mount point already removed".to_string());
+            }
+            Err(e) => return Err(format!("Cannot inspect mount point: {}", e)),
+        };
+        let parent = path
+            .parent()
+            .and_then(|parent| fs::metadata(parent).ok())
+            .ok_or_else(|| "Cannot inspect mount point parent".to_string())?;
+        if metadata.dev() != parent.dev() {
+            return Err("Mount point is still mounted".to_string());
+        }
+        if !metadata.is_dir() {
+            return Err("Mount point is not a directory".to_string());
+        }
+        fs::remove_dir(&path)
+            .map(|_| "Removed stale mount point".to_string())
+            .map_err(|e| format!("Failed to remove mount point: {}", e))
+    })
+    .unwrap_or_else(|| Err("Mount point did not respond".to_string()))
+}
+
+fn step(name: &'static str, result: Result<String, String>) -> RecoveryStep {
+    match result {
+        Ok(message) => RecoveryStep { name, ok: true, message },
+        Err(message) => RecoveryStep { name, ok: false, message },
+    }
+}
+
+/// Recover a hung mount: force-unmount the host side, stop the VM serving
+/// `device` and remove the stale mount point. Every step runs even when an
+/// earlier one fails; the outcome reflects the final state.
+#[tauri::command]
+pub async fn recover_mount(
+    app: AppHandle,
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+    device: String,
+    mount_point: String,
+) -> Result<RecoveryResult, String> {
+    validate_device_path(&device)?;
+    let policies = elevation_state.operation_policies();
+    let unmount_timeout = policies.get(Operation::Unmount).timeout();
+    let stop_policy = policies.get(Operation::Stop);
+    let result = tokio::task::spawn_blocking(move || {
+        validate_recovery_target(&device, &mount_point)?;
+
+        let steps = vec![
+            step("force_unmount", force_unmount_host(&mount_point, unmount_timeout)),
+            step(
+                "stop_vm",
+                stop_policy.run(
+                    "stop",
+                    |_| execute_command(&["stop", &device], None, None, false),
+                    |message| is_transient_error(message),
+                ),
+            ),
+            step("remove_mount_point", remove_stale_mount_point(&mount_point)),
+        ];
+
+        let recovered = !check_device_mounted(&device)
+            && steps.iter().any(|s| s.name == "remove_mount_point" && s.ok);
+        let message = steps
+            .iter()
+            .filter(|s| !s.ok)
+            .map(|s| format!("{}: {}", s.name, s.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Ok::<_, String>(RecoveryResult {
+            outcome: if recovered {
+                RecoveryOutcome::Recovered
+            } else {
+                RecoveryOutcome::Failed
+            },
+            message: (!message.is_empty()).then_some(message),
+            steps,
+        })
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))??;
+
+    let _ = app.emit("status-changed", ());
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(mount_point: &str) -> MountInfo {
+        MountInfo {
+            device: "/dev/disk4s1".to_string(),
+            mount_point: mount_point.to_string(),
+            filesystem: Some("ext4".to_string()),
+            options: Vec::new(),
+            read_only: false,
+            ram_mb: None,
+            vcpus: None,
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn vm_process_is_an_anylinuxfs_mount_of_the_device() {
+        let listing = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/ps/mounted.txt"));
+        assert!(vm_process_running(listing, "/dev/disk4s1"));
+        assert!(vm_process_running(listing, "/dev/disk8s2"), "mount with an action and mount point");
+        assert!(!vm_process_running(listing, "/dev/disk4s"));
+        assert!(!vm_process_running(listing, "/dev/disk5s1"), "not an anylinuxfs process");
+        assert!(!vm_process_running(listing, "/dev/disk6s1"), "anylinuxfs only in an argument");
+        assert!(!vm_process_running(listing, "/dev/disk7s1"), "an unmount does not serve the device");
+        assert!(!vm_process_running(listing, "/dev/disk9s1"), "grep for the CLI");
+    }
+
+    #[test]
+    fn dead_vm_is_reported_once_per_incident() {
+        let directory = tempfile::tempdir().unwrap();
+        let mount_point = directory.path().to_string_lossy().to_string();
+        let mounts = vec![mount(&mount_point)];
+        let in_flight = Arc::new(Mutex::new(HashSet::new()));
+        let mut reported = HashSet::new();
+
+        let healthy = "4410 anylinuxfs mount /dev/disk4s1";
+        assert!(check_mounts(&mounts, Some(healthy), &in_flight, &mut reported).is_empty());
+
+        let events = check_mounts(&mounts, Some(""), &in_flight, &mut reported);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].reason, UnhealthyReason::VmNotRunning);
+        assert_eq!(events[0].recovery_command, "recover_mount");
+        assert!(check_mounts(&mounts, Some(""), &in_flight, &mut reported).is_empty());
+
+        // Healthy again re-arms the report
+        assert!(check_mounts(&mounts, Some(healthy), &in_flight, &mut reported).is_empty());
+        assert_eq!(check_mounts(&mounts, Some(""), &in_flight, &mut reported).len(), 1);
+    }
+
+    #[test]
+    fn stuck_stat_is_not_probed_again() {
+        let in_flight = Arc::new(Mutex::new(HashSet::from(["/Volumes/hung".to_string()])));
+        assert_eq!(probe_mount_point("/Volumes/hung", &in_flight), Probe::TimedOut);
+        assert!(matches!(
+            probe_mount_point("/nonexistent/anylinuxfs", &in_flight),
+            Probe::Failed(_)
+        ));
+        assert!(in_flight.lock().unwrap().len() == 1, "finished probes clear themselves");
+        assert!(bounded(Duration::from_millis(50), || std::thread::sleep(Duration::from_secs(1))).is_none());
+    }
+
+    #[test]
+    fn stale_mount_point_is_removed_only_when_empty() {
+        let directory = tempfile::tempdir().unwrap();
+        let stale = directory.path().join("backup");
+        fs::create_dir(&stale).unwrap();
+        fs::write(stale.join("file"), b"data").unwrap();
+        assert!(remove_stale_mount_point(&stale.to_string_lossy()).is_err());
+
+        fs::remove_file(stale.join("file")).unwrap();
+        assert!(remove_stale_mount_point(&stale.to_string_lossy()).is_ok());
+        assert!(!stale.exists());
+        assert!(remove_stale_mount_point(&stale.to_string_lossy()).is_ok());
+    }
+}
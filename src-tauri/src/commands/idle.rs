@@ -0,0 +1,604 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::backend;
+use crate::storage::write_private_toml;
+use super::disk::{unmount_disk, validate_device_path, UnmountOutcome};
+use super::favorite::validate_partition_uuid;
+use super::health::bounded;
+use super::log::WatcherState;
+use super::status::get_mount_status_sync;
+use super::usage::read_usage;
+
+/// Time between idle checks of all mounts
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long before the unmount `mount-idle-warning` is emitted
+const WARNING_LEAD: Duration = Duration::from_secs(120);
+
+/// Upper bound for stat and lsof on a mount point; a mount that does not
+/// answer is neither active nor idle for that check
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const MIN_IDLE_MINUTES: u32 = 5;
+const MAX_IDLE_MINUTES: u32 = 24 * 60;
+
+/// Unmount a partition after it has not been used for `idle_minutes`.
+/// Keyed by partition UUID so it follows the drive between plugs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlePolicy {
+    pub uuid: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub idle_minutes: u32,
+}
+
+/// Payload of `mount-idle-warning`
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleWarningEvent {
+    pub device: String,
+    pub mount_point: String,
+    pub uuid: Option<String>,
+    pub idle_minutes: u32,
+    pub unmount_in_seconds: u64,
+}
+
+/// Payload of `mount-idle-unmounted`
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleUnmountEvent {
+    pub device: String,
+    pub mount_point: String,
+    /// None when the unmount failed
+    pub outcome: Option<UnmountOutcome>,
+    pub message: Option<String>,
+}
+
+/// What the mount point looked like at one check. Any difference between
+/// checks counts as activity.
+#[derive(Debug, Clone, PartialEq)]
+struct ActivitySignature {
+    accessed: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    used_bytes: u64,
+    used_inodes: u64,
+}
+
+fn read_signature(mount_point: &str) -> Option<ActivitySignature> {
+    let path = PathBuf::from(mount_point);
+    let metadata = bounded(PROBE_TIMEOUT, move || fs::metadata(&path).ok()).flatten()?;
+    let usage = read_usage(mount_point)?;
+    Some(ActivitySignature {
+        accessed: metadata.accessed().ok(),
+        modified: metadata.modified().ok(),
+        used_bytes: usage.used_bytes,
+        used_inodes: usage.used_inodes,
+    })
+}
+
+/// Whether any process other than the GUI has files open on the mount
+fn in_use(mount_point: &str) -> bool {
+    let path = PathBuf::from(mount_point);
+    bounded(PROBE_TIMEOUT, move || {
+        backend::current()
+            .blocking_processes(&path)
+            .iter()
+            .any(|process| process.pid != std::process::id())
+    })
+    .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IdleAction {
+    Warn(Duration),
+    Unmount,
+}
+
+/// Idle bookkeeping for one mounted device
+#[derive(Debug)]
+struct IdleTracker {
+    mount_point: String,
+    uuid: Option<String>,
+    signature: Option<ActivitySignature>,
+    last_activity: Instant,
+    postponed_until: Option<Instant>,
+    warned: bool,
+}
+
+impl IdleTracker {
+    fn new(mount_point: String, uuid: Option<String>, now: Instant) -> Self {
+        Self {
+            mount_point,
+            uuid,
+            signature: None,
+            last_activity: now,
+            postponed_until: None,
+            warned: false,
+        }
+    }
+
+    fn touch(&mut self, now: Instant) {
+        self.last_activity = now;
+        self.postponed_until = None;
+        self.warned = false;
+    }
+
+    /// Record one check. The first signature is only a baseline; an
+    /// unreadable one keeps the previous baseline.
+    fn observe(&mut self, signature: Option<ActivitySignature>, in_use: bool, now: Instant) {
+        let changed = match (&self.signature, &signature) {
+            (Some(previous), Some(current)) => previous != current,
+            _ => false,
+        };
+        if in_use || changed {
+            self.touch(now);
+        }
+        if signature.is_some() {
+            self.signature = signature;
+        }
+    }
+
+    fn postpone(&mut self, by: Duration, now: Instant) {
+        self.postponed_until = Some(now + by);
+        self.warned = false;
+    }
+
+    fn next_action(&mut self, timeout: Duration, now: Instant) -> Option<IdleAction> {
+        let deadline = match self.postponed_until {
+            Some(postponed) => postponed.max(self.last_activity + timeout),
+            None => self.last_activity + timeout,
+        };
+        let remaining = deadline.saturating_duration_since(now);
+        if remaining.is_zero() {
+            Some(IdleAction::Unmount)
+        } else if remaining <= WARNING_LEAD && !self.warned {
+            self.warned = true;
+            Some(IdleAction::Warn(remaining))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredIdlePolicies {
+    #[serde(default)]
+    policies: Vec<IdlePolicy>,
+}
+
+/// Idle policies by UUID, session timeouts by device and the tracker of
+/// every mounted device
+pub struct IdlePolicyState {
+    path: PathBuf,
+    policies: Mutex<Vec<IdlePolicy>>,
+    /// Timeouts set for the current mount only, keyed by device
+    mount_timeouts: Mutex<HashMap<String, u32>>,
+    trackers: Mutex<HashMap<String, IdleTracker>>,
+}
+
+impl IdlePolicyState {
+    pub fn load(path: PathBuf) -> Self {
+        let policies = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<StoredIdlePolicies>(&contents).ok())
+            .map(|stored| stored.policies)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            policies: Mutex::new(policies),
+            mount_timeouts: Mutex::new(HashMap::new()),
+            trackers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<IdlePolicy> {
+        self.policies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Insert or replace the policy with the same UUID
+    pub fn upsert(&self, mut policy: IdlePolicy) -> Result<Vec<IdlePolicy>, String> {
+        validate_partition_uuid(&policy.uuid)?;
+        validate_idle_minutes(policy.idle_minutes)?;
+        policy.uuid = policy.uuid.to_uppercase();
+
+        let mut policies = self
+            .policies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = policies.clone();
+        match updated.iter_mut().find(|existing| existing.uuid == policy.uuid) {
+            Some(existing) => *existing = policy,
+            None => updated.push(policy),
+        }
+        write_idle_policies(&self.path, &updated)?;
+        *policies = updated.clone();
+        Ok(updated)
+    }
+
+    pub fn remove(&self, uuid: &str) -> Result<Vec<IdlePolicy>, String> {
+        let mut policies = self
+            .policies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = policies.clone();
+        let before = updated.len();
+        updated.retain(|policy| !policy.uuid.eq_ignore_ascii_case(uuid));
+        if updated.len() == before {
+            return Err(format!("Idle policy for '{}' not found", uuid));
+        }
+        write_idle_policies(&self.path, &updated)?;
+        *policies = updated.clone();
+        Ok(updated)
+    }
+
+    pub fn set_mount_timeout(&self, device: &str, minutes: Option<u32>) -> Result<(), String> {
+        let mut timeouts = self
+            .mount_timeouts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match minutes {
+            Some(minutes) => {
+                validate_idle_minutes(minutes)?;
+                timeouts.insert(device.to_string(), minutes);
+            }
+            None => {
+                timeouts.remove(device);
+            }
+        }
+        Ok(())
+    }
+
+    /// A session timeout for the mount wins over the policy of its UUID
+    fn idle_minutes(&self, device: &str, uuid: Option<&str>) -> Option<u32> {
+        let session = self
+            .mount_timeouts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(device)
+            .copied();
+        session.or_else(|| {
+            let uuid = uuid?;
+            self.list()
+                .iter()
+                .find(|policy| policy.uuid.eq_ignore_ascii_case(uuid))
+                .map(|policy| policy.idle_minutes)
+        })
+    }
+
+    /// Push the idle deadline of `device` back by `minutes`, or by its whole
+    /// timeout when not given
+    pub fn postpone(&self, device: &str, minutes: Option<u32>) -> Result<(), String> {
+        let mut trackers = self
+            .trackers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let tracker = trackers
+            .get_mut(device)
+            .ok_or_else(|| format!("{} is not tracked for idle unmount", device))?;
+        let minutes = match minutes {
+            Some(minutes) => minutes,
+            None => self
+                .idle_minutes(device, tracker.uuid.as_deref())
+                .ok_or_else(|| format!("{} has no idle timeout", device))?,
+        };
+        validate_idle_minutes(minutes)?;
+        tracker.postpone(Duration::from_secs(minutes as u64 * 60), Instant::now());
+        Ok(())
+    }
+
+    /// Forget trackers and session timeouts of devices no longer mounted and
+    /// start tracking new mounts
+    fn sync_mounts(&self, mounts: &[(String, String)], uuids: &HashMap<String, String>) {
+        let now = Instant::now();
+        let mut trackers = self
+            .trackers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        trackers.retain(|device, tracker| {
+            mounts
+                .iter()
+                .any(|(mounted, mount_point)| mounted == device && *mount_point == tracker.mount_point)
+        });
+        for (device, mount_point) in mounts {
+            trackers.entry(device.clone()).or_insert_with(|| {
+                IdleTracker::new(mount_point.clone(), uuids.get(device).cloned(), now)
+            });
+        }
+        drop(trackers);
+
+        self.mount_timeouts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .retain(|device, _| mounts.iter().any(|(mounted, _)| mounted == device));
+    }
+}
+
+fn validate_idle_minutes(minutes: u32) -> Result<(), String> {
+    if !(MIN_IDLE_MINUTES..=MAX_IDLE_MINUTES).contains(&minutes) {
+        return Err(format!(
+            "Idle timeout must be between {} and {} minutes",
+            MIN_IDLE_MINUTES, MAX_IDLE_MINUTES
+        ));
+    }
+    Ok(())
+}
+
+fn write_idle_policies(path: &Path, policies: &[IdlePolicy]) -> Result<(), String> {
+    let stored = StoredIdlePolicies {
+        policies: policies.to_vec(),
+    };
+    write_private_toml(path, &stored, "idle policies")
+}
+
+/// One pass over all mounts: observe activity, warn and collect the mounts
+/// whose idle deadline passed
+fn check_idle_mounts(app: &AppHandle, state: &IdlePolicyState) -> Vec<(String, String)> {
+    // Without a listing the trackers are left alone rather than treating
+    // every mount as gone and restarting its idle clock
+    let mounts = match get_mount_status_sync() {
+        Ok(mounts) => mounts,
+        Err(e) => {
+            log::warn!("Skipping idle check: {}", e);
+            return Vec::new();
+        }
+    };
+    let mounts: Vec<(String, String)> = mounts
+        .into_iter()
+        .map(|mount| (mount.device, mount.mount_point))
+        .collect();
+
+    let untracked = {
+        let trackers = state
+            .trackers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        mounts.iter().any(|(device, _)| !trackers.contains_key(device))
+    };
+    // Partition UUIDs are only looked up when a new mount appears
+    let uuids = if untracked {
+        backend::uuids_by_device(&backend::current().partition_details())
+    } else {
+        HashMap::new()
+    };
+    state.sync_mounts(&mounts, &uuids);
+
+    let mut expired = Vec::new();
+    for (device, mount_point) in &mounts {
+        let uuid = state
+            .trackers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(device)
+            .and_then(|tracker| tracker.uuid.clone());
+        let idle_minutes = state.idle_minutes(device, uuid.as_deref());
+
+        // Probe without holding the lock so postpone requests are not blocked
+        let observation = idle_minutes.map(|_| (read_signature(mount_point), in_use(mount_point)));
+
+        let now = Instant::now();
+        let mut trackers = state
+            .trackers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(tracker) = trackers.get_mut(device) else {
+            continue;
+        };
+        let (Some(idle_minutes), Some((signature, busy))) = (idle_minutes, observation) else {
+            // Mounts without a policy stay fresh, so adding one later does
+            // not unmount them straight away
+            tracker.touch(now);
+            continue;
+        };
+        tracker.observe(signature, busy, now);
+
+        match tracker.next_action(Duration::from_secs(idle_minutes as u64 * 60), now) {
+            Some(IdleAction::Warn(remaining)) => {
+                log::info!(
+                    "{} idle for {} minutes, unmounting in {}s",
+                    mount_point,
+                    idle_minutes,
+                    remaining.as_secs()
+                );
+                let _ = app.emit(
+                    "mount-idle-warning",
+                    IdleWarningEvent {
+                        device: device.clone(),
+                        mount_point: mount_point.clone(),
+                        uuid: uuid.clone(),
+                        idle_minutes,
+                        unmount_in_seconds: remaining.as_secs(),
+                    },
+                );
+            }
+            Some(IdleAction::Unmount) => {
+                // A failed unmount is retried only after another full timeout
+                tracker.touch(now);
+                expired.push((device.clone(), mount_point.clone()));
+            }
+            None => {}
+        }
+    }
+    expired
+}
+
+#[tauri::command]
+pub fn start_idle_monitor(app: AppHandle) -> Result<(), String> {
+    let watchers = app.state::<Arc<WatcherState>>().inner().clone();
+    if !watchers.idle_monitor.begin() {
+        return Ok(()); // Already running
+    }
+
+    std::thread::spawn(move || {
+        let state = app.state::<Arc<IdlePolicyState>>().inner().clone();
+        let mut last_check: Option<Instant> = None;
+
+        while !watchers.idle_monitor.stopping() {
+            if last_check.is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL) {
+                last_check = Some(Instant::now());
+                for (device, mount_point) in check_idle_mounts(&app, &state) {
+                    if watchers.idle_monitor.stopping() {
+                        break;
+                    }
+                    log::info!("Unmounting idle {} ({})", mount_point, device);
+                    let result = tauri::async_runtime::block_on(unmount_disk(
+                        app.clone(),
+                        Some(device.clone()),
+                    ));
+                    let event = match result {
+                        Ok(result) => IdleUnmountEvent {
+                            device,
+                            mount_point,
+                            outcome: Some(result.outcome),
+                            message: result.message,
+                        },
+                        Err(message) => IdleUnmountEvent {
+                            device,
+                            mount_point,
+                            outcome: None,
+                            message: Some(message),
+                        },
+                    };
+                    let _ = app.emit("mount-idle-unmounted", event);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+
+        watchers.idle_monitor.end();
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_idle_policies(state: tauri::State<'_, Arc<IdlePolicyState>>) -> Vec<IdlePolicy> {
+    state.list()
+}
+
+#[tauri::command]
+pub fn set_idle_policy(
+    state: tauri::State<'_, Arc<IdlePolicyState>>,
+    policy: IdlePolicy,
+) -> Result<Vec<IdlePolicy>, String> {
+    state.upsert(policy)
+}
+
+#[tauri::command]
+pub fn remove_idle_policy(
+    state: tauri::State<'_, Arc<IdlePolicyState>>,
+    uuid: String,
+) -> Result<Vec<IdlePolicy>, String> {
+    state.remove(&uuid)
+}
+
+/// Idle timeout for the current mount of `device` only; `None` clears it
+#[tauri::command]
+pub fn set_mount_idle_timeout(
+    state: tauri::State<'_, Arc<IdlePolicyState>>,
+    device: String,
+    minutes: Option<u32>,
+) -> Result<(), String> {
+    validate_device_path(&device)?;
+    state.set_mount_timeout(&device, minutes)
+}
+
+#[tauri::command]
+pub fn postpone_idle_unmount(
+    state: tauri::State<'_, Arc<IdlePolicyState>>,
+    device: String,
+    minutes: Option<u32>,
+) -> Result<(), String> {
+    validate_device_path(&device)?;
+    state.postpone(&device, minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    fn signature(used_bytes: u64) -> Option<ActivitySignature> {
+        Some(ActivitySignature {
+            accessed: None,
+            modified: None,
+            used_bytes,
+            used_inodes: 10,
+        })
+    }
+
+    #[test]
+    fn idle_mount_is_warned_then_unmounted() {
+        let start = Instant::now();
+        let mut tracker = IdleTracker::new("/Volumes/usb".to_string(), None, start);
+        tracker.observe(signature(100), false, start);
+
+        let minute = |n: u64| start + Duration::from_secs(n * 60);
+        assert_eq!(tracker.next_action(TIMEOUT, minute(5)), None);
+        assert_eq!(
+            tracker.next_action(TIMEOUT, minute(8) + Duration::from_secs(30)),
+            Some(IdleAction::Warn(Duration::from_secs(90)))
+        );
+        assert_eq!(tracker.next_action(TIMEOUT, minute(9)), None, "warned once");
+        assert_eq!(tracker.next_action(TIMEOUT, minute(10)), Some(IdleAction::Unmount));
+    }
+
+    #[test]
+    fn activity_and_postpone_move_the_deadline() {
+        let start = Instant::now();
+        let minute = |n: u64| start + Duration::from_secs(n * 60);
+        let mut tracker = IdleTracker::new("/Volumes/usb".to_string(), None, start);
+        tracker.observe(signature(100), false, start);
+
+        // Writes change the signature, open files keep the mount busy
+        tracker.observe(signature(200), false, minute(6));
+        assert_eq!(tracker.next_action(TIMEOUT, minute(10)), None);
+        tracker.observe(signature(200), true, minute(9));
+        assert_eq!(tracker.next_action(TIMEOUT, minute(15)), None);
+        // An unreadable signature is neither activity nor a new baseline
+        tracker.observe(None, false, minute(16));
+        tracker.observe(signature(200), false, minute(17));
+        assert!(matches!(tracker.next_action(TIMEOUT, minute(18)), Some(IdleAction::Warn(_))));
+
+        tracker.postpone(Duration::from_secs(30 * 60), minute(18));
+        assert_eq!(tracker.next_action(TIMEOUT, minute(40)), None);
+        assert_eq!(tracker.next_action(TIMEOUT, minute(48)), Some(IdleAction::Unmount));
+    }
+
+    #[test]
+    fn session_timeout_wins_over_uuid_policy() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("idle.toml");
+        let state = IdlePolicyState::load(path.clone());
+        state
+            .upsert(IdlePolicy {
+                uuid: "6f1c2a4e".to_string(),
+                label: None,
+                idle_minutes: 30,
+            })
+            .unwrap();
+        assert!(state
+            .upsert(IdlePolicy {
+                uuid: "6F1C2A4E".to_string(),
+                label: None,
+                idle_minutes: 1,
+            })
+            .is_err());
+
+        assert_eq!(state.idle_minutes("/dev/disk4s1", Some("6F1C2A4E")), Some(30));
+        state.set_mount_timeout("/dev/disk4s1", Some(15)).unwrap();
+        assert_eq!(state.idle_minutes("/dev/disk4s1", Some("6F1C2A4E")), Some(15));
+
+        // Unmounting forgets the session timeout but not the policy
+        state.sync_mounts(&[], &HashMap::new());
+        assert_eq!(state.idle_minutes("/dev/disk4s1", Some("6F1C2A4E")), Some(30));
+        assert_eq!(IdlePolicyState::load(path).list()[0].uuid, "6F1C2A4E");
+    }
+}
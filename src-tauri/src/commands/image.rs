@@ -1,5 +1,5 @@
-use serde::Serialize;
-use crate::cli::execute_command;
+use serde::{Deserialize, Serialize};
+use crate::cli::{self, execute_command, CliOutput};
 
 /// Validate image name format to prevent path traversal or command injection.
 /// Image names should only contain alphanumeric characters, hyphens, dots, and underscores.
@@ -17,41 +17,46 @@ pub fn validate_image_name(image: &str) -> Result<(), String> {
     Ok(())
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VmImage {
     pub name: String,
     pub installed: bool,
 }
 
-#[tauri::command]
-pub fn list_images() -> Result<Vec<VmImage>, String> {
-    let output = execute_command(&["image", "list"], false, None, false)?;
+impl CliOutput for Vec<VmImage> {
+    /// One image per line, installed ones suffixed with "(installed)"
+    fn from_text(output: &str) -> Result<Self, String> {
+        let mut images = Vec::new();
+        for line in output.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
 
-    let mut images = Vec::new();
-    for line in output.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+            let installed = line.ends_with("(installed)");
+            let name = if installed {
+                line.trim_end_matches("(installed)").trim().to_string()
+            } else {
+                line.to_string()
+            };
 
-        let installed = line.ends_with("(installed)");
-        let name = if installed {
-            line.trim_end_matches("(installed)").trim().to_string()
-        } else {
-            line.to_string()
-        };
+            images.push(VmImage { name, installed });
+        }
 
-        images.push(VmImage { name, installed });
+        Ok(images)
     }
+}
 
-    Ok(images)
+#[tauri::command]
+pub fn list_images() -> Result<Vec<VmImage>, String> {
+    cli::query(&["image", "list"])
 }
 
 #[tauri::command]
 pub async fn install_image(name: String) -> Result<(), String> {
     validate_image_name(&name)?;
     tokio::task::spawn_blocking(move || {
-        execute_command(&["image", "install", &name], false, None, false)?;
+        execute_command(&["image", "install", &name], None, None, false)?;
         Ok(())
     })
     .await
@@ -62,9 +67,66 @@ pub async fn install_image(name: String) -> Result<(), String> {
 pub async fn uninstall_image(name: String) -> Result<(), String> {
     validate_image_name(&name)?;
     tokio::task::spawn_blocking(move || {
-        execute_command(&["image", "uninstall", &name], false, None, false)?;
+        execute_command(&["image", "uninstall", &name], None, None, false)?;
         Ok(())
     })
     .await
     .map_err(|e| format!("Task error: {}", e))?
 }
+
+/// Image to mount a filesystem with when the user did not pick one, from the
+/// support table's image family. Alpine is the CLI's default image, so it
+/// needs no `-i`.
+pub fn image_for_family(family: &str) -> Result<Option<String>, String> {
+    if family == "alpine" {
+        return Ok(None);
+    }
+    pick_installed_image(family, &list_images()?).map(Some)
+}
+
+/// Version parts of an image name, numeric ones compared as numbers so
+/// 14.10 sorts after 14.9
+fn version_key(version: &str) -> Vec<(Option<u64>, String)> {
+    version
+        .split(['.', '-'])
+        .map(|part| (part.parse().ok(), part.to_string()))
+        .collect()
+}
+
+/// Installed image of `family`, preferring the `-latest` one, then the
+/// highest version
+fn pick_installed_image(family: &str, images: &[VmImage]) -> Result<String, String> {
+    let prefix = format!("{}-", family);
+    images
+        .iter()
+        .filter(|image| image.installed && image.name.starts_with(&prefix))
+        .map(|image| image.name.clone())
+        .max_by_key(|name| (name.ends_with("-latest"), version_key(&name[prefix.len()..])))
+        .ok_or_else(|| {
+            format!(
+                "This filesystem needs a {} image; install one from the Images page",
+                family
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn family_resolves_to_an_installed_image() {
+        let images = Vec::<VmImage>::from_text(
+            "alpine-latest (installed)\nfreebsd-14.2 (installed)\nfreebsd-14.3 (installed)\nfreebsd-latest\n",
+        )
+        .unwrap();
+        assert_eq!(pick_installed_image("freebsd", &images), Ok("freebsd-14.3".to_string()));
+        assert!(pick_installed_image("freebsd", &images[..1]).is_err());
+
+        let images = Vec::<VmImage>::from_text(
+            "freebsd-14.9 (installed)\nfreebsd-14.10 (installed)\nfreebsd-9.3 (installed)\n",
+        )
+        .unwrap();
+        assert_eq!(pick_installed_image("freebsd", &images), Ok("freebsd-14.10".to_string()));
+    }
+}
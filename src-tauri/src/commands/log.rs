@@ -8,6 +8,39 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 use crate::paths::{get_log_dir, get_log_paths};
+use crate::backend;
+use super::favorite::auto_mount_attached_favorites;
+use super::topology::DiskSnapshot;
+
+/// Running and stop flags of a background polling thread
+#[derive(Default)]
+pub struct MonitorFlags {
+    running: AtomicBool,
+    stop: AtomicBool,
+}
+
+impl MonitorFlags {
+    /// Claim the thread; false when one is already running
+    pub fn begin(&self) -> bool {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        self.stop.store(false, Ordering::SeqCst);
+        true
+    }
+
+    pub fn stopping(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    pub fn end(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn request_stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
 
 /// State to track and control watcher threads
 pub struct WatcherState {
@@ -15,6 +48,8 @@ pub struct WatcherState {
     log_watcher_stop: AtomicBool,
     disk_watcher_running: AtomicBool,
     disk_watcher_stop: AtomicBool,
+    pub health_monitor: MonitorFlags,
+    pub idle_monitor: MonitorFlags,
 }
 
 impl Default for WatcherState {
@@ -24,6 +59,8 @@ impl Default for WatcherState {
             log_watcher_stop: AtomicBool::new(false),
             disk_watcher_running: AtomicBool::new(false),
             disk_watcher_stop: AtomicBool::new(false),
+            health_monitor: MonitorFlags::default(),
+            idle_monitor: MonitorFlags::default(),
         }
     }
 }
@@ -32,6 +69,8 @@ impl WatcherState {
     pub fn shutdown(&self) {
         self.log_watcher_stop.store(true, Ordering::SeqCst);
         self.disk_watcher_stop.store(true, Ordering::SeqCst);
+        self.health_monitor.request_stop();
+        self.idle_monitor.request_stop();
     }
 }
 
@@ -44,12 +83,12 @@ pub struct LogFileInfo {
     pub size: u64,
 }
 
-/// Extract device and mount name from the first few lines of a log file.
+/// Read the device and mount name from the first few lines of a log file.
 /// Looks for "macOS: disk: /dev/diskXsY" and "macOS: mount name: XXX"
-fn extract_log_label(path: &std::path::Path) -> String {
+fn read_log_header(path: &std::path::Path) -> (Option<String>, Option<String>) {
     let file = match File::open(path) {
         Ok(f) => f,
-        Err(_) => return String::new(),
+        Err(_) => return (None, None),
     };
     let reader = BufReader::new(file);
     let mut device = None;
@@ -66,7 +105,17 @@ fn extract_log_label(path: &std::path::Path) -> String {
         }
     }
 
-    match (device, mount_name) {
+    (device, mount_name)
+}
+
+/// Device a per-mount log file belongs to, if its header names one
+pub fn log_file_device(path: &std::path::Path) -> Option<String> {
+    read_log_header(path).0
+}
+
+/// Extract device and mount name label for display in the log file picker
+fn extract_log_label(path: &std::path::Path) -> String {
+    match read_log_header(path) {
         (Some(d), Some(m)) => format!("{} ({})", d, m),
         (Some(d), None) => d,
         (None, Some(m)) => m,
@@ -286,31 +335,39 @@ pub fn start_disk_watcher(app: AppHandle) -> Result<(), String> {
             }
         };
 
-        // Watch /Volumes for mount/unmount events
-        let volumes_path = PathBuf::from("/Volumes");
-        if watcher.watch(&volumes_path, RecursiveMode::NonRecursive).is_err() {
-            log::error!("Failed to watch /Volumes");
+        // Watch the platform's mount and device directories
+        let backend = backend::current();
+        let mut watched_any = false;
+        for path in backend.watch_paths() {
+            if watcher.watch(&path, RecursiveMode::NonRecursive).is_ok() {
+                watched_any = true;
+            } else {
+                log::error!("Failed to watch {}", path.display());
+            }
+        }
+        if !watched_any {
             state_clone.disk_watcher_running.store(false, Ordering::SeqCst);
             return;
         }
 
-        // Also watch /dev for physical disk connect/disconnect events
-        // This catches Linux-only disks that don't get mounted to /Volumes
-        let dev_path = PathBuf::from("/dev");
-        if watcher.watch(&dev_path, RecursiveMode::NonRecursive).is_err() {
-            log::error!("Failed to watch /dev (continuing with /Volumes only)");
-            // Don't return - /Volumes watching is still useful
-        }
-
         // Track pending event - we wait for events to settle before emitting
         let mut pending_event: Option<Instant> = None;
         let settle_duration = Duration::from_millis(1500); // Wait 1.5s after last event
 
         // Track disk count for polling fallback (for Linux-only disks not in /Volumes)
-        let mut last_disk_count = count_disks();
+        let mut last_disk_count = backend.disk_count();
         let mut last_poll = Instant::now();
         let poll_interval = Duration::from_secs(3); // Poll every 3 seconds
 
+        // Partition UUIDs attached when the watcher started; favorites only
+        // auto-mount when their UUID shows up after this point
+        let details = backend.partition_details();
+        let mut known_uuids: std::collections::HashSet<String> =
+            backend::uuids_by_device(&details).into_keys().collect();
+
+        // Last topology reported to the frontend; each event carries the diff
+        let mut snapshot = DiskSnapshot::capture(backend, &details);
+
         loop {
             // Check if we should stop
             if state_clone.disk_watcher_stop.load(Ordering::SeqCst) {
@@ -322,12 +379,10 @@ pub fn start_disk_watcher(app: AppHandle) -> Result<(), String> {
                     match event.kind {
                         EventKind::Create(_) | EventKind::Remove(_) => {
                             // Filter /dev events to only disk-related changes
-                            let is_disk_event = event.paths.iter().any(|p| {
-                                let path_str = p.to_string_lossy();
-                                // Match /Volumes/* or /dev/disk*
-                                path_str.starts_with("/Volumes/") ||
-                                (path_str.starts_with("/dev/disk") && !path_str.contains("s"))
-                            });
+                            let is_disk_event = event
+                                .paths
+                                .iter()
+                                .any(|p| backend.is_disk_event(p));
 
                             if is_disk_event {
                                 // Mark that we have a pending event, reset settle timer
@@ -344,17 +399,29 @@ pub fn start_disk_watcher(app: AppHandle) -> Result<(), String> {
                     // Timeout - check if we have a pending event that has settled
                     if let Some(event_time) = pending_event {
                         if event_time.elapsed() >= settle_duration {
-                            // Events have settled, emit and clear
-                            let _ = app.emit("disks-changed", ());
+                            // Events have settled, emit the diff and clear
+                            let details = backend.partition_details();
+                            let current = DiskSnapshot::capture(backend, &details);
+                            let changes = snapshot.diff(&current);
+                            if changes.is_empty() {
+                                log::debug!("Disk change not visible in host topology");
+                            }
+                            let _ = app.emit("disks-changed", changes);
+                            snapshot = current;
                             pending_event = None;
-                            last_disk_count = count_disks(); // Update count after emit
+                            last_disk_count = backend.disk_count(); // Update count after emit
+                            auto_mount_attached_favorites(
+                                &app,
+                                &mut known_uuids,
+                                backend::uuids_by_device(&details),
+                            );
                         }
                     }
 
                     // Polling fallback: check disk count periodically
                     // This catches Linux-only disks that don't trigger /Volumes events
                     if last_poll.elapsed() >= poll_interval {
-                        let current_count = count_disks();
+                        let current_count = backend.disk_count();
                         if current_count != last_disk_count {
                             pending_event = Some(Instant::now());
                             last_disk_count = current_count;
@@ -371,28 +438,12 @@ pub fn start_disk_watcher(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Count physical disks by checking /dev/disk* entries
-fn count_disks() -> usize {
-    std::fs::read_dir("/dev")
-        .map(|entries| {
-            entries
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let name = e.file_name();
-                    let name_str = name.to_string_lossy();
-                    // Match disk0, disk1, etc. but not disk0s1 (partitions)
-                    name_str.starts_with("disk") &&
-                    name_str[4..].chars().all(|c| c.is_ascii_digit())
-                })
-                .count()
-        })
-        .unwrap_or(0)
-}
-
 #[tauri::command]
 pub fn stop_watchers(app: AppHandle) -> Result<(), String> {
     let state = app.state::<Arc<WatcherState>>();
     state.log_watcher_stop.store(true, Ordering::SeqCst);
     state.disk_watcher_stop.store(true, Ordering::SeqCst);
+    state.health_monitor.request_stop();
+    state.idle_monitor.request_stop();
     Ok(())
 }
@@ -6,6 +6,20 @@ pub mod shell;
 pub mod image;
 pub mod apk;
 pub mod action;
+pub mod favorite;
+pub mod filesystem;
+pub mod mount_options;
+pub mod remount;
+pub mod progress;
+pub mod resources;
+pub mod secret;
+pub mod unlock;
+pub mod topology;
+pub mod share;
+pub mod usage;
+pub mod health;
+pub mod idle;
+pub mod reconcile;
 
 pub use disk::*;
 pub use status::{check_cli, get_mount_status, get_mount_status_sync};
@@ -15,3 +29,17 @@ pub use shell::{start_shell, write_shell, resize_shell, stop_shell, PtyState};
 pub use image::*;
 pub use apk::*;
 pub use action::*;
+pub use favorite::{list_favorites, add_favorite, remove_favorite, FavoritesState};
+pub use filesystem::{get_filesystem_support, reload_filesystem_support, FilesystemSupportState};
+pub use mount_options::validate_mount_options;
+pub use remount::remount_disk;
+pub use resources::{list_vm_resource_overrides, forget_vm_resource_override, VmResourcesState};
+pub use secret::{list_saved_passphrases, forget_saved_passphrase, clear_saved_passphrases};
+pub use usage::{get_usage_alert_threshold, set_usage_alert_threshold, UsageAlertState};
+pub use health::{start_health_monitor, recover_mount};
+pub use idle::{
+    list_idle_policies, set_idle_policy, remove_idle_policy, set_mount_idle_timeout,
+    postpone_idle_unmount, start_idle_monitor, IdlePolicyState,
+};
+pub use reconcile::{reconcile_previous_session, SessionJournal};
+pub use share::{list_share_settings, set_share_settings, remove_share_settings, list_active_shares, SharesState};
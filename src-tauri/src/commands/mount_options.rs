@@ -0,0 +1,561 @@
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use super::filesystem::FilesystemSupportState;
+
+/// Value an option accepts after `=`
+#[derive(Debug, Clone, Copy)]
+enum ValueKind {
+    /// No value (`noatime`)
+    Flag,
+    /// Bare flag or one of the listed values (`discard`, `discard=async`)
+    FlagOr(&'static [&'static str]),
+    /// One of the listed values (`data=ordered`)
+    Choice(&'static [&'static str]),
+    /// Unsigned decimal integer (`commit=30`)
+    Integer,
+    /// Signed decimal integer (`time_offset=-60`)
+    SignedInteger,
+    /// Octal permission mask (`umask=022`)
+    Octal,
+    /// Byte size with optional k/m/g suffix (`allocsize=64k`)
+    Size,
+    /// Compression algorithm with optional level (`compress=zstd:3`)
+    Compression,
+    /// Free-form non-empty text (`subvol=@home`)
+    Text,
+}
+
+/// Filesystems an option is accepted by, as support table ids
+#[derive(Debug, Clone, Copy)]
+enum Applies {
+    All,
+    Only(&'static [&'static str]),
+}
+
+struct OptionSpec {
+    name: &'static str,
+    value: ValueKind,
+    applies: Applies,
+}
+
+const fn spec(name: &'static str, value: ValueKind, applies: Applies) -> OptionSpec {
+    OptionSpec { name, value, applies }
+}
+
+use Applies::{All, Only};
+use ValueKind::*;
+
+const EXT: &[&str] = &["ext"];
+const BTRFS: &[&str] = &["btrfs"];
+const XFS: &[&str] = &["xfs"];
+const EXT_BTRFS: &[&str] = &["ext", "btrfs"];
+const EXT_BTRFS_XFS: &[&str] = &["ext", "btrfs", "xfs", "f2fs"];
+const DISCARD: &[&str] = &["ext", "btrfs", "xfs", "f2fs", "exfat", "fat"];
+const WINDOWS: &[&str] = &["ntfs", "exfat", "fat"];
+const NTFS: &[&str] = &["ntfs"];
+const FAT: &[&str] = &["exfat", "fat"];
+const ERRORS: &[&str] = &["ext", "exfat", "fat"];
+const XFS_QUOTA: &[&str] = &["xfs", "ext"];
+
+const COMPRESSION: &[&str] = &["zlib", "lzo", "zstd", "no"];
+
+/// Options the GUI knows how to check. Anything else is rejected unless it is
+/// passed through the raw options escape hatch.
+static KNOWN_OPTIONS: &[OptionSpec] = &[
+    // Generic VFS options
+    spec("ro", Flag, All),
+    spec("rw", Flag, All),
+    spec("atime", Flag, All),
+    spec("noatime", Flag, All),
+    spec("relatime", Flag, All),
+    spec("strictatime", Flag, All),
+    spec("nodiratime", Flag, All),
+    spec("lazytime", Flag, All),
+    spec("nolazytime", Flag, All),
+    spec("exec", Flag, All),
+    spec("noexec", Flag, All),
+    spec("suid", Flag, All),
+    spec("nosuid", Flag, All),
+    spec("dev", Flag, All),
+    spec("nodev", Flag, All),
+    spec("sync", Flag, All),
+    spec("async", Flag, All),
+    spec("dirsync", Flag, All),
+    // ext2/3/4
+    spec("data", Choice(&["journal", "ordered", "writeback"]), Only(EXT)),
+    spec("errors", Choice(&["continue", "remount-ro", "panic"]), Only(ERRORS)),
+    spec("user_xattr", Flag, Only(EXT)),
+    spec("nouser_xattr", Flag, Only(EXT)),
+    spec("journal_checksum", Flag, Only(EXT)),
+    spec("nojournal_checksum", Flag, Only(EXT)),
+    spec("noload", Flag, Only(EXT)),
+    spec("delalloc", Flag, Only(EXT)),
+    spec("nodelalloc", Flag, Only(EXT)),
+    spec("stripe", Integer, Only(EXT)),
+    spec("inode_readahead_blks", Integer, Only(EXT)),
+    // Shared by the Linux-native filesystems
+    spec("acl", Flag, Only(&["ext", "btrfs", "f2fs", "ntfs"])),
+    spec("noacl", Flag, Only(EXT_BTRFS)),
+    spec("barrier", FlagOr(&["0", "1"]), Only(EXT_BTRFS)),
+    spec("nobarrier", Flag, Only(EXT_BTRFS)),
+    spec("commit", Integer, Only(EXT_BTRFS)),
+    spec("discard", FlagOr(&["sync", "async"]), Only(DISCARD)),
+    spec("nodiscard", Flag, Only(EXT_BTRFS_XFS)),
+    // btrfs
+    spec("subvol", Text, Only(BTRFS)),
+    spec("subvolid", Integer, Only(BTRFS)),
+    spec("compress", Compression, Only(BTRFS)),
+    spec("compress-force", Compression, Only(BTRFS)),
+    spec("space_cache", FlagOr(&["v1", "v2"]), Only(BTRFS)),
+    spec("nospace_cache", Flag, Only(BTRFS)),
+    spec("clear_cache", Flag, Only(BTRFS)),
+    spec("ssd", Flag, Only(BTRFS)),
+    spec("ssd_spread", Flag, Only(BTRFS)),
+    spec("nossd", Flag, Only(BTRFS)),
+    spec("autodefrag", Flag, Only(BTRFS)),
+    spec("noautodefrag", Flag, Only(BTRFS)),
+    spec("datacow", Flag, Only(BTRFS)),
+    spec("nodatacow", Flag, Only(BTRFS)),
+    spec("datasum", Flag, Only(BTRFS)),
+    spec("nodatasum", Flag, Only(BTRFS)),
+    spec("degraded", Flag, Only(BTRFS)),
+    spec("skip_balance", Flag, Only(BTRFS)),
+    spec("device", Text, Only(BTRFS)),
+    spec("thread_pool", Integer, Only(BTRFS)),
+    spec("rescue", Text, Only(BTRFS)),
+    // xfs
+    spec("allocsize", Size, Only(XFS)),
+    spec("attr2", Flag, Only(XFS)),
+    spec("noattr2", Flag, Only(XFS)),
+    spec("inode32", Flag, Only(XFS)),
+    spec("inode64", Flag, Only(XFS)),
+    spec("largeio", Flag, Only(XFS)),
+    spec("nolargeio", Flag, Only(XFS)),
+    spec("logbufs", Integer, Only(XFS)),
+    spec("logbsize", Size, Only(XFS)),
+    spec("logdev", Text, Only(XFS)),
+    spec("norecovery", Flag, Only(XFS)),
+    spec("nouuid", Flag, Only(XFS)),
+    spec("wsync", Flag, Only(XFS)),
+    spec("swalloc", Flag, Only(XFS)),
+    spec("filestreams", Flag, Only(XFS)),
+    spec("sunit", Integer, Only(XFS)),
+    spec("swidth", Integer, Only(XFS)),
+    spec("quota", Flag, Only(XFS_QUOTA)),
+    spec("noquota", Flag, Only(XFS_QUOTA)),
+    spec("usrquota", Flag, Only(XFS_QUOTA)),
+    spec("grpquota", Flag, Only(XFS_QUOTA)),
+    spec("prjquota", Flag, Only(XFS)),
+    // Windows filesystems map ownership at mount time
+    spec("uid", Integer, Only(WINDOWS)),
+    spec("gid", Integer, Only(WINDOWS)),
+    spec("umask", Octal, Only(WINDOWS)),
+    spec("fmask", Octal, Only(WINDOWS)),
+    spec("dmask", Octal, Only(WINDOWS)),
+    spec("iocharset", Text, Only(WINDOWS)),
+    spec("nls", Text, Only(&["ntfs", "exfat", "fat", "f2fs", "zfs"])),
+    // ntfs-3g / ntfs3
+    spec("windows_names", Flag, Only(NTFS)),
+    spec("streams_interface", Choice(&["none", "windows", "xattr", "openxattr"]), Only(NTFS)),
+    spec("hide_hid_files", Flag, Only(NTFS)),
+    spec("hide_dot_files", Flag, Only(NTFS)),
+    spec("show_sys_files", Flag, Only(NTFS)),
+    spec("compression", Flag, Only(NTFS)),
+    spec("nocompression", Flag, Only(NTFS)),
+    spec("big_writes", Flag, Only(NTFS)),
+    spec("remove_hiberfile", Flag, Only(NTFS)),
+    spec("recover", Flag, Only(NTFS)),
+    spec("norecover", Flag, Only(NTFS)),
+    spec("ignore_case", Flag, Only(NTFS)),
+    spec("permissions", Flag, Only(NTFS)),
+    spec("inherit", Flag, Only(NTFS)),
+    spec("prealloc", Flag, Only(NTFS)),
+    spec("force", Flag, Only(NTFS)),
+    spec("locale", Text, Only(NTFS)),
+    // exFAT / FAT
+    spec("keep_last_dots", Flag, Only(FAT)),
+    spec("allow_utime", Octal, Only(FAT)),
+    spec("time_offset", SignedInteger, Only(FAT)),
+    spec("codepage", Integer, Only(&["fat"])),
+    spec("shortname", Choice(&["lower", "win95", "winnt", "mixed"]), Only(&["fat"])),
+    spec("utf8", Flag, Only(&["fat"])),
+    spec("flush", Flag, Only(&["fat"])),
+    spec("quiet", Flag, Only(&["fat"])),
+    spec("showexec", Flag, Only(&["fat"])),
+    spec("check", Choice(&["relaxed", "normal", "strict", "r", "n", "s"]), Only(&["fat"])),
+];
+
+/// Options that cannot be combined; at most one per group may be given
+static CONFLICTS: &[&[&str]] = &[
+    &["ro", "rw"],
+    &["atime", "noatime", "relatime", "strictatime"],
+    &["lazytime", "nolazytime"],
+    &["exec", "noexec"],
+    &["suid", "nosuid"],
+    &["dev", "nodev"],
+    &["sync", "async"],
+    &["acl", "noacl"],
+    &["user_xattr", "nouser_xattr"],
+    &["barrier", "nobarrier"],
+    &["discard", "nodiscard"],
+    &["journal_checksum", "nojournal_checksum"],
+    &["delalloc", "nodelalloc"],
+    &["compress", "compress-force"],
+    &["space_cache", "nospace_cache"],
+    &["ssd", "ssd_spread", "nossd"],
+    &["autodefrag", "noautodefrag"],
+    &["datacow", "nodatacow"],
+    &["datasum", "nodatasum"],
+    &["subvol", "subvolid"],
+    &["attr2", "noattr2"],
+    &["inode32", "inode64"],
+    &["largeio", "nolargeio"],
+    &["quota", "noquota"],
+    &["compression", "nocompression"],
+    &["recover", "norecover"],
+];
+
+/// Options the kernel can change on a live mount with `-o remount`
+static REMOUNTABLE: &[&str] = &[
+    "ro", "rw", "atime", "noatime", "relatime", "strictatime", "nodiratime",
+    "lazytime", "nolazytime", "exec", "noexec", "suid", "nosuid", "dev", "nodev",
+    "sync", "async", "dirsync", "commit", "errors", "discard", "nodiscard",
+    "barrier", "nobarrier", "autodefrag", "noautodefrag", "compress", "compress-force",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MountOptionError {
+    pub option: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountOption {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl fmt::Display for MountOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(f, "{}={}", self.name, value),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Comma-separated mount options parsed into name/value pairs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MountOptions {
+    options: Vec<MountOption>,
+}
+
+impl MountOptions {
+    pub fn parse(options: &str) -> Self {
+        let options = options
+            .split(',')
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .map(|option| match option.split_once('=') {
+                Some((name, value)) => MountOption {
+                    name: name.trim().to_string(),
+                    value: Some(value.trim().to_string()),
+                },
+                None => MountOption {
+                    name: option.to_string(),
+                    value: None,
+                },
+            })
+            .collect();
+        Self { options }
+    }
+
+    pub fn push_flag(&mut self, name: &str) {
+        if !self.options.iter().any(|option| option.name == name) {
+            self.options.push(MountOption {
+                name: name.to_string(),
+                value: None,
+            });
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.options.retain(|option| option.name != name);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.options.iter().map(|option| option.name.as_str())
+    }
+
+    /// Names of options that need a fresh mount to take effect
+    pub fn not_remountable(&self) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|option| !REMOUNTABLE.contains(&option.name.as_str()))
+            .map(|option| option.name.clone())
+            .collect()
+    }
+
+    /// Check every option against the known keys for `filesystem` (a support
+    /// table id; None when the filesystem is unknown, which skips the
+    /// per-filesystem check). Returns one error per offending option.
+    pub fn validate(&self, filesystem: Option<&str>) -> Vec<MountOptionError> {
+        let mut errors = Vec::new();
+
+        for (index, option) in self.options.iter().enumerate() {
+            let error = |message: String| MountOptionError {
+                option: option.to_string(),
+                message,
+            };
+
+            let Some(spec) = KNOWN_OPTIONS.iter().find(|spec| spec.name == option.name) else {
+                let message = match suggest(&option.name, filesystem) {
+                    Some(known) => format!("Unknown option '{}' (did you mean '{}'?)", option.name, known),
+                    None => format!("Unknown option '{}'", option.name),
+                };
+                errors.push(error(message));
+                continue;
+            };
+
+            if let (Some(fs), Only(filesystems)) = (filesystem, spec.applies) {
+                if !filesystems.contains(&fs) {
+                    errors.push(error(format!("'{}' is not supported by {}", spec.name, fs)));
+                    continue;
+                }
+            }
+
+            if let Err(message) = check_value(spec, option.value.as_deref()) {
+                errors.push(error(message));
+                continue;
+            }
+
+            if self.options[..index].iter().any(|earlier| earlier.name == option.name) {
+                errors.push(error(format!("'{}' is given more than once", option.name)));
+                continue;
+            }
+
+            let conflicting = CONFLICTS
+                .iter()
+                .filter(|group| group.contains(&option.name.as_str()))
+                .find_map(|group| {
+                    self.options[..index]
+                        .iter()
+                        .find(|earlier| earlier.name != option.name && group.contains(&earlier.name.as_str()))
+                });
+            if let Some(earlier) = conflicting {
+                errors.push(error(format!(
+                    "'{}' conflicts with '{}'",
+                    option.name, earlier.name
+                )));
+            }
+        }
+
+        errors
+    }
+}
+
+impl fmt::Display for MountOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let joined: Vec<String> = self.options.iter().map(|option| option.to_string()).collect();
+        write!(f, "{}", joined.join(","))
+    }
+}
+
+fn check_value(spec: &OptionSpec, value: Option<&str>) -> Result<(), String> {
+    let name = spec.name;
+    let expect = |what: &str| format!("'{}' expects {}", name, what);
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    match (spec.value, value) {
+        (Flag, None) => Ok(()),
+        (Flag, Some(_)) => Err(format!("'{}' does not take a value", name)),
+        (FlagOr(_), None) => Ok(()),
+        (FlagOr(choices), Some(v)) | (Choice(choices), Some(v)) => {
+            if choices.contains(&v) {
+                Ok(())
+            } else {
+                Err(expect(&format!("one of {}", choices.join(", "))))
+            }
+        }
+        (Integer, Some(v)) if is_digits(v) => Ok(()),
+        (Integer, _) => Err(expect("a number")),
+        (SignedInteger, Some(v)) if is_digits(v.strip_prefix('-').unwrap_or(v)) => Ok(()),
+        (SignedInteger, _) => Err(expect("a number")),
+        (Octal, Some(v)) if !v.is_empty() && v.len() <= 4 && v.chars().all(|c| ('0'..='7').contains(&c)) => Ok(()),
+        (Octal, _) => Err(expect("an octal mask such as 022")),
+        (Size, Some(v))
+            if is_digits(v.trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G'])) =>
+        {
+            Ok(())
+        }
+        (Size, _) => Err(expect("a size such as 64k")),
+        (Compression, None) => Ok(()),
+        (Compression, Some(v)) => {
+            let (algorithm, level) = match v.split_once(':') {
+                Some((algorithm, level)) => (algorithm, Some(level)),
+                None => (v, None),
+            };
+            if !COMPRESSION.contains(&algorithm) {
+                Err(expect(&format!("one of {}", COMPRESSION.join(", "))))
+            } else if level.is_some_and(|level| !is_digits(level)) {
+                Err(expect("a numeric compression level"))
+            } else {
+                Ok(())
+            }
+        }
+        (Text, Some(v)) if !v.is_empty() => Ok(()),
+        (Choice(_), None) | (Text, _) => Err(expect("a value")),
+    }
+}
+
+/// Closest known option for a typo such as `subvolume` or `noatme`
+fn suggest(name: &str, filesystem: Option<&str>) -> Option<&'static str> {
+    KNOWN_OPTIONS
+        .iter()
+        .filter(|spec| match (filesystem, spec.applies) {
+            (Some(fs), Only(filesystems)) => filesystems.contains(&fs),
+            _ => true,
+        })
+        .map(|spec| {
+            let distance = if name.starts_with(spec.name) || spec.name.starts_with(name) {
+                1
+            } else {
+                edit_distance(name, spec.name)
+            };
+            (distance, spec.name)
+        })
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Check options for `device` as mount_disk would, for inline feedback while
+/// the user types
+#[tauri::command]
+pub fn validate_mount_options(
+    fs_state: tauri::State<'_, Arc<FilesystemSupportState>>,
+    device: String,
+    options: String,
+    read_only: Option<bool>,
+) -> Vec<MountOptionError> {
+    let mut typed = MountOptions::parse(&options);
+    if read_only.unwrap_or(false) {
+        typed.push_flag("ro");
+    }
+    // Inline feedback relies on the last listing rather than asking the host
+    // on every keystroke; mount_disk checks again against the partition
+    let filesystem_id = fs_state.entry_for(&device, None).map(|entry| entry.id);
+    typed.validate(filesystem_id.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(options: &str, filesystem: &str) -> Vec<String> {
+        MountOptions::parse(options)
+            .validate(Some(filesystem))
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn ext4_options() {
+        assert!(errors("noatime,data=ordered,commit=30,errors=remount-ro,acl", "ext").is_empty());
+        assert_eq!(
+            errors("data=fast,commit=soon", "ext"),
+            vec![
+                "'data' expects one of journal, ordered, writeback",
+                "'commit' expects a number",
+            ]
+        );
+        assert_eq!(errors("acl,noacl", "ext"), vec!["'noacl' conflicts with 'acl'"]);
+    }
+
+    #[test]
+    fn btrfs_options() {
+        assert!(errors("subvol=@home,compress=zstd:3,space_cache=v2,ssd,discard=async", "btrfs").is_empty());
+        assert_eq!(
+            errors("subvolume=@home", "btrfs"),
+            vec!["Unknown option 'subvolume' (did you mean 'subvol'?)"]
+        );
+        assert_eq!(errors("compress=lz4", "btrfs"), vec!["'compress' expects one of zlib, lzo, zstd, no"]);
+        assert_eq!(errors("subvol=@,subvolid=5", "btrfs"), vec!["'subvolid' conflicts with 'subvol'"]);
+    }
+
+    #[test]
+    fn xfs_options() {
+        assert!(errors("inode64,allocsize=64k,logbufs=8,nouuid", "xfs").is_empty());
+        assert_eq!(errors("allocsize=lots", "xfs"), vec!["'allocsize' expects a size such as 64k"]);
+        assert_eq!(errors("data=ordered", "xfs"), vec!["'data' is not supported by xfs"]);
+    }
+
+    #[test]
+    fn ntfs_options() {
+        assert!(errors("uid=501,gid=20,umask=022,windows_names,streams_interface=xattr", "ntfs").is_empty());
+        assert_eq!(errors("umask=999", "ntfs"), vec!["'umask' expects an octal mask such as 022"]);
+        assert_eq!(errors("windows_names=1", "ntfs"), vec!["'windows_names' does not take a value"]);
+    }
+
+    #[test]
+    fn exfat_options() {
+        assert!(errors("uid=501,dmask=022,fmask=133,errors=remount-ro,time_offset=-60", "exfat").is_empty());
+        assert_eq!(errors("compress=zstd", "exfat"), vec!["'compress' is not supported by exfat"]);
+    }
+
+    #[test]
+    fn unknown_and_unlisted_options_are_errors() {
+        // Raw options are the way to pass these; typed options must check out
+        assert_eq!(
+            errors("nls=utf8,inline_xattr,subvol=@", "f2fs"),
+            vec!["Unknown option 'inline_xattr'", "'subvol' is not supported by f2fs"]
+        );
+    }
+
+    #[test]
+    fn read_only_and_read_write_conflict() {
+        let mut options = MountOptions::parse("rw,noatime");
+        options.push_flag("ro");
+        assert_eq!(
+            options.validate(None),
+            vec![MountOptionError {
+                option: "ro".to_string(),
+                message: "'ro' conflicts with 'rw'".to_string(),
+            }]
+        );
+        assert_eq!(errors("noatime,noatime", "ext"), vec!["'noatime' is given more than once"]);
+    }
+
+    #[test]
+    fn only_live_changeable_options_are_remountable() {
+        let options = MountOptions::parse("ro,noatime,commit=60,subvol=@,uid=501");
+        assert_eq!(options.not_remountable(), vec!["subvol", "uid"]);
+    }
+
+    #[test]
+    fn unknown_filesystems_only_check_names_values_and_conflicts() {
+        let options = MountOptions::parse(" subvol=@, uid=501 ,noatime");
+        assert!(options.validate(None).is_empty());
+        assert_eq!(options.to_string(), "subvol=@,uid=501,noatime");
+    }
+}
@@ -0,0 +1,307 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+use crate::paths::get_log_paths;
+use super::log::log_file_device;
+
+/// Stages a mount passes through, as reported by the per-mount log file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum MountStage {
+    VmStarting,
+    DeviceAttached,
+    FsCheck,
+    FilesystemMounted,
+    NfsExportReady,
+    HostMountDone,
+    Failed { reason: String },
+}
+
+impl MountStage {
+    /// Position in the normal mount sequence; failures have no position
+    fn rank(&self) -> Option<u8> {
+        match self {
+            Self::VmStarting => Some(0),
+            Self::DeviceAttached => Some(1),
+            Self::FsCheck => Some(2),
+            Self::FilesystemMounted => Some(3),
+            Self::NfsExportReady => Some(4),
+            Self::HostMountDone => Some(5),
+            Self::Failed { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MountProgressEvent {
+    pub device: String,
+    #[serde(flatten)]
+    pub stage: MountStage,
+}
+
+/// Classify a single log line. Returns None for lines that carry no progress.
+/// Only the CLI's own fatal error, the guest's `mount` command (which prints
+/// nothing on success) and a panic end a mount; kernel boot messages that
+/// merely mention a failure are noise.
+fn classify_line(line: &str) -> Option<MountStage> {
+    let failure = line
+        .strip_prefix("Error: ")
+        .or_else(|| line.strip_prefix("Linux: ").filter(|rest| rest.starts_with("mount: ")))
+        .or_else(|| line.contains("panicked at").then_some(line));
+    if let Some(reason) = failure {
+        return Some(MountStage::Failed {
+            reason: reason.trim().to_string(),
+        });
+    }
+
+    let lower = line.to_lowercase();
+    if lower.contains("mount_nfs") || lower.contains("mounted on /volumes")
+        || lower.starts_with("macos: mounted")
+    {
+        return Some(MountStage::HostMountDone);
+    }
+    if lower.contains("exportfs") || lower.contains("nfs export") || lower.contains("nfsd") {
+        return Some(MountStage::NfsExportReady);
+    }
+    if lower.contains("fsck") || lower.contains("ntfsfix") || lower.contains("checking filesystem") {
+        return Some(MountStage::FsCheck);
+    }
+    if lower.starts_with("linux: mounted") || lower.contains("mount succeeded")
+        || (lower.starts_with("linux:") && lower.contains("mounted "))
+    {
+        return Some(MountStage::FilesystemMounted);
+    }
+    if lower.contains("attaching disk") || lower.contains("block device")
+        || (lower.starts_with("linux:") && lower.contains("/dev/vd"))
+    {
+        return Some(MountStage::DeviceAttached);
+    }
+    if lower.contains("starting vm") || lower.contains("starting microvm")
+        || lower.contains("booting") || lower.contains("krun_start")
+    {
+        return Some(MountStage::VmStarting);
+    }
+    None
+}
+
+/// Turns log lines into stage transitions. Stages only move forward so
+/// repeated or out-of-order lines do not produce duplicate events, and only
+/// the first failure is reported.
+#[derive(Default)]
+pub struct MountProgressParser {
+    last_rank: Option<u8>,
+    failed: bool,
+}
+
+impl MountProgressParser {
+    pub fn feed(&mut self, line: &str) -> Option<MountStage> {
+        if self.failed {
+            return None;
+        }
+        let stage = classify_line(line)?;
+        match stage.rank() {
+            None => {
+                self.failed = true;
+                Some(stage)
+            }
+            Some(rank) if self.last_rank.map(|last| rank > last).unwrap_or(true) => {
+                self.last_rank = Some(rank);
+                Some(stage)
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+/// Tails the log file of a running mount and emits `mount-progress` events.
+/// The tailer stops when the tracker is dropped, after a final read.
+pub struct MountProgressTracker {
+    stop: Arc<AtomicBool>,
+}
+
+impl MountProgressTracker {
+    pub fn start(app: AppHandle, device: String) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        // Allow for coarse mtime resolution on the freshly created log file
+        let started = SystemTime::now() - Duration::from_secs(2);
+
+        std::thread::spawn(move || {
+            let mut parser = MountProgressParser::default();
+            let mut log_path: Option<PathBuf> = None;
+            let mut position = 0u64;
+            let mut partial = String::new();
+
+            loop {
+                let stopping = thread_stop.load(Ordering::SeqCst);
+
+                if log_path.is_none() {
+                    log_path = find_mount_log(&device, started);
+                }
+                if let Some(ref path) = log_path {
+                    for line in read_new_lines(path, &mut position, &mut partial) {
+                        if let Some(stage) = parser.feed(&line) {
+                            let _ = app.emit(
+                                "mount-progress",
+                                MountProgressEvent {
+                                    device: device.clone(),
+                                    stage,
+                                },
+                            );
+                        }
+                    }
+                }
+
+                if stopping {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        });
+
+        Self { stop }
+    }
+}
+
+impl Drop for MountProgressTracker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Newest log file written since the mount started whose header names `device`
+fn find_mount_log(device: &str, started: SystemTime) -> Option<PathBuf> {
+    get_log_paths()
+        .into_iter()
+        .rev() // newest first
+        .filter(|path| {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .map(|mtime| mtime >= started)
+                .unwrap_or(false)
+        })
+        .find(|path| log_file_device(path).as_deref() == Some(device))
+}
+
+/// Read complete lines appended since `position`. An incomplete trailing line
+/// is kept in `partial` until the rest of it is written.
+fn read_new_lines(path: &Path, position: &mut u64, partial: &mut String) -> Vec<String> {
+    let Ok(mut file) = File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len < *position {
+        // Truncated, start over
+        *position = 0;
+        partial.clear();
+    }
+    if len == *position || file.seek(SeekFrom::Start(*position)).is_err() {
+        return Vec::new();
+    }
+
+    let mut bytes = Vec::new();
+    if file.read_to_end(&mut bytes).is_err() {
+        return Vec::new();
+    }
+    *position += bytes.len() as u64;
+    partial.push_str(&String::from_utf8_lossy(&bytes));
+
+    let mut lines = Vec::new();
+    while let Some(newline) = partial.find('\n') {
+        let line: String = partial.drain(..=newline).collect();
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stages(log: &str) -> Vec<MountStage> {
+        let mut parser = MountProgressParser::default();
+        log.lines().filter_map(|line| parser.feed(line)).collect()
+    }
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/logs/", $name))
+        };
+    }
+
+    #[test]
+    fn successful_mount_reports_every_stage_once() {
+        // Kernel boot messages mentioning failures must not end the mount
+        assert_eq!(
+            stages(fixture!("reconstructed_mount_ext4_success.log")),
+            vec![
+                MountStage::VmStarting,
+                MountStage::DeviceAttached,
+                MountStage::FsCheck,
+                MountStage::FilesystemMounted,
+                MountStage::NfsExportReady,
+                MountStage::HostMountDone,
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_mount_reports_the_first_error_once() {
+        let stages = stages(fixture!("reconstructed_mount_wrong_fs_type.log"));
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[0], MountStage::VmStarting);
+        match &stages[1] {
+            MountStage::Failed { reason } => {
+                assert!(reason.starts_with("mount: /mnt/disk6s2: wrong fs type"))
+            }
+            other => panic!("unexpected stage: {:?}", other),
+        }
+        assert_eq!(
+            classify_line("Error: Failed to mount filesystem in VM"),
+            Some(MountStage::Failed {
+                reason: "Failed to mount filesystem in VM".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn partial_lines_wait_for_their_newline() {
+        use std::io::Write;
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("anylinuxfs-test.log");
+        let mut file = File::create(&path).unwrap();
+        let mut position = 0;
+        let mut partial = String::new();
+
+        file.write_all(b"macOS: disk: /dev/disk5s1\nLinux: e2f").unwrap();
+        assert_eq!(
+            read_new_lines(&path, &mut position, &mut partial),
+            vec!["macOS: disk: /dev/disk5s1".to_string()]
+        );
+        file.write_all(b"sck -p /dev/vda\n").unwrap();
+        assert_eq!(
+            read_new_lines(&path, &mut position, &mut partial),
+            vec!["Linux: e2fsck -p /dev/vda".to_string()]
+        );
+    }
+
+    #[test]
+    fn progress_events_have_a_stable_frontend_shape() {
+        let json = serde_json::to_value(MountProgressEvent {
+            device: "/dev/disk5s1".to_string(),
+            stage: MountStage::Failed {
+                reason: "bad superblock".to_string(),
+            },
+        })
+        .unwrap();
+        assert_eq!(json["device"], "/dev/disk5s1");
+        assert_eq!(json["stage"], "failed");
+        assert_eq!(json["reason"], "bad superblock");
+    }
+}
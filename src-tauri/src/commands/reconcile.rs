@@ -0,0 +1,448 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::cli::{self, execute_command};
+use crate::elevation::{ElevationMode, ElevationState};
+use crate::storage::write_private_toml;
+use crate::timeouts::{is_transient_error, Operation};
+use super::health::remove_stale_mount_point;
+use super::status::{get_mount_status_sync, MountInfo};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalState {
+    /// The mount command was started and has not reported back yet
+    Mounting,
+    Mounted,
+}
+
+/// How a mount was started, so a remount can mount it the same way again
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MountParameters {
+    /// Custom action chosen by the user (not the GUI-managed share action)
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub ignore_permissions: bool,
+    #[serde(default)]
+    pub raw_options: Option<String>,
+    /// VM size given explicitly for the mount
+    #[serde(default)]
+    pub ram_mb: Option<u32>,
+    #[serde(default)]
+    pub vcpus: Option<u32>,
+    /// A passphrase or keyfile unlocked the volume
+    #[serde(default)]
+    pub unlocked: bool,
+}
+
+/// A mount started by the GUI, kept on disk so a later session knows about it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JournalEntry {
+    pub device: String,
+    pub state: JournalState,
+    #[serde(default)]
+    pub mount_point: Option<String>,
+    pub elevation_mode: ElevationMode,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    #[serde(default)]
+    pub parameters: MountParameters,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredJournal {
+    #[serde(default)]
+    entries: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdoptedMount {
+    pub device: String,
+    pub mount_point: String,
+}
+
+/// What the startup reconciliation found and did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    /// Journaled mounts that are still live and tracked again
+    pub adopted: Vec<AdoptedMount>,
+    /// Live mounts the GUI did not start; left untouched
+    pub unmanaged: Vec<String>,
+    /// Journaled mounts that are gone; their entries were dropped
+    pub released: Vec<String>,
+    /// Interrupted mounts whose VM was stopped
+    pub stopped: Vec<String>,
+    /// Interrupted mounts still waiting in a Terminal tab
+    pub pending: Vec<String>,
+    /// Shell PIDs of Terminal sessions from an earlier run that still wait
+    pub running_terminal_sessions: Vec<u32>,
+    pub removed_handoff_dirs: usize,
+    pub removed_askpass_scripts: usize,
+    /// Empty mount points of released mounts that were removed
+    pub removed_mount_points: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Journal of GUI-initiated mounts and the report of the reconciliation run
+/// at startup
+pub struct SessionJournal {
+    path: PathBuf,
+    /// Anything the previous session left behind is older than this
+    started_at: SystemTime,
+    entries: Mutex<Vec<JournalEntry>>,
+    report: Mutex<Option<ReconcileReport>>,
+}
+
+/// Journal entry of a mount in progress. Dropping it without `commit`
+/// removes the entry, so only a crash leaves a `Mounting` entry behind.
+pub struct MountJournalGuard {
+    journal: Arc<SessionJournal>,
+    device: String,
+    committed: bool,
+}
+
+impl MountJournalGuard {
+    pub fn commit(mut self, mount_point: Option<String>) {
+        self.committed = true;
+        self.journal.update(|entries| {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.device == self.device) {
+                entry.state = JournalState::Mounted;
+                entry.mount_point = mount_point;
+            }
+        });
+    }
+}
+
+impl Drop for MountJournalGuard {
+    fn drop(&mut self) {
+        if !self.committed {
+            let device = &self.device;
+            self.journal.update(|entries| {
+                entries.retain(|entry| {
+                    entry.device != *device || entry.state != JournalState::Mounting
+                })
+            });
+        }
+    }
+}
+
+impl SessionJournal {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<StoredJournal>(&contents).ok())
+            .map(|stored| stored.entries)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            started_at: SystemTime::now(),
+            entries: Mutex::new(entries),
+            report: Mutex::new(None),
+        }
+    }
+
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Apply `change` and persist the result. The journal is advisory, so a
+    /// failed write is logged rather than failing the operation.
+    fn update(&self, change: impl FnOnce(&mut Vec<JournalEntry>)) {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        change(&mut entries);
+        if let Err(e) = write_journal(&self.path, &entries) {
+            log::warn!("Failed to update mount journal: {}", e);
+        }
+    }
+
+    /// Parameters `device` was mounted with, if the GUI mounted it
+    pub fn parameters(&self, device: &str) -> Option<MountParameters> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .find(|entry| entry.device == device && entry.state == JournalState::Mounted)
+            .map(|entry| entry.parameters.clone())
+    }
+
+    pub fn begin_mount(
+        self: &Arc<Self>,
+        device: &str,
+        elevation_mode: ElevationMode,
+        parameters: MountParameters,
+    ) -> MountJournalGuard {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        self.update(|entries| {
+            entries.retain(|entry| entry.device != device);
+            entries.push(JournalEntry {
+                device: device.to_string(),
+                state: JournalState::Mounting,
+                mount_point: None,
+                elevation_mode,
+                started_at,
+                parameters,
+            });
+        });
+        MountJournalGuard {
+            journal: self.clone(),
+            device: device.to_string(),
+            committed: false,
+        }
+    }
+
+    /// Forget `device`, or every mount when None, after an unmount
+    pub fn forget(&self, device: Option<&str>) {
+        self.update(|entries| match device {
+            Some(device) => entries.retain(|entry| entry.device != device),
+            None => entries.retain(|entry| entry.state == JournalState::Mounting),
+        });
+    }
+}
+
+fn write_journal(path: &Path, entries: &[JournalEntry]) -> Result<(), String> {
+    let stored = StoredJournal {
+        entries: entries.to_vec(),
+    };
+    write_private_toml(path, &stored, "mount journal")
+}
+
+/// How one journal entry relates to the live mounts
+#[derive(Debug, Clone, PartialEq)]
+enum EntryFate {
+    Adopt(String),
+    /// A mount that was interrupted before it appeared
+    Interrupted,
+    Gone,
+}
+
+fn entry_fate(entry: &JournalEntry, mounts: &[MountInfo]) -> EntryFate {
+    match mounts.iter().find(|mount| mount.device == entry.device) {
+        Some(mount) => EntryFate::Adopt(mount.mount_point.clone()),
+        None if entry.state == JournalState::Mounting => EntryFate::Interrupted,
+        None => EntryFate::Gone,
+    }
+}
+
+/// Compare the journal with `anylinuxfs status`, clean up after the previous
+/// session and rewrite the journal to match. Without a status listing every
+/// journal entry would look gone, so the pass is aborted before touching
+/// anything.
+fn reconcile(journal: &SessionJournal, elevation: &ElevationState) -> Result<ReconcileReport, String> {
+    let mut report = ReconcileReport::default();
+    let mounts = get_mount_status_sync()
+        .map_err(|e| format!("Cannot reconcile the previous session: {}", e))?;
+
+    let handoffs = elevation.reclaim_orphaned_handoffs(journal.started_at);
+    report.removed_handoff_dirs = handoffs.removed.len();
+    report.running_terminal_sessions = handoffs.running.iter().map(|running| running.pid).collect();
+    report.removed_askpass_scripts = cli::remove_orphaned_askpass_scripts(journal.started_at).len();
+
+    let stop_policy = elevation.operation_policies().get(Operation::Stop);
+    let mut kept = Vec::new();
+    for entry in journal.entries() {
+        let fate = entry_fate(&entry, &mounts);
+        let mount_point = match fate {
+            EntryFate::Adopt(mount_point) => {
+                report.adopted.push(AdoptedMount {
+                    device: entry.device.clone(),
+                    mount_point: mount_point.clone(),
+                });
+                kept.push(JournalEntry {
+                    state: JournalState::Mounted,
+                    mount_point: Some(mount_point),
+                    ..entry
+                });
+                continue;
+            }
+            EntryFate::Interrupted
+                if elevation.is_operation_active(&format!("mount:{}", entry.device))
+                    || handoffs
+                        .running
+                        .iter()
+                        .any(|running| running.targets_device(&entry.device)) =>
+            {
+                // Mounting again now, or the user may still be typing a
+                // password in a Terminal tab
+                report.pending.push(entry.device.clone());
+                kept.push(entry);
+                continue;
+            }
+            EntryFate::Interrupted => {
+                let stopped = stop_policy.run(
+                    "stop",
+                    |_| execute_command(&["stop", &entry.device], None, None, false),
+                    |message| is_transient_error(message),
+                );
+                match stopped {
+                    Ok(_) => report.stopped.push(entry.device.clone()),
+                    Err(e) => report.errors.push(format!("stop {}: {}", entry.device, e)),
+                }
+                entry.mount_point.clone()
+            }
+            EntryFate::Gone => entry.mount_point.clone(),
+        };
+        report.released.push(entry.device.clone());
+
+        // Only the empty /Volumes directory of a mount the GUI made is removed
+        if let Some(mount_point) = mount_point
+            .filter(|mount_point| Path::new(mount_point).parent() == Some(Path::new("/Volumes")))
+        {
+            let existed = Path::new(&mount_point).exists();
+            match remove_stale_mount_point(&mount_point) {
+                Ok(_) if existed => report.removed_mount_points.push(mount_point),
+                Ok(_) => {}
+                Err(e) => report.errors.push(format!("{}: {}", mount_point, e)),
+            }
+        }
+    }
+
+    report.unmanaged = mounts
+        .iter()
+        .filter(|mount| !kept.iter().any(|entry| entry.device == mount.device))
+        .map(|mount| mount.device.clone())
+        .collect();
+    journal.update(|entries| {
+        // Keep mounts started while the reconciliation ran
+        let started_meanwhile: Vec<JournalEntry> = entries
+            .iter()
+            .filter(|entry| entry.state == JournalState::Mounting)
+            .filter(|entry| !kept.iter().any(|kept| kept.device == entry.device))
+            .filter(|entry| !report.released.contains(&entry.device))
+            .cloned()
+            .collect();
+        *entries = kept;
+        entries.extend(started_meanwhile);
+    });
+
+    log::info!(
+        "Session reconciliation: {} adopted, {} released, {} stopped, {} handoff dir(s) and {} askpass script(s) removed",
+        report.adopted.len(),
+        report.released.len(),
+        report.stopped.len(),
+        report.removed_handoff_dirs,
+        report.removed_askpass_scripts
+    );
+    Ok(report)
+}
+
+/// Reconcile with the previous GUI session. The work is done once per
+/// process; later calls return the same report. A pass aborted because the
+/// mount status was unavailable is not remembered, so a later call retries.
+#[tauri::command]
+pub async fn reconcile_previous_session(
+    journal: tauri::State<'_, Arc<SessionJournal>>,
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+) -> Result<ReconcileReport, String> {
+    let journal = journal.inner().clone();
+    let elevation_state = elevation_state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let mut report = journal
+            .report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(report) = report.as_ref() {
+            return Ok(report.clone());
+        }
+        let fresh = reconcile(&journal, &elevation_state)?;
+        *report = Some(fresh.clone());
+        Ok(fresh)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(device: &str, mount_point: &str) -> MountInfo {
+        MountInfo {
+            device: device.to_string(),
+            mount_point: mount_point.to_string(),
+            filesystem: Some("ext4".to_string()),
+            options: Vec::new(),
+            read_only: false,
+            ram_mb: None,
+            vcpus: None,
+            usage: None,
+        }
+    }
+
+    fn entry(device: &str, state: JournalState) -> JournalEntry {
+        JournalEntry {
+            device: device.to_string(),
+            state,
+            mount_point: Some("/Volumes/old".to_string()),
+            elevation_mode: ElevationMode::Native,
+            started_at: 0,
+            parameters: MountParameters::default(),
+        }
+    }
+
+    #[test]
+    fn journal_entries_are_matched_against_live_mounts() {
+        let mounts = vec![mount("/dev/disk4s1", "/Volumes/backup")];
+        assert_eq!(
+            entry_fate(&entry("/dev/disk4s1", JournalState::Mounting), &mounts),
+            EntryFate::Adopt("/Volumes/backup".to_string())
+        );
+        assert_eq!(
+            entry_fate(&entry("/dev/disk5s1", JournalState::Mounting), &mounts),
+            EntryFate::Interrupted
+        );
+        assert_eq!(
+            entry_fate(&entry("/dev/disk5s1", JournalState::Mounted), &mounts),
+            EntryFate::Gone
+        );
+    }
+
+    #[test]
+    fn only_a_crash_leaves_a_mounting_entry_behind() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("mount_journal.toml");
+        let journal = Arc::new(SessionJournal::load(path.clone()));
+
+        drop(journal.begin_mount("/dev/disk4s1", ElevationMode::Native, MountParameters::default()));
+        assert!(journal.entries().is_empty());
+
+        let parameters = MountParameters {
+            image: Some("freebsd-14.3".to_string()),
+            unlocked: true,
+            ..MountParameters::default()
+        };
+        journal
+            .begin_mount("/dev/disk4s1", ElevationMode::Native, parameters.clone())
+            .commit(Some("/Volumes/backup".to_string()));
+        let crashed = journal.begin_mount(
+            "/dev/disk5s1",
+            ElevationMode::InteractiveTerminal,
+            MountParameters::default(),
+        );
+        std::mem::forget(crashed);
+
+        let entries = SessionJournal::load(path.clone()).entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].state, JournalState::Mounted);
+        assert_eq!(entries[0].mount_point.as_deref(), Some("/Volumes/backup"));
+        assert_eq!(entries[0].parameters, parameters);
+        assert_eq!(entries[1].state, JournalState::Mounting);
+
+        journal.forget(None);
+        assert_eq!(SessionJournal::load(path).entries()[0].device, "/dev/disk5s1");
+    }
+}
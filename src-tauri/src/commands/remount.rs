@@ -0,0 +1,378 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::timeout;
+use crate::backend::BlockingProcess;
+use crate::cache;
+use crate::cli::{self, execute_command_with_elevation, CommandExecutionError};
+use crate::elevation::{ElevationMode, ElevationState, TerminalInteraction};
+use crate::secrets::SecretsState;
+use crate::timeouts::Operation;
+use super::disk::{
+    check_device_mounted, mount, run_unmount, validate_device_path, validate_extra_options,
+    MountOutcome, MountRequest, UnmountOutcome,
+};
+use super::filesystem::FilesystemSupportState;
+use super::mount_options::{MountOptionError, MountOptions};
+use super::reconcile::SessionJournal;
+use super::resources::partition_details;
+use super::status::{get_mount_status_sync, MountInfo};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemountOutcome {
+    /// Options changed on the live mount; the VM kept running
+    Remounted,
+    /// Options changed by unmounting and mounting again
+    Cycled,
+    /// Nothing changed; the volume is still mounted with its old options
+    Unchanged,
+    /// The fallback unmounted the volume but mounting it again failed
+    Unmounted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemountResult {
+    pub outcome: RemountOutcome,
+    pub message: Option<String>,
+    /// Mount state after the operation, when the volume is still mounted
+    pub mount: Option<MountInfo>,
+    /// Outcome of the fresh mount after unmounting
+    pub mount_outcome: Option<MountOutcome>,
+    pub option_errors: Vec<MountOptionError>,
+    /// Processes that kept the volume from being unmounted
+    pub blocking_processes: Vec<BlockingProcess>,
+}
+
+impl RemountResult {
+    fn new(outcome: RemountOutcome, message: impl Into<Option<String>>) -> Self {
+        Self {
+            outcome,
+            message: message.into(),
+            mount: None,
+            mount_outcome: None,
+            option_errors: Vec::new(),
+            blocking_processes: Vec::new(),
+        }
+    }
+}
+
+fn current_mount(device: &str) -> Option<MountInfo> {
+    get_mount_status_sync()
+        .ok()?
+        .into_iter()
+        .find(|mount| mount.device == device)
+}
+
+/// Options of the running mount that a fresh mount keeps: what the CLI
+/// reports for it, minus ro/rw and the options being changed. They are passed
+/// on unchecked, like raw options, since the kernel already accepted them.
+fn carried_options(current: &[String], changes: &MountOptions) -> MountOptions {
+    let mut carried = MountOptions::parse(&current.join(","));
+    carried.remove("ro");
+    carried.remove("rw");
+    for name in changes.names() {
+        carried.remove(name);
+    }
+    carried
+}
+
+/// Outcome of `anylinuxfs remount`, or why the options need a full cycle
+enum LiveRemount {
+    Done(MountInfo),
+    Fallback(String),
+}
+
+/// Change the options of the running mount through the CLI's `remount`
+/// subcommand. Only CLIs that list one in `--help` are asked; released
+/// versions have none, so they always take the cycle.
+async fn remount_live(
+    app: &AppHandle,
+    elevation_state: &Arc<ElevationState>,
+    device: &str,
+    options: &MountOptions,
+    read_only: bool,
+) -> Result<LiveRemount, String> {
+    let not_remountable = options.not_remountable();
+    if !not_remountable.is_empty() {
+        return Ok(LiveRemount::Fallback(format!(
+            "{} cannot be changed on a live mount",
+            not_remountable.join(", ")
+        )));
+    }
+    if !cli::supports_subcommand("remount") {
+        return Ok(LiveRemount::Fallback(
+            "The installed anylinuxfs cannot remount a live volume".to_string(),
+        ));
+    }
+
+    let elevation_state = elevation_state.clone();
+    let operation = format!("remount:{}", device);
+    let operation_guard = elevation_state.begin_operation(operation.clone())?;
+    let elevation_mode = operation_guard.mode();
+    let policy = elevation_state
+        .operation_policies()
+        .for_mode(Operation::Remount, elevation_mode);
+    let remount_device = device.to_string();
+    let option_string = options.to_string();
+    let remount_state = elevation_state.clone();
+    let remount_operation = operation.clone();
+    let remount_future = tokio::task::spawn_blocking(move || {
+        policy
+            .run(
+                "remount",
+                |_| {
+                    execute_command_with_elevation(
+                        &["remount", "-o", &option_string, &remount_device],
+                        true,
+                        None,
+                        false,
+                        elevation_mode,
+                        &remount_state,
+                        TerminalInteraction::CaptureOutput {
+                            operation: remount_operation.clone(),
+                        },
+                        policy,
+                    )
+                },
+                CommandExecutionError::is_transient,
+            )
+            .map_err(|error| error.message())?;
+        cache::invalidate_all();
+        Ok::<_, String>(current_mount(&remount_device))
+    });
+    let result = match timeout(policy.budget(), remount_future).await {
+        Ok(result) => result.map_err(|e| format!("Task error: {}", e))?,
+        Err(_) => {
+            elevation_state.cancel_pending_operation(&operation);
+            Err(format!("Remount timed out after {} seconds", policy.budget().as_secs()))
+        }
+    };
+    drop(operation_guard);
+    let _ = app.emit("status-changed", ());
+
+    Ok(match result {
+        // Some filesystems accept the remount but keep their old mode
+        // (e.g. ntfs-3g stays read-only on a hibernated volume)
+        Ok(Some(mount)) if mount.read_only == read_only => LiveRemount::Done(mount),
+        Ok(Some(_)) => LiveRemount::Fallback("The filesystem kept its previous mode".to_string()),
+        Ok(None) => LiveRemount::Fallback("The volume disappeared during remount".to_string()),
+        Err(error) => LiveRemount::Fallback(format!("Live remount failed: {}", error)),
+    })
+}
+
+/// Change `ro`/`rw` and other options on an anylinuxfs mount. Options the
+/// kernel can change live go through the CLI's `remount` subcommand when it
+/// has one. Otherwise the volume is unmounted and mounted again with its
+/// current options, custom action, image, permissions mode and VM size;
+/// share settings are applied again by the mount. Encrypted volumes need
+/// `passphrase` (or a saved one) before anything is unmounted.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn remount_disk(
+    app: AppHandle,
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+    fs_state: tauri::State<'_, Arc<FilesystemSupportState>>,
+    secrets: tauri::State<'_, Arc<SecretsState>>,
+    device: String,
+    read_only: bool,
+    extra_options: Option<String>,
+    passphrase: Option<String>,
+) -> Result<RemountResult, String> {
+    validate_device_path(&device)?;
+    if let Some(ref opts) = extra_options {
+        validate_extra_options(opts)?;
+    }
+    let status_device = device.clone();
+    let (before, details) = tokio::task::spawn_blocking(move || {
+        (current_mount(&status_device), partition_details(&status_device))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?;
+    let Some(before) = before else {
+        return Err(format!("{} is not mounted by anylinuxfs", device));
+    };
+
+    let mut options = MountOptions::parse(extra_options.as_deref().unwrap_or(""));
+    options.push_flag(if read_only { "ro" } else { "rw" });
+    let filesystem_id = fs_state
+        .entry_for(&device, details.filesystem.as_deref())
+        .map(|entry| entry.id);
+    let option_errors = options.validate(filesystem_id.as_deref());
+    if !option_errors.is_empty() {
+        let message = option_errors
+            .iter()
+            .map(|error| error.message.clone())
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Ok(RemountResult {
+            mount: Some(before),
+            option_errors,
+            ..RemountResult::new(RemountOutcome::Unchanged, message)
+        });
+    }
+
+    let fallback_reason =
+        match remount_live(&app, elevation_state.inner(), &device, &options, read_only).await? {
+            LiveRemount::Done(mount) => {
+                return Ok(RemountResult {
+                    mount: Some(mount),
+                    ..RemountResult::new(RemountOutcome::Remounted, None)
+                });
+            }
+            LiveRemount::Fallback(reason) => reason,
+        };
+    log::info!("Remounting {} with a full cycle: {}", device, fallback_reason);
+
+    // Mounts started by another session or tool use the defaults
+    let parameters = app
+        .state::<Arc<SessionJournal>>()
+        .parameters(&device)
+        .unwrap_or_default();
+
+    // An encrypted volume must not be unmounted unless it can be unlocked
+    // again. Interactive Terminal asks for the passphrase there.
+    let encrypted = parameters.unlocked
+        || matches!(filesystem_id.as_deref(), Some("luks") | Some("bitlocker"));
+    let uuid = details.uuid.filter(|uuid| !uuid.is_empty());
+    let key_available = passphrase.is_some()
+        || elevation_state.mode() == ElevationMode::InteractiveTerminal
+        || uuid
+            .as_deref()
+            .is_some_and(|uuid| matches!(secrets.get(uuid), Ok(Some(_))));
+    if encrypted && !key_available {
+        return Ok(RemountResult {
+            mount: Some(before),
+            ..RemountResult::new(
+                RemountOutcome::Unchanged,
+                format!(
+                    "{}; the volume is encrypted, so enter its passphrase to remount it",
+                    fallback_reason
+                ),
+            )
+        });
+    }
+
+    let unmount_device = device.clone();
+    let unmount_policy = elevation_state.operation_policies().get(Operation::Unmount);
+    let unmount = tokio::task::spawn_blocking(move || {
+        let result = run_unmount(Some(&unmount_device), &unmount_policy)?;
+        // Let the VM shut down before mounting again (up to 5 seconds)
+        for _ in 0..10 {
+            if !check_device_mounted(&unmount_device) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        Ok::<_, String>(result)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?;
+    cache::invalidate_all();
+    let _ = app.emit("status-changed", ());
+    match unmount {
+        Ok(result) if matches!(result.outcome, UnmountOutcome::Busy) => {
+            return Ok(RemountResult {
+                mount: Some(before),
+                blocking_processes: result.blocking_processes,
+                ..RemountResult::new(
+                    RemountOutcome::Unchanged,
+                    format!("{}; the volume is busy so it was left mounted", fallback_reason),
+                )
+            });
+        }
+        Ok(_) => {}
+        Err(error) => {
+            return Ok(RemountResult {
+                mount: Some(before),
+                ..RemountResult::new(
+                    RemountOutcome::Unchanged,
+                    format!("{}; unmount failed: {}", fallback_reason, error),
+                )
+            });
+        }
+    }
+
+    // ro/rw travels separately through `read_only`
+    let mut fresh_options = options.clone();
+    fresh_options.remove("ro");
+    fresh_options.remove("rw");
+    let carried = carried_options(&before.options, &fresh_options).to_string();
+    let raw_options = [Some(carried), parameters.raw_options]
+        .into_iter()
+        .flatten()
+        .filter(|opts| !opts.is_empty())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mounted = mount(
+        app,
+        MountRequest {
+            device: device.clone(),
+            passphrase,
+            read_only: Some(read_only),
+            extra_options: Some(fresh_options.to_string()).filter(|opts| !opts.is_empty()),
+            ignore_permissions: Some(parameters.ignore_permissions),
+            action: parameters.action,
+            image: parameters.image,
+            raw_options: Some(raw_options).filter(|opts| !opts.is_empty()),
+            ram_mb: parameters.ram_mb,
+            vcpus: parameters.vcpus,
+            ..MountRequest::default()
+        },
+    )
+    .await;
+
+    let (outcome, message, mount_outcome) = match mounted {
+        Ok(result) if matches!(result.outcome, MountOutcome::Mounted) => (
+            RemountOutcome::Cycled,
+            format!("{}; remounted with a full unmount and mount", fallback_reason),
+            Some(result.outcome),
+        ),
+        Ok(result) => (
+            RemountOutcome::Unmounted,
+            format!(
+                "{}; the volume was unmounted but mounting it again failed: {}",
+                fallback_reason,
+                result.message.as_deref().unwrap_or("unknown error")
+            ),
+            Some(result.outcome),
+        ),
+        Err(error) => (
+            RemountOutcome::Unmounted,
+            format!(
+                "{}; the volume was unmounted but mounting it again failed: {}",
+                fallback_reason, error
+            ),
+            Some(MountOutcome::Failed),
+        ),
+    };
+    let status_device = device.clone();
+    let mount = tokio::task::spawn_blocking(move || current_mount(&status_device))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?;
+
+    Ok(RemountResult {
+        mount,
+        mount_outcome,
+        ..RemountResult::new(outcome, message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresh_mount_keeps_current_options_except_the_changed_ones() {
+        let current: Vec<String> = ["rw", "noatime", "compress=zstd:3", "subvolid=5", "subvol=/"]
+            .iter()
+            .map(|option| option.to_string())
+            .collect();
+        let changes = MountOptions::parse("compress=lzo");
+        assert_eq!(
+            carried_options(&current, &changes).to_string(),
+            "noatime,subvolid=5,subvol=/"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::backend::{self, PartitionDetails};
+use crate::storage::write_private_toml;
+use super::config::validate_vm_resources;
+use super::favorite::validate_partition_uuid;
+
+/// RAM/vCPU for the microVM serving one partition. Unset values fall back to
+/// the global `krun` configuration.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct VmResources {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ram_mb: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcpus: Option<u32>,
+}
+
+impl VmResources {
+    pub fn is_empty(&self) -> bool {
+        self.ram_mb.is_none() && self.vcpus.is_none()
+    }
+
+    /// Explicit values take precedence, field by field, over remembered ones
+    pub fn or(self, remembered: VmResources) -> VmResources {
+        VmResources {
+            ram_mb: self.ram_mb.or(remembered.ram_mb),
+            vcpus: self.vcpus.or(remembered.vcpus),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredResources {
+    #[serde(default)]
+    overrides: BTreeMap<String, VmResources>,
+}
+
+/// Per-partition VM resource overrides keyed by uppercased partition UUID
+pub struct VmResourcesState {
+    path: PathBuf,
+    overrides: Mutex<BTreeMap<String, VmResources>>,
+}
+
+impl VmResourcesState {
+    pub fn load(path: PathBuf) -> Self {
+        let overrides = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<StoredResources>(&contents).ok())
+            .map(|stored| stored.overrides)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            overrides: Mutex::new(overrides),
+        }
+    }
+
+    pub fn list(&self) -> BTreeMap<String, VmResources> {
+        self.overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<VmResources> {
+        self.overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&uuid.to_uppercase())
+            .copied()
+    }
+
+    pub fn remember(&self, uuid: &str, resources: VmResources) -> Result<(), String> {
+        let mut overrides = self
+            .overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if overrides.get(&uuid.to_uppercase()) == Some(&resources) {
+            return Ok(());
+        }
+        let mut updated = overrides.clone();
+        updated.insert(uuid.to_uppercase(), resources);
+        write_resources(&self.path, &updated)?;
+        *overrides = updated;
+        Ok(())
+    }
+
+    pub fn forget(&self, uuid: &str) -> Result<BTreeMap<String, VmResources>, String> {
+        let mut overrides = self
+            .overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = overrides.clone();
+        if updated.remove(&uuid.to_uppercase()).is_none() {
+            return Err(format!("No VM resource override for '{}'", uuid));
+        }
+        write_resources(&self.path, &updated)?;
+        *overrides = updated.clone();
+        Ok(updated)
+    }
+}
+
+fn write_resources(path: &Path, overrides: &BTreeMap<String, VmResources>) -> Result<(), String> {
+    let stored = StoredResources {
+        overrides: overrides.clone(),
+    };
+    write_private_toml(path, &stored, "VM resources")
+}
+
+/// Filesystem and UUID of `device` as reported by the host
+pub fn partition_details(device: &str) -> PartitionDetails {
+    let device_id = device.trim_start_matches("/dev/");
+    backend::current()
+        .partition_details()
+        .remove(device_id)
+        .unwrap_or_default()
+}
+
+/// Resources for a mount: explicit overrides (validated) merged over the
+/// values remembered for `uuid`
+pub fn resolve_vm_resources(
+    state: &VmResourcesState,
+    uuid: Option<&str>,
+    explicit: VmResources,
+) -> Result<VmResources, String> {
+    validate_vm_resources(explicit.ram_mb, explicit.vcpus)?;
+    let remembered = uuid.and_then(|uuid| state.get(uuid)).unwrap_or_default();
+    Ok(explicit.or(remembered))
+}
+
+#[tauri::command]
+pub fn list_vm_resource_overrides(
+    state: tauri::State<'_, Arc<VmResourcesState>>,
+) -> BTreeMap<String, VmResources> {
+    state.list()
+}
+
+#[tauri::command]
+pub fn forget_vm_resource_override(
+    state: tauri::State<'_, Arc<VmResourcesState>>,
+    uuid: String,
+) -> Result<BTreeMap<String, VmResources>, String> {
+    validate_partition_uuid(&uuid)?;
+    state.forget(&uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_values_override_remembered_ones_per_field() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("vm_resources.toml");
+        let state = VmResourcesState::load(path.clone());
+        let array = "6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60";
+        state
+            .remember(array, VmResources { ram_mb: Some(4096), vcpus: Some(4) })
+            .unwrap();
+
+        let reloaded = VmResourcesState::load(path);
+        let resolved = resolve_vm_resources(
+            &reloaded,
+            Some(&array.to_uppercase()),
+            VmResources { ram_mb: None, vcpus: Some(2) },
+        )
+        .unwrap();
+        assert_eq!(resolved, VmResources { ram_mb: Some(4096), vcpus: Some(2) });
+
+        // A USB stick without an override keeps the global defaults
+        let stick = resolve_vm_resources(&reloaded, Some("0A1B-2C3D"), VmResources::default());
+        assert!(stick.unwrap().is_empty());
+    }
+
+    #[test]
+    fn overrides_use_the_update_config_bounds() {
+        let directory = tempfile::tempdir().unwrap();
+        let state = VmResourcesState::load(directory.path().join("vm_resources.toml"));
+        for invalid in [
+            VmResources { ram_mb: Some(128), vcpus: None },
+            VmResources { ram_mb: Some(65537), vcpus: None },
+            VmResources { ram_mb: None, vcpus: Some(0) },
+            VmResources { ram_mb: None, vcpus: Some(33) },
+        ] {
+            assert!(resolve_vm_resources(&state, None, invalid).is_err());
+        }
+    }
+}
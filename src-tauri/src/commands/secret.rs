@@ -0,0 +1,55 @@
+use serde::Serialize;
+use std::sync::Arc;
+use crate::secrets::SecretsState;
+use super::favorite::validate_partition_uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedPassphrases {
+    /// Where the passphrases are kept (e.g. "Keychain")
+    pub backend: String,
+    /// Partition UUIDs with a saved passphrase
+    pub uuids: Vec<String>,
+}
+
+fn saved_passphrases(state: &SecretsState) -> Result<SavedPassphrases, String> {
+    Ok(SavedPassphrases {
+        backend: state.backend().to_string(),
+        uuids: state
+            .list()
+            .map_err(|e| format!("Failed to list saved passphrases: {}", e))?,
+    })
+}
+
+/// Partitions with a saved passphrase; the passphrases themselves never leave
+/// the backend
+#[tauri::command]
+pub fn list_saved_passphrases(
+    state: tauri::State<'_, Arc<SecretsState>>,
+) -> Result<SavedPassphrases, String> {
+    saved_passphrases(&state)
+}
+
+#[tauri::command]
+pub fn forget_saved_passphrase(
+    state: tauri::State<'_, Arc<SecretsState>>,
+    uuid: String,
+) -> Result<SavedPassphrases, String> {
+    validate_partition_uuid(&uuid)?;
+    if !state
+        .delete(&uuid)
+        .map_err(|e| format!("Failed to forget passphrase: {}", e))?
+    {
+        return Err(format!("No saved passphrase for '{}'", uuid));
+    }
+    saved_passphrases(&state)
+}
+
+#[tauri::command]
+pub fn clear_saved_passphrases(
+    state: tauri::State<'_, Arc<SecretsState>>,
+) -> Result<SavedPassphrases, String> {
+    state
+        .clear()
+        .map_err(|e| format!("Failed to clear saved passphrases: {}", e))?;
+    saved_passphrases(&state)
+}
@@ -0,0 +1,432 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::{IpAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::backend;
+use crate::storage::write_private_toml;
+use super::action::remove_share_action;
+use super::favorite::validate_partition_uuid;
+use super::status::get_mount_status_sync;
+
+/// Upper bound on export entries, keeping the generated line readable
+const MAX_ALLOWED_NETWORKS: usize = 16;
+
+/// The host mounts the VM's export through the vmnet gateway, so that client
+/// is always allowed in addition to the configured networks
+const HOST_CLIENT: &str = "192.168.64.1";
+
+/// How the NFS server maps remote root and other users
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum SquashMode {
+    /// Remote root becomes the anonymous user
+    #[default]
+    #[serde(rename = "root_squash")]
+    Root,
+    #[serde(rename = "no_root_squash")]
+    NoRoot,
+    /// Every remote user becomes the anonymous user
+    #[serde(rename = "all_squash")]
+    All,
+}
+
+impl SquashMode {
+    fn export_option(self) -> &'static str {
+        match self {
+            SquashMode::Root => "root_squash",
+            SquashMode::NoRoot => "no_root_squash",
+            SquashMode::All => "all_squash",
+        }
+    }
+}
+
+/// Export settings for a partition shared beyond this Mac, keyed by
+/// partition UUID like favorites
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShareSettings {
+    pub uuid: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Client addresses or networks in CIDR notation (e.g. "10.0.4.0/24")
+    pub allowed_networks: Vec<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub squash: SquashMode,
+    /// Anonymous user/group for squashed requests
+    #[serde(default)]
+    pub anon_uid: Option<u32>,
+    #[serde(default)]
+    pub anon_gid: Option<u32>,
+}
+
+impl ShareSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_partition_uuid(&self.uuid)?;
+        if self.allowed_networks.is_empty() {
+            return Err("At least one allowed network is required to share a mount".to_string());
+        }
+        if self.allowed_networks.len() > MAX_ALLOWED_NETWORKS {
+            return Err(format!(
+                "At most {} allowed networks can be configured",
+                MAX_ALLOWED_NETWORKS
+            ));
+        }
+        for network in &self.allowed_networks {
+            validate_network(network)?;
+        }
+        if self.squash == SquashMode::NoRoot
+            && (self.anon_uid.is_some() || self.anon_gid.is_some())
+        {
+            return Err("Anonymous user and group only apply when squashing".to_string());
+        }
+        Ok(())
+    }
+
+    /// Client list for the CLI's `override_nfs_export`, in exports(5) form
+    /// without the path: `host(options) network(options) ...`
+    pub fn export_override(&self) -> String {
+        let mut options = vec![
+            if self.read_only { "ro" } else { "rw" }.to_string(),
+            "sync".to_string(),
+            "no_subtree_check".to_string(),
+            // macOS NFS clients connect from unprivileged ports
+            "insecure".to_string(),
+            self.squash.export_option().to_string(),
+        ];
+        if let Some(uid) = self.anon_uid {
+            options.push(format!("anonuid={}", uid));
+        }
+        if let Some(gid) = self.anon_gid {
+            options.push(format!("anongid={}", gid));
+        }
+        let options = options.join(",");
+
+        let mut clients = vec![HOST_CLIENT.to_string()];
+        for network in &self.allowed_networks {
+            let network = network.trim().to_string();
+            if !clients.contains(&network) {
+                clients.push(network);
+            }
+        }
+        clients
+            .iter()
+            .map(|client| format!("{}({})", client, options))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Accept an IP address or an address with a prefix length; hostnames and
+/// wildcards are rejected so the export cannot be wider than intended
+fn validate_network(network: &str) -> Result<(), String> {
+    let network = network.trim();
+    let (address, prefix) = match network.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (network, None),
+    };
+    let address: IpAddr = address
+        .parse()
+        .map_err(|_| format!("'{}' is not an IP address or network", network))?;
+    if let Some(prefix) = prefix {
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+        match prefix.parse::<u8>() {
+            Ok(length) if length <= max_prefix => {}
+            _ => return Err(format!("'{}' has an invalid prefix length", network)),
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredShares {
+    #[serde(default)]
+    shares: BTreeMap<String, ShareSettings>,
+}
+
+/// Share settings keyed by uppercased partition UUID
+pub struct SharesState {
+    path: PathBuf,
+    /// anylinuxfs config holding the per-share actions
+    actions_config: PathBuf,
+    shares: Mutex<BTreeMap<String, ShareSettings>>,
+}
+
+impl SharesState {
+    pub fn load(path: PathBuf, actions_config: PathBuf) -> Self {
+        let shares = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<StoredShares>(&contents).ok())
+            .map(|stored| stored.shares)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            actions_config,
+            shares: Mutex::new(shares),
+        }
+    }
+
+    pub fn list(&self) -> Vec<ShareSettings> {
+        self.shares
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<ShareSettings> {
+        self.shares
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&uuid.to_uppercase())
+            .cloned()
+    }
+
+    pub fn set(&self, settings: ShareSettings) -> Result<Vec<ShareSettings>, String> {
+        settings.validate()?;
+        let uuid = settings.uuid.to_uppercase();
+        let settings = ShareSettings {
+            uuid: uuid.clone(),
+            allowed_networks: settings
+                .allowed_networks
+                .iter()
+                .map(|network| network.trim().to_string())
+                .collect(),
+            ..settings
+        };
+        let mut shares = self
+            .shares
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = shares.clone();
+        updated.insert(uuid, settings);
+        write_shares(&self.path, &updated)?;
+        *shares = updated.clone();
+        Ok(updated.into_values().collect())
+    }
+
+    /// Forget a partition's share settings and the action its shared mounts
+    /// used, so the export list does not linger in the anylinuxfs config
+    pub fn remove(&self, uuid: &str) -> Result<Vec<ShareSettings>, String> {
+        let mut shares = self
+            .shares
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut updated = shares.clone();
+        if updated.remove(&uuid.to_uppercase()).is_none() {
+            return Err(format!("No share settings for '{}'", uuid));
+        }
+        remove_share_action(&self.actions_config, uuid)?;
+        write_shares(&self.path, &updated)?;
+        *shares = updated.clone();
+        Ok(updated.into_values().collect())
+    }
+}
+
+fn write_shares(path: &Path, shares: &BTreeMap<String, ShareSettings>) -> Result<(), String> {
+    let stored = StoredShares {
+        shares: shares.clone(),
+    };
+    write_private_toml(path, &stored, "shares")
+}
+
+/// A mounted partition that other machines can reach
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveShare {
+    pub device: String,
+    pub uuid: String,
+    pub label: Option<String>,
+    pub mount_point: String,
+    /// Path exported by the VM's NFS server
+    pub export_path: String,
+    pub allowed_networks: Vec<String>,
+    pub read_only: bool,
+    /// `host:/path` specs other machines can mount
+    pub addresses: Vec<String>,
+}
+
+/// Export path behind a host NFS mount ("localhost:/mnt/data" -> "/mnt/data").
+/// Falls back to the CLI's `/mnt/<volume name>` layout when the host mount
+/// table does not show the source.
+fn export_path(mount_point: &str, system_mounts: &[(String, String)]) -> String {
+    system_mounts
+        .iter()
+        .filter(|(_, mounted_at)| mounted_at == mount_point)
+        .find_map(|(source, _)| {
+            source
+                .split_once(":/")
+                .map(|(_, path)| format!("/{}", path))
+        })
+        .unwrap_or_else(|| {
+            let name = Path::new(mount_point)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            format!("/mnt/{}", name)
+        })
+}
+
+/// Address other machines on the LAN reach this Mac at. Connecting a UDP
+/// socket sends nothing; it only selects the outgoing interface.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    let address = socket.local_addr().ok()?.ip();
+    (!address.is_loopback() && !address.is_unspecified()).then_some(address)
+}
+
+fn active_shares(state: &SharesState) -> Result<Vec<ActiveShare>, String> {
+    let mounts = get_mount_status_sync()?;
+    if mounts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let backend = backend::current();
+    let details = backend.partition_details();
+    let system_mounts = backend.system_mounts();
+    let address = lan_address();
+
+    let mut shares = Vec::new();
+    for mount in mounts {
+        let Some(uuid) = details
+            .get(mount.device.trim_start_matches("/dev/"))
+            .and_then(|details| details.uuid.clone())
+        else {
+            continue;
+        };
+        let Some(settings) = state.get(&uuid) else {
+            continue;
+        };
+        let export_path = export_path(&mount.mount_point, &system_mounts);
+        let addresses = address
+            .iter()
+            .map(|address| format!("{}:{}", address, export_path))
+            .collect();
+        shares.push(ActiveShare {
+            device: mount.device,
+            uuid: settings.uuid,
+            label: settings.label,
+            mount_point: mount.mount_point,
+            export_path,
+            allowed_networks: settings.allowed_networks,
+            read_only: settings.read_only || mount.read_only,
+            addresses,
+        });
+    }
+    Ok(shares)
+}
+
+#[tauri::command]
+pub fn list_share_settings(state: tauri::State<'_, Arc<SharesState>>) -> Vec<ShareSettings> {
+    state.list()
+}
+
+/// Share settings apply from the next mount of the partition
+#[tauri::command]
+pub fn set_share_settings(
+    state: tauri::State<'_, Arc<SharesState>>,
+    settings: ShareSettings,
+) -> Result<Vec<ShareSettings>, String> {
+    state.set(settings)
+}
+
+#[tauri::command]
+pub fn remove_share_settings(
+    state: tauri::State<'_, Arc<SharesState>>,
+    uuid: String,
+) -> Result<Vec<ShareSettings>, String> {
+    validate_partition_uuid(&uuid)?;
+    state.remove(&uuid)
+}
+
+#[tauri::command]
+pub async fn list_active_shares(
+    state: tauri::State<'_, Arc<SharesState>>,
+) -> Result<Vec<ActiveShare>, String> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || active_shares(&state))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(networks: &[&str]) -> ShareSettings {
+        ShareSettings {
+            uuid: "6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60".to_string(),
+            label: Some("lab-data".to_string()),
+            allowed_networks: networks.iter().map(|n| n.to_string()).collect(),
+            read_only: true,
+            squash: SquashMode::All,
+            anon_uid: Some(1000),
+            anon_gid: None,
+        }
+    }
+
+    #[test]
+    fn export_override_lists_host_and_allowed_networks() {
+        let share = settings(&["10.0.4.0/24", "192.168.1.20"]);
+        share.validate().unwrap();
+        assert_eq!(
+            share.export_override(),
+            "192.168.64.1(ro,sync,no_subtree_check,insecure,all_squash,anonuid=1000) \
+             10.0.4.0/24(ro,sync,no_subtree_check,insecure,all_squash,anonuid=1000) \
+             192.168.1.20(ro,sync,no_subtree_check,insecure,all_squash,anonuid=1000)"
+        );
+    }
+
+    #[test]
+    fn networks_must_be_addresses_or_cidr() {
+        assert!(settings(&["fd00::/64"]).validate().is_ok());
+        assert!(settings(&[]).validate().is_err());
+        assert!(settings(&["*"]).validate().is_err());
+        assert!(settings(&["lab.example.com"]).validate().is_err());
+        assert!(settings(&["10.0.4.0/33"]).validate().is_err());
+        assert!(settings(&["10.0.4.0/24(rw)"]).validate().is_err());
+        let no_squash = ShareSettings {
+            squash: SquashMode::NoRoot,
+            ..settings(&["10.0.4.0/24"])
+        };
+        assert!(no_squash.validate().is_err(), "anonuid needs a squash mode");
+    }
+
+    #[test]
+    fn shares_round_trip_by_uuid() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("shares.toml");
+        let actions_config = directory.path().join("config.toml");
+        let state = SharesState::load(path.clone(), actions_config.clone());
+        state.set(settings(&[" 10.0.4.0/24 "])).unwrap();
+        fs::write(
+            &actions_config,
+            "[custom_actions.gui-share-6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60]\n\
+             override_nfs_export = \"10.0.4.0/24(rw)\"\n\n\
+             [custom_actions.backup]\n\
+             after_mount = \"true\"\n",
+        )
+        .unwrap();
+
+        let reloaded = SharesState::load(path, actions_config.clone());
+        let share = reloaded.get("6F1C2A4E-8D2B-4C7E-9A3F-1B2C3D4E5F60").unwrap();
+        assert_eq!(share.allowed_networks, vec!["10.0.4.0/24"]);
+        assert!(reloaded.remove("6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60").unwrap().is_empty());
+        assert!(reloaded.remove("6f1c2a4e-8d2b-4c7e-9a3f-1b2c3d4e5f60").is_err());
+        let config = fs::read_to_string(&actions_config).unwrap();
+        assert!(!config.contains("gui-share-"), "the share's action is deleted");
+        assert!(config.contains("backup"), "other actions are kept");
+    }
+
+    #[test]
+    fn export_path_comes_from_the_host_nfs_mount() {
+        let mounts = vec![
+            ("/dev/disk3s1".to_string(), "/".to_string()),
+            ("localhost:/mnt/lab-data".to_string(), "/Volumes/lab-data".to_string()),
+        ];
+        assert_eq!(export_path("/Volumes/lab-data", &mounts), "/mnt/lab-data");
+        assert_eq!(export_path("/Volumes/other", &mounts), "/mnt/other");
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use crate::cli;
+use std::sync::Arc;
+use tauri::AppHandle;
+use crate::cli::{self, CliOutput};
+use super::usage::{report_usage, MountUsage, UsageAlertState};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CliStatus {
@@ -109,27 +112,49 @@ pub struct MountInfo {
     pub device: String,
     pub mount_point: String,
     pub filesystem: Option<String>,
+    /// Mount options after the filesystem type, as reported by the CLI
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub read_only: bool,
     pub ram_mb: Option<u32>,
     pub vcpus: Option<u32>,
+    /// Capacity from statvfs; only filled in by `get_mount_status`
+    #[serde(default)]
+    pub usage: Option<MountUsage>,
 }
 
 #[tauri::command]
-pub async fn get_mount_status() -> Result<Vec<MountInfo>, String> {
-    tokio::task::spawn_blocking(get_mount_status_sync)
-        .await
-        .map_err(|e| format!("Task error: {}", e))?
+pub async fn get_mount_status(
+    app: AppHandle,
+    alerts: tauri::State<'_, Arc<UsageAlertState>>,
+) -> Result<Vec<MountInfo>, String> {
+    let alerts = alerts.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let mut mounts = get_mount_status_sync()?;
+        report_usage(&app, &alerts, &mut mounts);
+        Ok(mounts)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
 }
 
+/// Mounts reported by `anylinuxfs status`. A failing query is an error, not
+/// an empty list; callers that act on "not mounted" must not guess.
 pub fn get_mount_status_sync() -> Result<Vec<MountInfo>, String> {
-    if let Some(status) = cli::get_status() {
-        if !status.is_empty() {
-            let mounts: Vec<MountInfo> = status.lines()
-                .filter_map(parse_status_line)
-                .collect();
-            return Ok(mounts);
-        }
+    cli::query::<Vec<MountInfo>>(&["status"])
+        .map_err(|e| format!("Failed to query mount status: {}", e))
+}
+
+impl CliOutput for Vec<MountInfo> {
+    /// Empty when nothing is mounted, one line per mount otherwise
+    fn from_text(output: &str) -> Result<Self, String> {
+        Ok(output.lines().filter_map(parse_status_line).collect())
     }
-    Ok(Vec::new())
+}
+
+fn is_read_only(options: &[String]) -> bool {
+    options.iter().any(|o| o == "ro" || o == "read-only" || o == "rdonly")
 }
 
 /// Parse a line from `anylinuxfs status` output.
@@ -149,6 +174,11 @@ fn parse_status_line(line: &str) -> Option<MountInfo> {
     let paren_content = &rest[paren_pos + 1..close_paren];
     let filesystem = paren_content.split(',').next()
         .map(|s| s.trim().to_string());
+    let options: Vec<String> = paren_content.split(',').skip(1)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let read_only = is_read_only(&options);
 
     // Parse VM info: "VM[cpus: N, ram: N MiB]"
     let mut ram_mb = None;
@@ -171,7 +201,32 @@ fn parse_status_line(line: &str) -> Option<MountInfo> {
         device,
         mount_point,
         filesystem,
+        options,
+        read_only,
         ram_mb,
         vcpus,
+        usage: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_line_reports_options_and_read_only_state() {
+        let mount = parse_status_line(
+            "/dev/disk4s1 on /Volumes/backup (ext4, ro, noatime) VM[cpus: 2, ram: 1024 MiB]",
+        )
+        .unwrap();
+        assert_eq!(mount.device, "/dev/disk4s1");
+        assert_eq!(mount.mount_point, "/Volumes/backup");
+        assert_eq!(mount.filesystem.as_deref(), Some("ext4"));
+        assert_eq!(mount.options, vec!["ro", "noatime"]);
+        assert!(mount.read_only);
+        assert_eq!((mount.vcpus, mount.ram_mb), (Some(2), Some(1024)));
+
+        let writable = parse_status_line("/dev/disk5s1 on /Volumes/data (ntfs, uid=501)").unwrap();
+        assert!(!writable.read_only);
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use crate::backend::{DiskBackend, PartitionDetails};
+
+/// Host view of one partition, compared between watcher checks
+#[derive(Debug, Clone, PartialEq)]
+struct PartitionState {
+    uuid: Option<String>,
+    filesystem: Option<String>,
+    mount_point: Option<String>,
+}
+
+/// Whole disks and their partitions keyed by device path, as seen by the
+/// host at one watcher check
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskSnapshot {
+    disks: BTreeMap<String, BTreeMap<String, PartitionState>>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PartitionChange {
+    pub device: String,
+    /// Whole disk the partition belongs to
+    pub disk: String,
+    pub uuid: Option<String>,
+    pub filesystem: Option<String>,
+    /// Where the host has it mounted, if anywhere
+    pub mount_point: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DiskChange {
+    pub device: String,
+    /// Partition devices on the disk
+    pub partitions: Vec<String>,
+}
+
+/// Payload of `disks-changed`. Partition lists include the partitions of
+/// added and removed disks; removed entries carry their last known UUID.
+/// All lists empty means the change was not visible to the host (for example
+/// an anylinuxfs NFS mount appearing), and listeners should do a full refresh.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DisksChangedEvent {
+    pub added_disks: Vec<DiskChange>,
+    pub removed_disks: Vec<DiskChange>,
+    /// Disks present before and after whose partitions changed
+    pub changed_disks: Vec<DiskChange>,
+    pub added_partitions: Vec<PartitionChange>,
+    pub removed_partitions: Vec<PartitionChange>,
+    /// Partitions whose UUID, filesystem or host mount changed
+    pub changed_partitions: Vec<PartitionChange>,
+}
+
+impl DisksChangedEvent {
+    pub fn is_empty(&self) -> bool {
+        self.added_disks.is_empty()
+            && self.removed_disks.is_empty()
+            && self.changed_disks.is_empty()
+            && self.added_partitions.is_empty()
+            && self.removed_partitions.is_empty()
+            && self.changed_partitions.is_empty()
+    }
+}
+
+impl DiskSnapshot {
+    /// Snapshot from partition details the caller already fetched, so the
+    /// watcher runs `diskutil info -all` / lsblk once per check
+    pub fn capture(backend: &dyn DiskBackend, details: &HashMap<String, PartitionDetails>) -> Self {
+        Self::from_parts(details, &backend.system_mounts(), |device_id| {
+            backend.whole_disk(device_id)
+        })
+    }
+
+    fn from_parts(
+        details: &HashMap<String, PartitionDetails>,
+        system_mounts: &[(String, String)],
+        whole_disk: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let mut disks: BTreeMap<String, BTreeMap<String, PartitionState>> = BTreeMap::new();
+        for (device_id, details) in details {
+            let Some(disk_id) = whole_disk(device_id) else {
+                continue;
+            };
+            let partitions = disks.entry(format!("/dev/{}", disk_id)).or_default();
+            // The whole-disk entry is only a partition when it holds a
+            // filesystem itself (issue #83), not a partition table
+            if *device_id == disk_id && details.filesystem.is_none() {
+                continue;
+            }
+
+            let device = format!("/dev/{}", device_id);
+            let mount_point = system_mounts
+                .iter()
+                .find(|(mount_device, _)| *mount_device == device)
+                .map(|(_, mount_point)| mount_point.clone());
+            partitions.insert(
+                device,
+                PartitionState {
+                    uuid: details
+                        .uuid
+                        .as_ref()
+                        .filter(|uuid| !uuid.is_empty())
+                        .map(|uuid| uuid.to_uppercase()),
+                    filesystem: details.filesystem.clone(),
+                    mount_point,
+                },
+            );
+        }
+        Self { disks }
+    }
+
+    /// What changed from `self` to `newer`
+    pub fn diff(&self, newer: &DiskSnapshot) -> DisksChangedEvent {
+        let mut event = DisksChangedEvent::default();
+        let empty = BTreeMap::new();
+
+        for (disk, partitions) in &newer.disks {
+            let previous = match self.disks.get(disk) {
+                Some(previous) => previous,
+                None => {
+                    event.added_disks.push(disk_change(disk, partitions));
+                    &empty
+                }
+            };
+            let mut partitions_changed = false;
+            for (device, state) in partitions {
+                match previous.get(device) {
+                    None => {
+                        partitions_changed = true;
+                        event.added_partitions.push(partition_change(disk, device, state));
+                    }
+                    Some(previous_state) if previous_state != state => {
+                        event.changed_partitions.push(partition_change(disk, device, state));
+                    }
+                    Some(_) => {}
+                }
+            }
+            for (device, state) in previous {
+                if !partitions.contains_key(device) {
+                    partitions_changed = true;
+                    event.removed_partitions.push(partition_change(disk, device, state));
+                }
+            }
+            if partitions_changed && self.disks.contains_key(disk) {
+                event.changed_disks.push(disk_change(disk, partitions));
+            }
+        }
+
+        for (disk, partitions) in &self.disks {
+            if !newer.disks.contains_key(disk) {
+                event.removed_disks.push(disk_change(disk, partitions));
+                event.removed_partitions.extend(
+                    partitions
+                        .iter()
+                        .map(|(device, state)| partition_change(disk, device, state)),
+                );
+            }
+        }
+
+        event
+    }
+}
+
+fn disk_change(disk: &str, partitions: &BTreeMap<String, PartitionState>) -> DiskChange {
+    DiskChange {
+        device: disk.to_string(),
+        partitions: partitions.keys().cloned().collect(),
+    }
+}
+
+fn partition_change(disk: &str, device: &str, state: &PartitionState) -> PartitionChange {
+    PartitionChange {
+        device: device.to_string(),
+        disk: disk.to_string(),
+        uuid: state.uuid.clone(),
+        filesystem: state.filesystem.clone(),
+        mount_point: state.mount_point.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(entries: &[(&str, Option<&str>, Option<&str>)]) -> HashMap<String, PartitionDetails> {
+        entries
+            .iter()
+            .map(|(device_id, filesystem, uuid)| {
+                (
+                    device_id.to_string(),
+                    PartitionDetails {
+                        filesystem: filesystem.map(str::to_string),
+                        uuid: uuid.map(str::to_string),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn snapshot(
+        entries: &[(&str, Option<&str>, Option<&str>)],
+        mounts: &[(&str, &str)],
+    ) -> DiskSnapshot {
+        let mounts: Vec<(String, String)> = mounts
+            .iter()
+            .map(|(device, mount_point)| (device.to_string(), mount_point.to_string()))
+            .collect();
+        DiskSnapshot::from_parts(&details(entries), &mounts, |device_id| {
+            device_id
+                .strip_prefix("disk")
+                .map(|rest| format!("disk{}", rest.split('s').next().unwrap_or(rest)))
+        })
+    }
+
+    #[test]
+    fn plugged_in_drive_is_reported_with_its_partitions() {
+        let before = snapshot(&[("disk0", None, None), ("disk0s1", Some("apfs"), Some("aaaa"))], &[]);
+        let after = snapshot(
+            &[
+                ("disk0", None, None),
+                ("disk0s1", Some("apfs"), Some("aaaa")),
+                ("disk4", None, None),
+                ("disk4s1", Some("ext4"), Some("6f1c2a4e")),
+                ("disk4s2", Some("ntfs"), Some("0a1b2c3d")),
+            ],
+            &[],
+        );
+
+        let event = before.diff(&after);
+        assert_eq!(event.added_disks.len(), 1);
+        assert_eq!(event.added_disks[0].device, "/dev/disk4");
+        assert_eq!(event.added_disks[0].partitions, vec!["/dev/disk4s1", "/dev/disk4s2"]);
+        assert_eq!(event.added_partitions.len(), 2);
+        assert_eq!(event.added_partitions[0].uuid.as_deref(), Some("6F1C2A4E"));
+        assert!(event.removed_disks.is_empty() && event.changed_disks.is_empty());
+
+        let reverse = after.diff(&before);
+        assert_eq!(reverse.removed_disks[0].device, "/dev/disk4");
+        assert_eq!(reverse.removed_partitions.len(), 2, "removed partitions keep their UUIDs");
+        assert_eq!(reverse.removed_partitions[1].uuid.as_deref(), Some("0A1B2C3D"));
+    }
+
+    #[test]
+    fn repartitioning_and_host_mounts_are_changes() {
+        let before = snapshot(
+            &[("disk4", None, None), ("disk4s1", Some("ext4"), Some("aaaa"))],
+            &[],
+        );
+        let after = snapshot(
+            &[
+                ("disk4", None, None),
+                ("disk4s1", Some("exfat"), Some("bbbb")),
+                ("disk4s2", Some("ntfs"), Some("cccc")),
+            ],
+            &[("/dev/disk4s2", "/Volumes/Data")],
+        );
+
+        let event = before.diff(&after);
+        assert!(event.added_disks.is_empty());
+        assert_eq!(event.changed_disks.len(), 1);
+        assert_eq!(event.changed_partitions.len(), 1);
+        assert_eq!(event.changed_partitions[0].filesystem.as_deref(), Some("exfat"));
+        assert_eq!(event.added_partitions[0].mount_point.as_deref(), Some("/Volumes/Data"));
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn whole_disk_filesystems_are_partitions() {
+        let current = snapshot(&[("disk5", Some("crypto_LUKS"), Some("dddd"))], &[]);
+        let event = DiskSnapshot::default().diff(&current);
+        assert_eq!(event.added_disks[0].partitions, vec!["/dev/disk5"]);
+    }
+}
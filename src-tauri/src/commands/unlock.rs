@@ -0,0 +1,199 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// cryptsetup's default upper bound for `--keyfile-size`
+const MAX_KEYFILE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// How an encrypted partition is unlocked. Plain passphrases keep using
+/// `MountRequest::passphrase`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum UnlockMethod {
+    /// LUKS keyfile on the host; its contents are streamed to the CLI
+    Keyfile { path: String },
+    /// BitLocker 48-digit recovery key
+    RecoveryKey { key: String },
+}
+
+/// Unlock material after validation, ready to hand to the CLI
+pub enum UnlockKey {
+    Passphrase(String),
+    Keyfile(Vec<u8>),
+}
+
+impl UnlockMethod {
+    /// Validate and load the key; runs before a VM is started
+    pub fn resolve(&self) -> Result<UnlockKey, String> {
+        match self {
+            Self::Keyfile { path } => read_keyfile(Path::new(path)).map(UnlockKey::Keyfile),
+            Self::RecoveryKey { key } => {
+                normalize_recovery_key(key).map(UnlockKey::Passphrase)
+            }
+        }
+    }
+}
+
+/// Read a keyfile into memory. Only regular files up to 8 MiB are accepted
+/// so a device node or FIFO cannot stall the mount.
+pub fn read_keyfile(path: &Path) -> Result<Vec<u8>, String> {
+    if !path.is_absolute() {
+        return Err("Keyfile path must be absolute".to_string());
+    }
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Cannot read keyfile {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("Keyfile {} is not a regular file", path.display()));
+    }
+    if metadata.len() == 0 {
+        return Err(format!("Keyfile {} is empty", path.display()));
+    }
+    if metadata.len() > MAX_KEYFILE_BYTES {
+        return Err(format!(
+            "Keyfile {} is larger than {} MiB",
+            path.display(),
+            MAX_KEYFILE_BYTES / 1024 / 1024
+        ));
+    }
+    fs::read(path).map_err(|e| format!("Cannot read keyfile {}: {}", path.display(), e))
+}
+
+/// Check a BitLocker recovery key and return it in the canonical
+/// `111111-222222-...` form. Each of the eight 6-digit groups is a multiple
+/// of 11 below 720896, which catches most typos before the VM starts.
+pub fn normalize_recovery_key(key: &str) -> Result<String, String> {
+    let groups: Vec<&str> = key
+        .split(|c: char| c == '-' || c.is_whitespace())
+        .filter(|group| !group.is_empty())
+        .collect();
+    if groups.len() != 8 || groups.iter().any(|group| group.len() != 6) {
+        return Err(
+            "A BitLocker recovery key is 48 digits in eight groups of six".to_string(),
+        );
+    }
+    for (index, group) in groups.iter().enumerate() {
+        let value: u32 = group
+            .parse()
+            .map_err(|_| format!("Recovery key group {} contains non-digits", index + 1))?;
+        if !value.is_multiple_of(11) || value / 11 > u16::MAX as u32 {
+            return Err(format!(
+                "Recovery key group {} ({}) is not valid; check for a typo",
+                index + 1,
+                group
+            ));
+        }
+    }
+    Ok(groups.join("-"))
+}
+
+/// Why an encrypted mount failed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncryptionFailure {
+    /// No key was given, or the CLI asked for one
+    KeyRequired,
+    /// A key was given and rejected
+    WrongKey,
+}
+
+const WRONG_KEY_MARKERS: &[&str] = &[
+    "wrong key",
+    "wrong passphrase",
+    "incorrect passphrase",
+    "incorrect password",
+    "invalid passphrase",
+    "invalid recovery key",
+    "no key available with this passphrase",
+    "bad password",
+    "authentication failed",
+];
+
+const KEY_REQUIRED_MARKERS: &[&str] = &[
+    "passphrase required",
+    "key required",
+    "enter passphrase",
+    "no passphrase",
+];
+
+const ENCRYPTION_MARKERS: &[&str] =
+    &["luks", "bitlocker", "decrypt", "passphrase", "password", "encrypted", "keyfile"];
+
+/// Classify CLI output from a failed mount. `key_supplied` is false for the
+/// probe attempt, whose sentinel passphrase is always rejected, so a "wrong
+/// key" then still means a key is needed.
+pub fn classify_encryption_error(output: &str, key_supplied: bool) -> Option<EncryptionFailure> {
+    let lower = output.to_lowercase();
+    let contains_any = |markers: &[&str]| markers.iter().any(|marker| lower.contains(marker));
+    let wrong_key = contains_any(WRONG_KEY_MARKERS);
+    if !wrong_key && !contains_any(ENCRYPTION_MARKERS) {
+        return None;
+    }
+    if key_supplied && (wrong_key || !contains_any(KEY_REQUIRED_MARKERS)) {
+        Some(EncryptionFailure::WrongKey)
+    } else {
+        Some(EncryptionFailure::KeyRequired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_keys_are_validated_and_normalised() {
+        let key = "162393-049412-583649-710908-311366-296263-616506-174163";
+        assert_eq!(normalize_recovery_key(key).unwrap(), key);
+        assert_eq!(
+            normalize_recovery_key(&key.replace('-', " ")).unwrap(),
+            key,
+            "spaces are accepted as separators"
+        );
+        // One digit off in the third group breaks the checksum
+        assert!(normalize_recovery_key(
+            "162393-049412-583648-710908-311366-296263-616506-174163"
+        )
+        .is_err());
+        // 720896 = 11 * 65536 is one past the largest valid group
+        assert!(normalize_recovery_key(
+            "720896-049412-583649-710908-311366-296263-616506-174163"
+        )
+        .is_err());
+        assert!(normalize_recovery_key("162393-049412").is_err());
+        assert!(normalize_recovery_key("correct horse battery staple").is_err());
+    }
+
+    #[test]
+    fn keyfiles_must_be_small_regular_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let keyfile = directory.path().join("volume.key");
+        fs::write(&keyfile, [0u8, 1, 2, 255]).unwrap();
+        assert_eq!(read_keyfile(&keyfile).unwrap(), vec![0u8, 1, 2, 255]);
+
+        fs::write(directory.path().join("empty.key"), b"").unwrap();
+        assert!(read_keyfile(&directory.path().join("empty.key")).is_err());
+        assert!(read_keyfile(directory.path()).is_err());
+        assert!(read_keyfile(Path::new("volume.key")).is_err());
+    }
+
+    #[test]
+    fn wrong_key_is_distinguished_from_key_required() {
+        let rejected = "Error: No key available with this passphrase.";
+        assert_eq!(
+            classify_encryption_error(rejected, true),
+            Some(EncryptionFailure::WrongKey)
+        );
+        assert_eq!(
+            classify_encryption_error(rejected, false),
+            Some(EncryptionFailure::KeyRequired),
+            "the probe sentinel is always rejected"
+        );
+        assert_eq!(
+            classify_encryption_error("LUKS volume: passphrase required", true),
+            Some(EncryptionFailure::KeyRequired)
+        );
+        assert_eq!(
+            classify_encryption_error("Failed to decrypt BitLocker volume", true),
+            Some(EncryptionFailure::WrongKey)
+        );
+        assert_eq!(classify_encryption_error("mount: unknown filesystem", true), None);
+    }
+}
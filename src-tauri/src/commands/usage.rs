@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use crate::storage::write_private_toml;
+use super::health::bounded;
+use super::status::MountInfo;
+
+/// How long statvfs may take before a mount is reported without usage. An
+/// unresponsive NFS server blocks the call indefinitely.
+const STATVFS_TIMEOUT: Duration = Duration::from_secs(2);
+
+const DEFAULT_THRESHOLD_PERCENT: u8 = 90;
+const MIN_THRESHOLD_PERCENT: u8 = 50;
+const MAX_THRESHOLD_PERCENT: u8 = 99;
+
+/// A mount that triggered an alert is re-armed once usage drops this many
+/// points below the threshold, so hovering around it does not spam events
+const REARM_MARGIN_PERCENT: f64 = 2.0;
+
+/// Capacity of a mounted filesystem as seen through the host NFS mount
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MountUsage {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// Space available to unprivileged users
+    pub available_bytes: u64,
+    pub total_inodes: u64,
+    pub used_inodes: u64,
+    pub available_inodes: u64,
+}
+
+impl MountUsage {
+    /// Used share of the space usable by unprivileged users, like df
+    pub fn used_percent(&self) -> f64 {
+        percent(self.used_bytes, self.used_bytes + self.available_bytes)
+    }
+
+    /// Filesystems without inode accounting (e.g. FAT) report zero inodes
+    pub fn inode_used_percent(&self) -> f64 {
+        percent(self.used_inodes, self.used_inodes + self.available_inodes)
+    }
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 * 100.0 / total as f64
+    }
+}
+
+fn statvfs(path: &Path) -> Option<MountUsage> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // Safe: `path` is NUL-terminated and `stat` is a valid out-pointer
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let block_size = stat.f_frsize as u64;
+    let total_bytes = stat.f_blocks as u64 * block_size;
+    let free_bytes = stat.f_bfree as u64 * block_size;
+    let total_inodes = stat.f_files as u64;
+    Some(MountUsage {
+        total_bytes,
+        used_bytes: total_bytes.saturating_sub(free_bytes),
+        available_bytes: stat.f_bavail as u64 * block_size,
+        total_inodes,
+        used_inodes: total_inodes.saturating_sub(stat.f_ffree as u64),
+        available_inodes: stat.f_favail as u64,
+    })
+}
+
+/// statvfs on a worker thread, giving up after `STATVFS_TIMEOUT`. A thread
+/// stuck on a dead mount is left behind rather than blocking the caller.
+pub fn read_usage(mount_point: &str) -> Option<MountUsage> {
+    let path = PathBuf::from(mount_point);
+    match bounded(STATVFS_TIMEOUT, move || statvfs(&path)) {
+        Some(usage) => usage,
+        None => {
+            log::warn!("statvfs on {} did not return in time", mount_point);
+            None
+        }
+    }
+}
+
+/// Payload of `mount-almost-full`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MountAlmostFullEvent {
+    pub device: String,
+    pub mount_point: String,
+    pub used_percent: f64,
+    pub inode_used_percent: f64,
+    pub available_bytes: u64,
+    pub threshold_percent: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredAlerts {
+    threshold_percent: u8,
+}
+
+/// "Almost full" threshold and the mounts already alerted for
+pub struct UsageAlertState {
+    path: PathBuf,
+    threshold_percent: Mutex<u8>,
+    alerted: Mutex<HashSet<String>>,
+}
+
+impl UsageAlertState {
+    pub fn load(path: PathBuf) -> Self {
+        let threshold_percent = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<StoredAlerts>(&contents).ok())
+            .map(|stored| stored.threshold_percent)
+            .filter(|percent| validate_threshold(*percent).is_ok())
+            .unwrap_or(DEFAULT_THRESHOLD_PERCENT);
+
+        Self {
+            path,
+            threshold_percent: Mutex::new(threshold_percent),
+            alerted: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn threshold_percent(&self) -> u8 {
+        *self
+            .threshold_percent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn set_threshold_percent(&self, percent: u8) -> Result<(), String> {
+        validate_threshold(percent)?;
+        let mut threshold = self
+            .threshold_percent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_alerts(&self.path, percent)?;
+        *threshold = percent;
+        // Re-evaluate every mount against the new threshold
+        self.alerted
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+        Ok(())
+    }
+
+    /// Mounts that crossed the threshold since the previous check. Space and
+    /// inodes both count; unmounted mount points are forgotten.
+    pub fn check(&self, mounts: &[MountInfo]) -> Vec<MountAlmostFullEvent> {
+        let threshold_percent = self.threshold_percent();
+        let threshold = threshold_percent as f64;
+        let mut alerted = self
+            .alerted
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        alerted.retain(|mount_point| mounts.iter().any(|m| &m.mount_point == mount_point));
+
+        let mut events = Vec::new();
+        for mount in mounts {
+            let Some(usage) = mount.usage else {
+                continue;
+            };
+            let fullest = usage.used_percent().max(usage.inode_used_percent());
+            if fullest >= threshold {
+                if alerted.insert(mount.mount_point.clone()) {
+                    events.push(MountAlmostFullEvent {
+                        device: mount.device.clone(),
+                        mount_point: mount.mount_point.clone(),
+                        used_percent: usage.used_percent(),
+                        inode_used_percent: usage.inode_used_percent(),
+                        available_bytes: usage.available_bytes,
+                        threshold_percent,
+                    });
+                }
+            } else if fullest < threshold - REARM_MARGIN_PERCENT {
+                alerted.remove(&mount.mount_point);
+            }
+        }
+        events
+    }
+}
+
+fn validate_threshold(percent: u8) -> Result<(), String> {
+    if !(MIN_THRESHOLD_PERCENT..=MAX_THRESHOLD_PERCENT).contains(&percent) {
+        return Err(format!(
+            "Threshold must be between {}% and {}%",
+            MIN_THRESHOLD_PERCENT, MAX_THRESHOLD_PERCENT
+        ));
+    }
+    Ok(())
+}
+
+fn write_alerts(path: &Path, threshold_percent: u8) -> Result<(), String> {
+    write_private_toml(path, &StoredAlerts { threshold_percent }, "usage alerts")
+}
+
+/// Fill in usage for each mount and emit `mount-almost-full` for mounts that
+/// crossed the threshold
+pub fn report_usage(app: &AppHandle, state: &UsageAlertState, mounts: &mut [MountInfo]) {
+    for mount in mounts.iter_mut() {
+        mount.usage = read_usage(&mount.mount_point);
+    }
+    for event in state.check(mounts) {
+        log::warn!(
+            "{} is {:.0}% full ({:.0}% of inodes)",
+            event.mount_point,
+            event.used_percent,
+            event.inode_used_percent
+        );
+        let _ = app.emit("mount-almost-full", event);
+    }
+}
+
+#[tauri::command]
+pub fn get_usage_alert_threshold(state: tauri::State<'_, Arc<UsageAlertState>>) -> u8 {
+    state.threshold_percent()
+}
+
+#[tauri::command]
+pub fn set_usage_alert_threshold(
+    state: tauri::State<'_, Arc<UsageAlertState>>,
+    percent: u8,
+) -> Result<u8, String> {
+    state.set_threshold_percent(percent)?;
+    Ok(state.threshold_percent())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(mount_point: &str, used: u64, available: u64, inodes: (u64, u64)) -> MountInfo {
+        MountInfo {
+            device: "/dev/disk4s1".to_string(),
+            mount_point: mount_point.to_string(),
+            filesystem: Some("ext4".to_string()),
+            options: Vec::new(),
+            read_only: false,
+            ram_mb: None,
+            vcpus: None,
+            usage: Some(MountUsage {
+                total_bytes: used + available,
+                used_bytes: used,
+                available_bytes: available,
+                total_inodes: inodes.0 + inodes.1,
+                used_inodes: inodes.0,
+                available_inodes: inodes.1,
+            }),
+        }
+    }
+
+    #[test]
+    fn statvfs_reports_the_filesystem_of_a_directory() {
+        let directory = tempfile::tempdir().unwrap();
+        let usage = read_usage(&directory.path().to_string_lossy()).unwrap();
+        assert!(usage.total_bytes > 0);
+        assert!(usage.used_bytes <= usage.total_bytes);
+        assert!(read_usage("/nonexistent/anylinuxfs").is_none());
+    }
+
+    #[test]
+    fn almost_full_fires_once_until_usage_drops() {
+        let directory = tempfile::tempdir().unwrap();
+        let state = UsageAlertState::load(directory.path().join("usage_alerts.toml"));
+
+        assert!(state.check(&[mount("/Volumes/backup", 80, 20, (0, 0))]).is_empty());
+        let events = state.check(&[mount("/Volumes/backup", 95, 5, (0, 0))]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].threshold_percent, 90);
+        assert!(state.check(&[mount("/Volumes/backup", 96, 4, (0, 0))]).is_empty());
+
+        // Inside the re-arm margin nothing changes, below it the alert re-arms
+        assert!(state.check(&[mount("/Volumes/backup", 89, 11, (0, 0))]).is_empty());
+        assert!(state.check(&[mount("/Volumes/backup", 95, 5, (0, 0))]).is_empty());
+        state.check(&[mount("/Volumes/backup", 50, 50, (0, 0))]);
+        assert_eq!(state.check(&[mount("/Volumes/backup", 95, 5, (0, 0))]).len(), 1);
+    }
+
+    #[test]
+    fn inode_exhaustion_counts_as_full() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("usage_alerts.toml");
+        let state = UsageAlertState::load(path.clone());
+        state.set_threshold_percent(80).unwrap();
+        assert!(state.set_threshold_percent(100).is_err());
+
+        let events = state.check(&[mount("/Volumes/mail", 10, 90, (850, 150))]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].inode_used_percent, 85.0);
+        assert_eq!(UsageAlertState::load(path).threshold_percent(), 80);
+    }
+}
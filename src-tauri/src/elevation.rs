@@ -6,21 +6,41 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::{Duration, Instant};
-
-/// Managed approval and an encryption prompt can both require user interaction.
-pub const INTERACTIVE_ELEVATION_TIMEOUT_SECS: u64 = 600;
+use std::time::{Duration, Instant, SystemTime};
+use crate::askpass::{self, AskpassBackend};
+use crate::audit::{AuditMethod, AuditOutcome, AuditScope};
+use crate::cli;
+use crate::helper::HelperClient;
+use crate::ownership::ownership_warnings;
+use crate::paths::get_helper_socket_path;
+use crate::polkit;
+use crate::storage::write_private_toml;
+use crate::timeouts::OperationPolicies;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ElevationMode {
     Native,
     InteractiveTerminal,
+    /// Long-lived root helper started once with sudo
+    Helper,
+    /// pkexec, for Linux desktops where polkit is set up and sudo is not
+    Polkit,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ElevationPolicy {
     pub mode: ElevationMode,
+    /// Password dialog sudo falls back to in native mode
+    pub askpass: AskpassBackend,
+    pub available_askpass: Vec<AskpassBackend>,
+    pub operations: OperationPolicies,
+    /// Replaces the detected terminal in Interactive Terminal mode
+    pub terminal_command: Option<String>,
+    /// Why the helper would refuse to run the detected CLI as root
+    pub helper_warnings: Vec<String>,
+    /// Start the helper despite `helper_warnings`
+    pub accept_helper_ownership_risk: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -55,9 +75,39 @@ pub enum TerminalExecutionError {
     Launch(String),
 }
 
+/// Terminal session from an earlier GUI run whose shell is still alive
+#[derive(Debug, Clone)]
+pub struct RunningHandoff {
+    pub pid: u32,
+    script: String,
+}
+
+impl RunningHandoff {
+    /// Whether the session's command line names `device`
+    pub fn targets_device(&self, device: &str) -> bool {
+        self.script.contains(&shell_quote(device))
+    }
+}
+
+/// Result of sweeping handoff directories from earlier sessions
+#[derive(Debug, Default)]
+pub struct HandoffReclaim {
+    pub removed: Vec<PathBuf>,
+    /// Sessions whose shell is still running; these are left alone
+    pub running: Vec<RunningHandoff>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct StoredPreferences {
     mode: ElevationMode,
+    #[serde(default)]
+    askpass: AskpassBackend,
+    #[serde(default)]
+    operations: OperationPolicies,
+    #[serde(default)]
+    terminal_command: Option<String>,
+    #[serde(default)]
+    accept_helper_ownership_risk: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +125,11 @@ struct TerminalSession {
 pub struct ElevationState {
     config_path: PathBuf,
     mode: RwLock<ElevationMode>,
+    askpass: RwLock<AskpassBackend>,
+    operations: RwLock<OperationPolicies>,
+    terminal_command: RwLock<Option<String>>,
+    accept_helper_ownership_risk: RwLock<bool>,
+    helper: HelperClient,
     sessions: Mutex<HashMap<u64, TerminalSession>>,
     active_operations: Mutex<HashSet<String>>,
     cancellation_requests: Mutex<HashSet<String>>,
@@ -101,11 +156,22 @@ impl Drop for ElevationOperationGuard {
 
 impl ElevationState {
     pub fn load(config_path: PathBuf) -> Self {
-        let mode = read_stored_mode(&config_path).unwrap_or(ElevationMode::Native);
+        let preferences = read_stored_preferences(&config_path).unwrap_or(StoredPreferences {
+            mode: ElevationMode::Native,
+            askpass: AskpassBackend::Auto,
+            operations: OperationPolicies::default(),
+            terminal_command: None,
+            accept_helper_ownership_risk: false,
+        });
 
         Self {
             config_path,
-            mode: RwLock::new(mode),
+            mode: RwLock::new(preferences.mode),
+            askpass: RwLock::new(preferences.askpass),
+            operations: RwLock::new(preferences.operations),
+            terminal_command: RwLock::new(preferences.terminal_command),
+            accept_helper_ownership_risk: RwLock::new(preferences.accept_helper_ownership_risk),
+            helper: HelperClient::new(get_helper_socket_path()),
             sessions: Mutex::new(HashMap::new()),
             active_operations: Mutex::new(HashSet::new()),
             cancellation_requests: Mutex::new(HashSet::new()),
@@ -114,7 +180,32 @@ impl ElevationState {
     }
 
     pub fn policy(&self) -> ElevationPolicy {
-        ElevationPolicy { mode: self.mode() }
+        ElevationPolicy {
+            mode: self.mode(),
+            askpass: self.askpass_backend(),
+            available_askpass: askpass::available_backends(),
+            operations: self.operation_policies(),
+            terminal_command: self.terminal_command(),
+            helper_warnings: cli::get_path().map(ownership_warnings).unwrap_or_default(),
+            accept_helper_ownership_risk: self.helper_ownership_risk_accepted(),
+        }
+    }
+
+    fn stored_preferences(&self) -> StoredPreferences {
+        StoredPreferences {
+            mode: self.mode(),
+            askpass: self.askpass_backend(),
+            operations: self.operation_policies(),
+            terminal_command: self.terminal_command(),
+            accept_helper_ownership_risk: self.helper_ownership_risk_accepted(),
+        }
+    }
+
+    pub fn askpass_backend(&self) -> AskpassBackend {
+        *self
+            .askpass
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
     pub fn mode(&self) -> ElevationMode {
@@ -124,7 +215,35 @@ impl ElevationState {
             .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
+    pub fn operation_policies(&self) -> OperationPolicies {
+        self.operations
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn terminal_command(&self) -> Option<String> {
+        self.terminal_command
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn helper_ownership_risk_accepted(&self) -> bool {
+        *self
+            .accept_helper_ownership_risk
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn helper(&self) -> &HelperClient {
+        &self.helper
+    }
+
     pub fn set_mode(&self, mode: ElevationMode) -> Result<ElevationPolicy, String> {
+        if mode == ElevationMode::Polkit && polkit::find_pkexec().is_none() {
+            return Err("pkexec not found; install polkit to use this elevation method".to_string());
+        }
         // Keep the selected policy stable for the lifetime of every privileged
         // operation. Holding this lock through persistence closes the window in
         // which a new operation could snapshot the old mode while it is changing.
@@ -139,12 +258,143 @@ impl ElevationState {
             );
         }
 
-        write_stored_mode(&self.config_path, mode)?;
+        write_stored_preferences(
+            &self.config_path,
+            &StoredPreferences {
+                mode,
+                ..self.stored_preferences()
+            },
+        )?;
+        let previous = std::mem::replace(
+            &mut *self
+                .mode
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            mode,
+        );
+        drop(active_operations);
+        if previous == ElevationMode::Helper && mode != ElevationMode::Helper {
+            self.helper.shutdown();
+        }
+        Ok(self.policy())
+    }
+
+    pub fn set_askpass_backend(&self, backend: AskpassBackend) -> Result<ElevationPolicy, String> {
+        if backend != AskpassBackend::Auto {
+            backend.resolve()?;
+        }
+        // Serializes with `set_mode` so neither write loses the other's field
+        let active_operations = self
+            .active_operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_stored_preferences(
+            &self.config_path,
+            &StoredPreferences {
+                askpass: backend,
+                ..self.stored_preferences()
+            },
+        )?;
         *self
-            .mode
+            .askpass
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = backend;
+        drop(active_operations);
+        Ok(self.policy())
+    }
+
+    /// Use `command` instead of the detected terminal; None or blank restores
+    /// detection
+    pub fn set_terminal_command(&self, command: Option<String>) -> Result<ElevationPolicy, String> {
+        let command = command
+            .map(|command| command.trim().to_string())
+            .filter(|command| !command.is_empty());
+        if let Some(ref command) = command {
+            let launch = terminal_launch_command(Some(command), Path::new("script"))?;
+            let program = Path::new(&launch[0]);
+            let found = if program.is_absolute() {
+                program.is_file()
+            } else {
+                askpass::find_in_path(&launch[0]).is_some()
+            };
+            if !found {
+                return Err(format!("Terminal program not found: {}", launch[0]));
+            }
+        }
+        let active_operations = self
+            .active_operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_stored_preferences(
+            &self.config_path,
+            &StoredPreferences {
+                terminal_command: command.clone(),
+                ..self.stored_preferences()
+            },
+        )?;
+        *self
+            .terminal_command
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = command;
+        drop(active_operations);
+        Ok(self.policy())
+    }
+
+    /// Operations already running keep the limits they started with
+    pub fn set_operation_policies(
+        &self,
+        operations: OperationPolicies,
+    ) -> Result<ElevationPolicy, String> {
+        operations.validate()?;
+        let active_operations = self
+            .active_operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_stored_preferences(
+            &self.config_path,
+            &StoredPreferences {
+                operations: operations.clone(),
+                ..self.stored_preferences()
+            },
+        )?;
+        *self
+            .operations
             .write()
-            .unwrap_or_else(|poisoned| poisoned.into_inner()) = mode;
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = operations;
+        drop(active_operations);
+        Ok(self.policy())
+    }
+
+    /// A running helper keeps the decision it was started with, so it is
+    /// stopped and restarts on the next command
+    pub fn set_helper_ownership_risk(&self, accept: bool) -> Result<ElevationPolicy, String> {
+        let active_operations = self
+            .active_operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !active_operations.is_empty() {
+            return Err(
+                "The helper cannot be changed while a privileged operation is active".to_string(),
+            );
+        }
+        write_stored_preferences(
+            &self.config_path,
+            &StoredPreferences {
+                accept_helper_ownership_risk: accept,
+                ..self.stored_preferences()
+            },
+        )?;
+        let previous = std::mem::replace(
+            &mut *self
+                .accept_helper_ownership_risk
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            accept,
+        );
         drop(active_operations);
+        if previous != accept {
+            self.helper.shutdown();
+        }
         Ok(self.policy())
     }
 
@@ -254,6 +504,13 @@ impl ElevationState {
         })
     }
 
+    pub fn is_operation_active(&self, operation: &str) -> bool {
+        self.active_operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(operation)
+    }
+
     fn finish_operation(&self, operation: &str) {
         let mut active_operations = self
             .active_operations
@@ -354,6 +611,20 @@ impl ElevationState {
         sessions.len()
     }
 
+    /// Remove Terminal handoff directories created before `before` whose
+    /// shell has exited. Directories of sessions owned by this process are
+    /// never touched.
+    pub fn reclaim_orphaned_handoffs(&self, before: SystemTime) -> HandoffReclaim {
+        let active: HashSet<PathBuf> = self
+            .sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .filter_map(|session| session.cancel_path.parent().map(Path::to_path_buf))
+            .collect();
+        reclaim_handoffs_in(&std::env::temp_dir(), before, &active)
+    }
+
     #[cfg(test)]
     fn active_session_count(&self) -> usize {
         self.sessions
@@ -363,49 +634,82 @@ impl ElevationState {
     }
 }
 
-fn read_stored_mode(path: &Path) -> Option<ElevationMode> {
+fn read_stored_preferences(path: &Path) -> Option<StoredPreferences> {
     let contents = fs::read_to_string(path).ok()?;
-    toml::from_str::<StoredPreferences>(&contents)
-        .ok()
-        .map(|preferences| preferences.mode)
+    toml::from_str::<StoredPreferences>(&contents).ok()
 }
 
-fn write_stored_mode(path: &Path, mode: ElevationMode) -> Result<(), String> {
-    let parent = path
-        .parent()
-        .ok_or_else(|| "Elevation preference path has no parent directory".to_string())?;
-    fs::create_dir_all(parent)
-        .map_err(|e| format!("Failed to create preference directory: {}", e))?;
+fn write_stored_preferences(path: &Path, preferences: &StoredPreferences) -> Result<(), String> {
+    write_private_toml(path, preferences, "elevation preference")
+}
 
-    let contents = toml::to_string(&StoredPreferences { mode })
-        .map_err(|e| format!("Failed to encode elevation preference: {}", e))?;
-    let mut temp = tempfile::NamedTempFile::new_in(parent)
-        .map_err(|e| format!("Failed to create temporary preference file: {}", e))?;
+/// Stands for the handoff script in a configured terminal command
+const TERMINAL_SCRIPT_PLACEHOLDER: &str = "{}";
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        temp.as_file()
-            .set_permissions(fs::Permissions::from_mode(0o600))
-            .map_err(|e| format!("Failed to secure preference file: {}", e))?;
-    }
-
-    temp.write_all(contents.as_bytes())
-        .map_err(|e| format!("Failed to write elevation preference: {}", e))?;
-    temp.as_file_mut()
-        .sync_all()
-        .map_err(|e| format!("Failed to flush elevation preference: {}", e))?;
-    temp.persist(path)
-        .map_err(|e| format!("Failed to save elevation preference: {}", e.error))?;
-    Ok(())
-}
+/// How long a terminal that exits is watched for a launch failure
+const TERMINAL_LAUNCH_CHECK: Duration = Duration::from_secs(2);
+
+/// Terminal emulators tried in order, with the arguments that make them run
+/// a program in a new window
+#[cfg(not(target_os = "macos"))]
+const TERMINAL_EMULATORS: &[(&str, &[&str])] = &[
+    ("gnome-terminal", &["--"]),
+    ("konsole", &["-e"]),
+    ("xterm", &["-e"]),
+];
 
 fn shell_quote(value: &str) -> String {
     format!("'{}'", value.replace('\'', "'\"'\"'"))
 }
 
+/// Shell the Terminal handoff script is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptShell {
+    /// Terminal.app's default login shell
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    Zsh,
+    /// Available on every Linux desktop
+    Bash,
+}
+
 #[cfg(target_os = "macos")]
+const TERMINAL_SHELL: ScriptShell = ScriptShell::Zsh;
+#[cfg(not(target_os = "macos"))]
+const TERMINAL_SHELL: ScriptShell = ScriptShell::Bash;
+
+impl ScriptShell {
+    fn interpreter(self) -> &'static str {
+        match self {
+            Self::Zsh => "/bin/zsh",
+            Self::Bash => "/bin/bash",
+        }
+    }
+
+    /// Exit status of the first command of the last pipeline
+    fn pipe_status(self) -> &'static str {
+        match self {
+            Self::Zsh => "${pipestatus[1]}",
+            Self::Bash => "${PIPESTATUS[0]}",
+        }
+    }
+
+    /// Run `command_line` on a pty that is not recorded anywhere. BSD and
+    /// util-linux `script` take the command differently; `-e` makes the
+    /// latter return the command's status.
+    fn unrecorded_pty(self, command_line: &str) -> String {
+        match self {
+            Self::Zsh => format!("/usr/bin/script -q /dev/null {}", command_line),
+            Self::Bash => format!(
+                "/usr/bin/script -q -e -c {} /dev/null",
+                shell_quote(command_line)
+            ),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn terminal_script_content(
+    shell: ScriptShell,
     command_line: &str,
     output_path: &Path,
     status_path: &Path,
@@ -415,7 +719,7 @@ fn terminal_script_content(
     prompts_for_secret: bool,
 ) -> String {
     let prelude = format!(
-        r#"#!/bin/zsh
+        r#"#!{interpreter}
 /usr/bin/printf '%s\n' "$$" > {pid_temp_path}
 /bin/mv -f {pid_temp_path} {pid_path}
 start_gate_owned=0
@@ -429,7 +733,7 @@ release_start_gate() {{
     start_gate_owned=0
   fi
 }}
-restore_terminal_echo() {{ /bin/stty echo 2>/dev/null }}
+restore_terminal_echo() {{ /bin/stty echo 2>/dev/null; }}
 finish_terminal() {{
   command_status="$1"
   completion="finished"
@@ -449,6 +753,7 @@ fi
 /bin/mv -f {launch_ack_temp_path} {launch_ack_path}
 release_start_gate
 "#,
+        interpreter = shell.interpreter(),
         pid_path = shell_quote(&pid_path.to_string_lossy()),
         pid_temp_path = shell_quote(&format!("{}.tmp", pid_path.to_string_lossy())),
         launch_ack_path = shell_quote(&launch_ack_path.to_string_lossy()),
@@ -461,30 +766,65 @@ release_start_gate
 
     let command = if prompts_for_secret {
         format!(
-            "/bin/stty -echo\n/usr/bin/script -q /dev/null {}\ncommand_status=$?\n",
-            command_line
+            "/bin/stty -echo\n{}\ncommand_status=$?\n",
+            shell.unrecorded_pty(command_line)
         )
     } else {
         format!(
-            "{} 2>&1 | /usr/bin/tee {}\ncommand_status=${{pipestatus[1]}}\n",
+            "{} 2>&1 | /usr/bin/tee {}\ncommand_status={}\n",
             command_line,
-            shell_quote(&output_path.to_string_lossy())
+            shell_quote(&output_path.to_string_lossy()),
+            shell.pipe_status()
         )
     };
 
     format!(
-        "{}{}finish_terminal \"$command_status\"\ntrap - HUP INT TERM\n/usr/bin/printf '\\nanylinuxfs finished with status %s. This Terminal tab can be closed.\\n' \"$command_status\"\nexit \"$command_status\"\n",
+        "{}{}finish_terminal \"$command_status\"\ntrap - HUP INT TERM\n/usr/bin/printf '\\nanylinuxfs finished with status %s. This terminal can be closed.\\n' \"$command_status\"\nexit \"$command_status\"\n",
         prelude, command
     )
 }
 
-#[cfg(target_os = "macos")]
+/// Run `cli_path args` as root in a Terminal tab, recording it in the audit
+/// log. `timeout` covers approval, any passphrase prompt and the command.
 pub fn execute_in_terminal(
     state: &ElevationState,
     cli_path: &Path,
     args: &[&str],
     silent: bool,
     interaction: TerminalInteraction,
+    timeout: Duration,
+) -> Result<String, TerminalExecutionError> {
+    let mut audit = AuditScope::begin(
+        ElevationMode::InteractiveTerminal,
+        AuditMethod::Terminal,
+        cli_path,
+        args,
+        None,
+        None,
+    );
+    let result = run_in_terminal(state, cli_path, args, silent, interaction, timeout);
+    match &result {
+        Ok(_) => audit.exit_status(Some(0)),
+        Err(TerminalExecutionError::CommandFailed { status, .. }) => {
+            audit.exit_status(Some(*status))
+        }
+        Err(TerminalExecutionError::InteractionRequired) => {
+            audit.outcome(AuditOutcome::AuthenticationRequired)
+        }
+        Err(TerminalExecutionError::Cancelled) => audit.outcome(AuditOutcome::Cancelled),
+        Err(TerminalExecutionError::TimedOut) => audit.outcome(AuditOutcome::TimedOut),
+        Err(TerminalExecutionError::Launch(_)) => audit.outcome(AuditOutcome::Failed),
+    }
+    result
+}
+
+fn run_in_terminal(
+    state: &ElevationState,
+    cli_path: &Path,
+    args: &[&str],
+    silent: bool,
+    interaction: TerminalInteraction,
+    timeout: Duration,
 ) -> Result<String, TerminalExecutionError> {
     use std::os::unix::fs::PermissionsExt;
 
@@ -522,6 +862,7 @@ pub fn execute_in_terminal(
     let command_line = command_parts.join(" ");
 
     let script_content = terminal_script_content(
+        TERMINAL_SHELL,
         &command_line,
         &output_path,
         &status_path,
@@ -555,25 +896,13 @@ pub fn execute_in_terminal(
         return Err(TerminalExecutionError::Cancelled);
     }
 
-    let launched = match Command::new("/usr/bin/open")
-        .args(["-a", "Terminal"])
-        .arg(&script_path)
-        .output()
-    {
-        Ok(output) => output,
-        Err(error) => {
-            state.unregister_session(session_id);
-            return Err(TerminalExecutionError::Launch(format!(
-                "Failed to open Terminal: {}",
-                error
-            )));
-        }
-    };
-    if !launched.status.success() {
+    let launched = terminal_launch_command(state.terminal_command().as_deref(), &script_path)
+        .and_then(|command| launch_terminal(&command));
+    if let Err(error) = launched {
         state.unregister_session(session_id);
         return Err(TerminalExecutionError::Launch(format!(
             "Failed to open Terminal: {}",
-            String::from_utf8_lossy(&launched.stderr).trim()
+            error
         )));
     }
 
@@ -584,7 +913,7 @@ pub fn execute_in_terminal(
         &status_path,
         &output_path,
         &cancel_path,
-        Duration::from_secs(INTERACTIVE_ELEVATION_TIMEOUT_SECS),
+        timeout,
     );
     if matches!(
         result,
@@ -596,17 +925,86 @@ pub fn execute_in_terminal(
     result
 }
 
+/// Program and arguments that open `script` in a new terminal window. A
+/// configured command gets the script in place of a `{}` argument, or
+/// appended when it has none.
+fn terminal_launch_command(configured: Option<&str>, script: &Path) -> Result<Vec<String>, String> {
+    let script = script.to_string_lossy().to_string();
+    if let Some(configured) = configured {
+        let mut command: Vec<String> = configured.split_whitespace().map(str::to_string).collect();
+        if command.is_empty() {
+            return Err("The configured terminal command is empty".to_string());
+        }
+        match command
+            .iter_mut()
+            .find(|arg| arg.as_str() == TERMINAL_SCRIPT_PLACEHOLDER)
+        {
+            Some(placeholder) => *placeholder = script,
+            None => command.push(script),
+        }
+        return Ok(command);
+    }
+    default_terminal_command(script)
+}
+
+#[cfg(target_os = "macos")]
+fn default_terminal_command(script: String) -> Result<Vec<String>, String> {
+    Ok(vec![
+        "/usr/bin/open".to_string(),
+        "-a".to_string(),
+        "Terminal".to_string(),
+        script,
+    ])
+}
+
 #[cfg(not(target_os = "macos"))]
-pub fn execute_in_terminal(
-    _state: &ElevationState,
-    _cli_path: &Path,
-    _args: &[&str],
-    _silent: bool,
-    _interaction: TerminalInteraction,
-) -> Result<String, TerminalExecutionError> {
-    Err(TerminalExecutionError::Launch(
-        "Interactive Terminal elevation is only available on macOS".to_string(),
-    ))
+fn default_terminal_command(script: String) -> Result<Vec<String>, String> {
+    TERMINAL_EMULATORS
+        .iter()
+        .find_map(|(program, args)| {
+            let path = askpass::find_in_path(program)?;
+            let mut command = vec![path.to_string_lossy().to_string()];
+            command.extend(args.iter().map(|arg| arg.to_string()));
+            command.push(script.clone());
+            Some(command)
+        })
+        .ok_or_else(|| {
+            "No terminal emulator found. Install gnome-terminal, konsole or xterm, or set a terminal command in Preferences.".to_string()
+        })
+}
+
+/// Start the terminal. `open` and gnome-terminal hand the script to another
+/// process and exit, xterm and konsole run until their window closes, so only
+/// an early failure is reported.
+fn launch_terminal(command: &[String]) -> Result<(), String> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| "No terminal command".to_string())?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", program, e))?;
+
+    let deadline = Instant::now() + TERMINAL_LAUNCH_CHECK;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(_)) => {
+                let output = child.wait_with_output().map_err(|e| e.to_string())?;
+                return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(error) => return Err(error.to_string()),
+        }
+    }
+    // Reap the terminal once its window is closed
+    std::thread::spawn(move || {
+        let _ = child.wait_with_output();
+    });
+    Ok(())
 }
 
 fn wait_for_terminal_result(
@@ -802,6 +1200,10 @@ fn recorded_terminal_shell_matches(session: &TerminalSession, pid: u32) -> bool
     let Some(work_dir) = session.pid_path.parent() else {
         return false;
     };
+    terminal_shell_matches(work_dir, pid)
+}
+
+fn terminal_shell_matches(work_dir: &Path, pid: u32) -> bool {
     let expected_script = work_dir.join("run-anylinuxfs.command");
     let output = Command::new("/bin/ps")
         .args(["-p", &pid.to_string(), "-o", "command="])
@@ -812,6 +1214,54 @@ fn recorded_terminal_shell_matches(session: &TerminalSession, pid: u32) -> bool
     String::from_utf8_lossy(&output.stdout).contains(expected_script.to_string_lossy().as_ref())
 }
 
+fn reclaim_handoffs_in(
+    temp_dir: &Path,
+    before: SystemTime,
+    active: &HashSet<PathBuf>,
+) -> HandoffReclaim {
+    let mut reclaim = HandoffReclaim::default();
+    let Ok(entries) = fs::read_dir(temp_dir) else {
+        return reclaim;
+    };
+    for entry in entries.flatten() {
+        let work_dir = entry.path();
+        let is_handoff = entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("anylinuxfs-terminal-");
+        let Ok(metadata) = fs::symlink_metadata(&work_dir) else {
+            continue;
+        };
+        if !is_handoff || !metadata.is_dir() || active.contains(&work_dir) {
+            continue;
+        }
+        if metadata.modified().map(|modified| modified >= before).unwrap_or(true) {
+            continue;
+        }
+
+        // A finished script leaves its status file; a live shell means the
+        // Terminal tab is still waiting for the user
+        let pid = read_session_pid(&work_dir.join("shell.pid")).filter(|pid| *pid > 1);
+        if let Some(pid) = pid.filter(|_| !work_dir.join("status.txt").exists()) {
+            if terminal_shell_matches(&work_dir, pid) {
+                let script =
+                    fs::read_to_string(work_dir.join("run-anylinuxfs.command")).unwrap_or_default();
+                reclaim.running.push(RunningHandoff { pid, script });
+                continue;
+            }
+        }
+        match fs::remove_dir_all(&work_dir) {
+            Ok(()) => reclaim.removed.push(work_dir),
+            Err(error) => log::warn!(
+                "Failed to remove Terminal handoff directory {}: {}",
+                work_dir.display(),
+                error
+            ),
+        }
+    }
+    reclaim
+}
+
 fn terminate_process_tree(session: &TerminalSession, root_pid: u32) {
     if root_pid <= 1 {
         return;
@@ -919,6 +1369,38 @@ pub fn set_elevation_mode(
     state.set_mode(mode)
 }
 
+#[tauri::command]
+pub fn set_askpass_backend(
+    state: tauri::State<'_, Arc<ElevationState>>,
+    backend: AskpassBackend,
+) -> Result<ElevationPolicy, String> {
+    state.set_askpass_backend(backend)
+}
+
+#[tauri::command]
+pub fn set_terminal_command(
+    state: tauri::State<'_, Arc<ElevationState>>,
+    command: Option<String>,
+) -> Result<ElevationPolicy, String> {
+    state.set_terminal_command(command)
+}
+
+#[tauri::command]
+pub fn set_helper_ownership_risk(
+    state: tauri::State<'_, Arc<ElevationState>>,
+    accept: bool,
+) -> Result<ElevationPolicy, String> {
+    state.set_helper_ownership_risk(accept)
+}
+
+#[tauri::command]
+pub fn set_operation_policies(
+    state: tauri::State<'_, Arc<ElevationState>>,
+    operations: OperationPolicies,
+) -> Result<ElevationPolicy, String> {
+    state.set_operation_policies(operations)
+}
+
 #[tauri::command]
 pub fn cancel_elevation_operation(
     state: tauri::State<'_, Arc<ElevationState>>,
@@ -957,10 +1439,35 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_os = "macos")]
     fn secret_prompt_uses_unrecorded_pty_and_echo_protection() {
-        let script = terminal_script_content(
-            "/usr/bin/sudo '/opt/homebrew/bin/anylinuxfs' 'mount' '/dev/disk7'",
+        for shell in [ScriptShell::Zsh, ScriptShell::Bash] {
+            let script = terminal_script_content(
+                shell,
+                "/usr/bin/sudo '/opt/homebrew/bin/anylinuxfs' 'mount' '/dev/disk7'",
+                Path::new("/tmp/output.txt"),
+                Path::new("/tmp/status.txt"),
+                Path::new("/tmp/cancel"),
+                Path::new("/tmp/shell.pid"),
+                Path::new("/tmp/launched"),
+                true,
+            );
+            assert!(script.starts_with(&format!("#!{}\n", shell.interpreter())));
+            assert!(script.contains("/bin/stty -echo"));
+            assert!(script.contains("shell.pid"));
+            assert!(script.contains("cancel"));
+            assert!(script.contains("/bin/mv -f '/tmp/launched.tmp' '/tmp/launched'"));
+            assert!(script.contains("/bin/mv -f '/tmp/status.txt.tmp' '/tmp/status.txt'"));
+            assert!(script.contains("finish_terminal 130; exit 130"));
+            let cancel_check = script.find("if [[ -e '/tmp/cancel' ]]").unwrap();
+            let command_start = script.find("command-starting").unwrap();
+            let secret_prompt = script.find("/bin/stty -echo").unwrap();
+            assert!(cancel_check < command_start && command_start < secret_prompt);
+            assert!(!script.contains("/usr/bin/tee"));
+        }
+
+        let zsh = terminal_script_content(
+            ScriptShell::Zsh,
+            "/usr/bin/sudo 'anylinuxfs' 'list'",
             Path::new("/tmp/output.txt"),
             Path::new("/tmp/status.txt"),
             Path::new("/tmp/cancel"),
@@ -968,68 +1475,146 @@ mod tests {
             Path::new("/tmp/launched"),
             true,
         );
-        assert!(script.contains("/bin/stty -echo"));
-        assert!(script.contains("/usr/bin/script -q /dev/null /usr/bin/sudo"));
-        assert!(script.contains("shell.pid"));
-        assert!(script.contains("cancel"));
-        assert!(script.contains("/bin/mv -f '/tmp/launched.tmp' '/tmp/launched'"));
-        assert!(script.contains("/bin/mv -f '/tmp/status.txt.tmp' '/tmp/status.txt'"));
-        assert!(script.contains("finish_terminal 130; exit 130"));
-        let cancel_check = script.find("if [[ -e '/tmp/cancel' ]]").unwrap();
-        let command_start = script.find("command-starting").unwrap();
-        let secret_prompt = script.find("/bin/stty -echo").unwrap();
-        assert!(cancel_check < command_start && command_start < secret_prompt);
-        assert!(!script.contains("/usr/bin/tee"));
-    }
-
-    #[test]
-    #[cfg(target_os = "macos")]
-    fn non_secret_command_captures_parseable_output() {
-        let script = terminal_script_content(
-            "/usr/bin/sudo '/opt/homebrew/bin/anylinuxfs' 'list'",
+        assert!(zsh.contains("/usr/bin/script -q /dev/null /usr/bin/sudo"));
+        let bash = terminal_script_content(
+            ScriptShell::Bash,
+            "/usr/bin/sudo 'anylinuxfs' 'list'",
             Path::new("/tmp/output.txt"),
             Path::new("/tmp/status.txt"),
             Path::new("/tmp/cancel"),
             Path::new("/tmp/shell.pid"),
             Path::new("/tmp/launched"),
-            false,
+            true,
         );
-        assert!(script.contains("/usr/bin/tee '/tmp/output.txt'"));
-        assert!(!script.contains("/bin/stty -echo"));
+        assert!(bash.contains(
+            "/usr/bin/script -q -e -c '/usr/bin/sudo '\"'\"'anylinuxfs'\"'\"' '\"'\"'list'\"'\"'' /dev/null"
+        ));
+    }
+
+    #[test]
+    fn non_secret_command_captures_parseable_output() {
+        for (shell, pipe_status) in [
+            (ScriptShell::Zsh, "command_status=${pipestatus[1]}"),
+            (ScriptShell::Bash, "command_status=${PIPESTATUS[0]}"),
+        ] {
+            let script = terminal_script_content(
+                shell,
+                "/usr/bin/sudo '/opt/homebrew/bin/anylinuxfs' 'list'",
+                Path::new("/tmp/output.txt"),
+                Path::new("/tmp/status.txt"),
+                Path::new("/tmp/cancel"),
+                Path::new("/tmp/shell.pid"),
+                Path::new("/tmp/launched"),
+                false,
+            );
+            assert!(script.contains("/usr/bin/tee '/tmp/output.txt'"));
+            assert!(script.contains(pipe_status));
+            assert!(!script.contains("/bin/stty -echo"));
+        }
+    }
+
+    /// Shells the handoff script can be run with on this machine
+    #[cfg(unix)]
+    fn installed_script_shells() -> Vec<ScriptShell> {
+        [ScriptShell::Zsh, ScriptShell::Bash]
+            .into_iter()
+            .filter(|shell| Path::new(shell.interpreter()).is_file())
+            .collect()
     }
 
     #[test]
-    #[cfg(target_os = "macos")]
+    #[cfg(unix)]
     fn preexisting_cancel_marker_prevents_command_execution() {
-        let directory = tempfile::tempdir().unwrap();
-        let command_marker = directory.path().join("command-ran");
-        let output_path = directory.path().join("output.txt");
-        let status_path = directory.path().join("status.txt");
-        let cancel_path = directory.path().join("cancel");
-        let pid_path = directory.path().join("shell.pid");
-        let launch_ack_path = directory.path().join("launched");
-        fs::write(&cancel_path, b"cancelled\n").unwrap();
+        for shell in installed_script_shells() {
+            let directory = tempfile::tempdir().unwrap();
+            let command_marker = directory.path().join("command-ran");
+            let output_path = directory.path().join("output.txt");
+            let status_path = directory.path().join("status.txt");
+            let cancel_path = directory.path().join("cancel");
+            let pid_path = directory.path().join("shell.pid");
+            let launch_ack_path = directory.path().join("launched");
+            fs::write(&cancel_path, b"cancelled\n").unwrap();
+
+            let script = terminal_script_content(
+                shell,
+                &format!(
+                    "/usr/bin/touch {}",
+                    shell_quote(&command_marker.to_string_lossy())
+                ),
+                &output_path,
+                &status_path,
+                &cancel_path,
+                &pid_path,
+                &launch_ack_path,
+                false,
+            );
+            let script_path = directory.path().join("cancelled.command");
+            fs::write(&script_path, script).unwrap();
 
-        let script = terminal_script_content(
-            &format!(
-                "/usr/bin/touch {}",
-                shell_quote(&command_marker.to_string_lossy())
-            ),
-            &output_path,
-            &status_path,
-            &cancel_path,
-            &pid_path,
-            &launch_ack_path,
-            false,
-        );
-        let script_path = directory.path().join("cancelled.command");
-        fs::write(&script_path, script).unwrap();
+            let result = Command::new(shell.interpreter())
+                .arg(&script_path)
+                .output()
+                .unwrap();
+            assert_eq!(result.status.code(), Some(130));
+            assert!(!command_marker.exists());
+            assert!(!launch_ack_path.exists());
+            assert_eq!(fs::read_to_string(status_path).unwrap(), "130\ncancelled\n");
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn finished_script_reports_status_and_output() {
+        for shell in installed_script_shells() {
+            let directory = tempfile::tempdir().unwrap();
+            let output_path = directory.path().join("output.txt");
+            let status_path = directory.path().join("status.txt");
+            let launch_ack_path = directory.path().join("launched");
+            let script = terminal_script_content(
+                shell,
+                "/bin/sh -c 'printf \"disk7 ext4\\n\"; exit 3'",
+                &output_path,
+                &status_path,
+                &directory.path().join("cancel"),
+                &directory.path().join("shell.pid"),
+                &launch_ack_path,
+                false,
+            );
+            let script_path = directory.path().join("run-anylinuxfs.command");
+            fs::write(&script_path, script).unwrap();
 
-        let result = Command::new("/bin/zsh").arg(&script_path).output().unwrap();
-        assert_eq!(result.status.code(), Some(130));
-        assert!(!command_marker.exists());
-        assert!(!launch_ack_path.exists());
-        assert_eq!(fs::read_to_string(status_path).unwrap(), "130\ncancelled\n");
+            let result = Command::new(shell.interpreter())
+                .arg(&script_path)
+                .stdin(Stdio::null())
+                .output()
+                .unwrap();
+            assert_eq!(result.status.code(), Some(3));
+            assert_eq!(fs::read_to_string(&status_path).unwrap(), "3\nfinished\n");
+            assert_eq!(fs::read_to_string(&output_path).unwrap(), "disk7 ext4\n");
+            assert_eq!(
+                fs::read_to_string(&launch_ack_path).unwrap(),
+                "command-starting\n"
+            );
+        }
+    }
+
+    #[test]
+    fn configured_terminal_command_receives_the_script() {
+        let script = Path::new("/tmp/anylinuxfs-terminal-x/run-anylinuxfs.command");
+        assert_eq!(
+            terminal_launch_command(Some("kitty --hold"), script).unwrap(),
+            vec!["kitty", "--hold", "/tmp/anylinuxfs-terminal-x/run-anylinuxfs.command"]
+        );
+        assert_eq!(
+            terminal_launch_command(Some("alacritty -e {} --flag"), script).unwrap(),
+            vec![
+                "alacritty",
+                "-e",
+                "/tmp/anylinuxfs-terminal-x/run-anylinuxfs.command",
+                "--flag"
+            ]
+        );
+        assert!(terminal_launch_command(Some("   "), script).is_err());
     }
 
     #[test]
@@ -1189,6 +1774,53 @@ mod tests {
         assert!(cleanup_marker.exists());
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn orphaned_handoffs_are_removed_unless_their_shell_runs() {
+        let directory = tempfile::tempdir().unwrap();
+        let finished = directory.path().join("anylinuxfs-terminal-finished");
+        fs::create_dir(&finished).unwrap();
+        fs::write(finished.join("status.txt"), b"0\nfinished\n").unwrap();
+        let active = directory.path().join("anylinuxfs-terminal-active");
+        fs::create_dir(&active).unwrap();
+        let unrelated = directory.path().join("other-terminal");
+        fs::create_dir(&unrelated).unwrap();
+
+        let running = directory.path().join("anylinuxfs-terminal-running");
+        fs::create_dir(&running).unwrap();
+        let script_path = running.join("run-anylinuxfs.command");
+        fs::write(
+            &script_path,
+            b"#!/bin/sh\n# /usr/bin/sudo '/usr/bin/anylinuxfs' 'mount' '/dev/disk7'\n/bin/sleep 30\n",
+        )
+        .unwrap();
+        let mut shell = Command::new("/bin/sh")
+            .arg(&script_path)
+            .stdin(Stdio::null())
+            .spawn()
+            .unwrap();
+        fs::write(running.join("shell.pid"), format!("{}\n", shell.id())).unwrap();
+
+        let reclaim = reclaim_handoffs_in(
+            directory.path(),
+            SystemTime::now() + Duration::from_secs(60),
+            &HashSet::from([active.clone()]),
+        );
+        let _ = shell.kill();
+        let _ = shell.wait();
+
+        assert_eq!(reclaim.removed, vec![finished.clone()]);
+        assert!(!finished.exists() && active.exists() && unrelated.exists());
+        assert_eq!(reclaim.running.len(), 1);
+        assert_eq!(reclaim.running[0].pid, shell.id());
+        assert!(reclaim.running[0].targets_device("/dev/disk7"));
+        assert!(!reclaim.running[0].targets_device("/dev/disk7s1"));
+
+        // Nothing created after the cutoff is touched
+        let reclaim = reclaim_handoffs_in(directory.path(), SystemTime::UNIX_EPOCH, &HashSet::new());
+        assert!(reclaim.removed.is_empty() && running.exists());
+    }
+
     #[test]
     fn preference_round_trip_uses_rust_owned_storage() {
         let directory = tempfile::tempdir().unwrap();
@@ -1198,9 +1830,28 @@ mod tests {
         assert_eq!(state.mode(), ElevationMode::Native);
         state.set_mode(ElevationMode::InteractiveTerminal).unwrap();
         assert_eq!(
-            ElevationState::load(path).mode(),
+            ElevationState::load(path.clone()).mode(),
             ElevationMode::InteractiveTerminal
         );
+
+        // Files written before the askpass choice existed still load
+        fs::write(&path, "mode = \"interactive_terminal\"\n").unwrap();
+        let state = ElevationState::load(path.clone());
+        assert_eq!(state.askpass_backend(), AskpassBackend::Auto);
+        state.set_askpass_backend(AskpassBackend::Auto).unwrap();
+        assert_eq!(ElevationState::load(path.clone()).mode(), ElevationMode::InteractiveTerminal);
+        assert_eq!(state.operation_policies(), OperationPolicies::default());
+
+        let mut operations = OperationPolicies::default();
+        operations.mount.timeout_secs = 900;
+        operations.mount.retries = 3;
+        state.set_operation_policies(operations.clone()).unwrap();
+        let reloaded = ElevationState::load(path);
+        assert_eq!(reloaded.operation_policies(), operations);
+        assert_eq!(reloaded.mode(), ElevationMode::InteractiveTerminal);
+
+        operations.list.timeout_secs = 0;
+        assert!(state.set_operation_policies(operations).is_err());
     }
 
     #[test]
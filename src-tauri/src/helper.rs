@@ -0,0 +1,689 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::askpass::{AskpassBackend, AskpassSession};
+use crate::audit::{AuditMethod, AuditOutcome, AuditScope};
+use crate::elevation::ElevationMode;
+use crate::ownership::check_ownership;
+use crate::sudoers::ALLOWED_SUBCOMMANDS;
+
+/// Bumped whenever a request or reply changes shape
+pub const HELPER_PROTOCOL_VERSION: u32 = 1;
+
+/// First argument that turns the GUI binary into the privileged helper
+const HELPER_ARG: &str = "--elevation-helper";
+
+/// Starting the helper may wait for a password prompt
+const START_TIMEOUT_SECS: u64 = 120;
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+/// Upper bound for one request or reply line (keyfiles and `list` output fit easily)
+const MAX_MESSAGE_BYTES: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HelperCommand {
+    Ping,
+    Run {
+        args: Vec<String>,
+        #[serde(default)]
+        passphrase: Option<String>,
+        #[serde(default)]
+        keyfile: Option<Vec<u8>>,
+    },
+    /// Honoured at any protocol version so an outdated helper can be replaced
+    Shutdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelperRequest {
+    pub version: u32,
+    #[serde(flatten)]
+    pub command: HelperCommand,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HelperReply {
+    Pong { pid: u32 },
+    Completed { status: i32, stdout: String, stderr: String },
+    Rejected { message: String },
+    Stopping,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelperResponse {
+    pub version: u32,
+    #[serde(flatten)]
+    pub reply: HelperReply,
+}
+
+/// Result of a CLI command run by the helper
+#[derive(Debug, Clone)]
+pub struct HelperOutput {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// ---------------------------------------------------------------------------
+// Helper process (runs as root)
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct HelperConfig {
+    pub cli_path: PathBuf,
+    /// Only this user (and root) may send requests
+    pub owner_uid: u32,
+    /// The helper exits once the GUI that started it is gone
+    pub parent_pid: Option<u32>,
+    /// Run the CLI even where someone other than root can replace it
+    pub accept_ownership_risk: bool,
+}
+
+impl HelperConfig {
+    fn parent_alive(&self) -> bool {
+        self.parent_pid.is_none_or(|pid| {
+            // Safe: signal 0 only checks that the process exists
+            let signalled = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0;
+            signalled || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+        })
+    }
+}
+
+/// Entry point when the GUI binary is started as the helper. Returns the
+/// exit code, or `None` for a normal app launch.
+pub fn run_helper_process() -> Option<i32> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) != Some(HELPER_ARG) {
+        return None;
+    }
+    let [_, socket, cli_path, owner_uid, parent_pid, accept_ownership_risk] = args.as_slice() else {
+        eprintln!(
+            "usage: {} SOCKET CLI OWNER_UID PARENT_PID ACCEPT_OWNERSHIP_RISK",
+            HELPER_ARG
+        );
+        return Some(2);
+    };
+    let (Ok(owner_uid), Ok(parent_pid)) = (owner_uid.parse(), parent_pid.parse()) else {
+        eprintln!("invalid owner uid or parent pid");
+        return Some(2);
+    };
+    let config = HelperConfig {
+        cli_path: PathBuf::from(cli_path),
+        owner_uid,
+        parent_pid: Some(parent_pid),
+        accept_ownership_risk: accept_ownership_risk == "1",
+    };
+    if let Err(error) = check_ownership(&config.cli_path, config.accept_ownership_risk) {
+        eprintln!("{}", error);
+        return Some(1);
+    }
+    let listener = match bind_socket(Path::new(socket), owner_uid) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("{}", error);
+            return Some(1);
+        }
+    };
+    let socket_path = listener
+        .local_addr()
+        .ok()
+        .and_then(|address| address.as_pathname().map(Path::to_path_buf));
+    serve(listener, Arc::new(config), Arc::new(AtomicBool::new(false)));
+    if let Some(path) = socket_path {
+        let _ = fs::remove_file(path);
+    }
+    Some(0)
+}
+
+/// Bind the helper socket inside the owner's cache directory. The directory
+/// must belong to the owner and not be writable by others; the socket is
+/// created world-connectable and every peer is checked by uid instead, so
+/// root never changes permissions through a path the user controls.
+pub fn bind_socket(socket_path: &Path, owner_uid: u32) -> Result<UnixListener, String> {
+    let parent = socket_path
+        .parent()
+        .ok_or_else(|| "Helper socket path has no parent directory".to_string())?;
+    let file_name = socket_path
+        .file_name()
+        .ok_or_else(|| "Helper socket path has no file name".to_string())?;
+    let parent = fs::canonicalize(parent)
+        .map_err(|e| format!("Helper socket directory is not accessible: {}", e))?;
+    let metadata = fs::metadata(&parent)
+        .map_err(|e| format!("Helper socket directory is not accessible: {}", e))?;
+    if (metadata.uid() != owner_uid && metadata.uid() != 0) || metadata.mode() & 0o022 != 0 {
+        return Err(format!(
+            "Helper socket directory {} must be owned by the user and not writable by others",
+            parent.display()
+        ));
+    }
+
+    let socket_path = parent.join(file_name);
+    if fs::symlink_metadata(&socket_path).is_ok() {
+        if UnixStream::connect(&socket_path).is_ok() {
+            return Err("A privileged helper is already running".to_string());
+        }
+        fs::remove_file(&socket_path)
+            .map_err(|e| format!("Failed to remove stale helper socket: {}", e))?;
+    }
+
+    // Safe: the helper is single-threaded until it starts serving
+    let previous = unsafe { libc::umask(0o111) };
+    let listener = UnixListener::bind(&socket_path);
+    unsafe { libc::umask(previous) };
+    listener.map_err(|e| format!("Failed to bind helper socket: {}", e))
+}
+
+/// Serve connections until shut down or the parent GUI exits. Each
+/// connection gets a thread so a slow mount does not hold up `list`.
+pub fn serve(listener: UnixListener, config: Arc<HelperConfig>, stop: Arc<AtomicBool>) {
+    if listener.set_nonblocking(true).is_err() {
+        return;
+    }
+    while !stop.load(Ordering::SeqCst) && config.parent_alive() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let config = config.clone();
+                let stop = stop.clone();
+                std::thread::spawn(move || handle_connection(stream, &config, &stop));
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut credentials: libc::ucred = unsafe { std::mem::zeroed() };
+    let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // Safe: SO_PEERCRED fills a ucred of exactly `length` bytes
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut length,
+        )
+    };
+    (result == 0).then_some(credentials.uid)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    // Safe: getpeereid only writes the two ids
+    let result = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+    (result == 0).then_some(uid)
+}
+
+fn handle_connection(stream: UnixStream, config: &HelperConfig, stop: &AtomicBool) {
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    if !peer_uid(&stream).is_some_and(|uid| uid == config.owner_uid || uid == 0) {
+        return;
+    }
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(reader.take(MAX_MESSAGE_BYTES));
+    let mut writer = stream;
+
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        // Each request gets a fresh size budget
+        reader.get_mut().set_limit(MAX_MESSAGE_BYTES);
+
+        let reply = match serde_json::from_str::<HelperRequest>(&line) {
+            Ok(request) => handle_request(request, config, stop),
+            Err(error) => HelperReply::Rejected {
+                message: format!("Malformed helper request: {}", error),
+            },
+        };
+        let response = HelperResponse {
+            version: HELPER_PROTOCOL_VERSION,
+            reply,
+        };
+        let Ok(mut encoded) = serde_json::to_vec(&response) else {
+            return;
+        };
+        encoded.push(b'\n');
+        if writer.write_all(&encoded).is_err() {
+            return;
+        }
+    }
+}
+
+fn handle_request(request: HelperRequest, config: &HelperConfig, stop: &AtomicBool) -> HelperReply {
+    if request.command == HelperCommand::Shutdown {
+        stop.store(true, Ordering::SeqCst);
+        return HelperReply::Stopping;
+    }
+    if request.version != HELPER_PROTOCOL_VERSION {
+        return HelperReply::Rejected {
+            message: format!(
+                "Unsupported helper protocol version {} (helper speaks {})",
+                request.version, HELPER_PROTOCOL_VERSION
+            ),
+        };
+    }
+    match request.command {
+        HelperCommand::Ping => HelperReply::Pong {
+            pid: std::process::id(),
+        },
+        HelperCommand::Run {
+            args,
+            passphrase,
+            keyfile,
+        } => {
+            if let Err(message) = validate_args(&args) {
+                return HelperReply::Rejected { message };
+            }
+            // The CLI may have been replaced since the helper started
+            if let Err(message) = check_ownership(&config.cli_path, config.accept_ownership_risk) {
+                return HelperReply::Rejected { message };
+            }
+            run_cli(&config.cli_path, &args, passphrase.as_deref(), keyfile)
+        }
+        HelperCommand::Shutdown => HelperReply::Stopping,
+    }
+}
+
+fn validate_args(args: &[String]) -> Result<(), String> {
+    match args.first() {
+        Some(subcommand) if ALLOWED_SUBCOMMANDS.contains(&subcommand.as_str()) => Ok(()),
+        Some(subcommand) => Err(format!(
+            "The privileged helper does not run '{}'",
+            subcommand
+        )),
+        None => Err("No anylinuxfs subcommand given".to_string()),
+    }
+}
+
+fn run_cli(
+    cli_path: &Path,
+    args: &[String],
+    passphrase: Option<&str>,
+    keyfile: Option<Vec<u8>>,
+) -> HelperReply {
+    let mut cmd = Command::new(cli_path);
+    cmd.args(args);
+    // Use piped stdin instead of null - libkrun's epoll fails with /dev/null
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    if let Some(passphrase) = passphrase {
+        cmd.env("ALFS_PASSPHRASE", passphrase);
+    }
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(error) => {
+            return HelperReply::Rejected {
+                message: format!("Failed to execute command: {}", error),
+            }
+        }
+    };
+    // Closing stdin marks the end of the key, as with a direct run
+    let stdin = child.stdin.take();
+    if let (Some(mut stdin), Some(bytes)) = (stdin, keyfile) {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&bytes);
+        });
+    }
+    match child.wait_with_output() {
+        Ok(output) => HelperReply::Completed {
+            status: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        },
+        Err(error) => HelperReply::Rejected {
+            message: format!("Failed to execute command: {}", error),
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Client (runs in the GUI)
+// ---------------------------------------------------------------------------
+
+/// Talks to the helper, starting it with sudo on first use
+pub struct HelperClient {
+    socket_path: PathBuf,
+    /// The `sudo` process the helper runs under, once started by this app
+    process: Mutex<Option<Child>>,
+}
+
+impl HelperClient {
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self {
+            socket_path,
+            process: Mutex::new(None),
+        }
+    }
+
+    fn exchange(&self, command: HelperCommand, timeout: Duration) -> Result<HelperReply, String> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .map_err(|e| format!("Privileged helper is not reachable: {}", e))?;
+        stream
+            .set_read_timeout(Some(timeout))
+            .map_err(|e| format!("Failed to configure helper connection: {}", e))?;
+
+        let mut encoded = serde_json::to_vec(&HelperRequest {
+            version: HELPER_PROTOCOL_VERSION,
+            command,
+        })
+        .map_err(|e| format!("Failed to encode helper request: {}", e))?;
+        encoded.push(b'\n');
+        stream
+            .write_all(&encoded)
+            .map_err(|e| format!("Failed to send helper request: {}", e))?;
+
+        let mut line = String::new();
+        BufReader::new(stream.take(MAX_MESSAGE_BYTES))
+            .read_line(&mut line)
+            .map_err(|e| format!("No reply from privileged helper: {}", e))?;
+        if line.is_empty() {
+            return Err("Privileged helper closed the connection".to_string());
+        }
+        let response = serde_json::from_str::<HelperResponse>(&line)
+            .map_err(|e| format!("Malformed reply from privileged helper: {}", e))?;
+        if response.version != HELPER_PROTOCOL_VERSION {
+            return Err(format!(
+                "Privileged helper speaks protocol version {}, expected {}",
+                response.version, HELPER_PROTOCOL_VERSION
+            ));
+        }
+        Ok(response.reply)
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(
+            self.exchange(HelperCommand::Ping, PING_TIMEOUT),
+            Ok(HelperReply::Pong { .. })
+        )
+    }
+
+    /// Run an allowed CLI subcommand as root, waiting at most `timeout` for
+    /// it. Starting the helper needs authentication, so `silent` callers get
+    /// `ALFS_SILENT_AUTH_EXPIRED` instead while it is not running.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run(
+        &self,
+        cli_path: &Path,
+        args: &[&str],
+        passphrase: Option<&str>,
+        keyfile: Option<&[u8]>,
+        askpass: AskpassBackend,
+        accept_ownership_risk: bool,
+        silent: bool,
+        timeout: Duration,
+    ) -> Result<HelperOutput, String> {
+        self.ensure_running(cli_path, askpass, accept_ownership_risk, silent)?;
+        let command = HelperCommand::Run {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            passphrase: passphrase.map(str::to_string),
+            keyfile: keyfile.map(<[u8]>::to_vec),
+        };
+        match self.exchange(command, timeout)? {
+            HelperReply::Completed {
+                status,
+                stdout,
+                stderr,
+            } => Ok(HelperOutput {
+                status,
+                stdout,
+                stderr,
+            }),
+            HelperReply::Rejected { message } => Err(message),
+            other => Err(format!("Unexpected reply from privileged helper: {:?}", other)),
+        }
+    }
+
+    fn ensure_running(
+        &self,
+        cli_path: &Path,
+        askpass: AskpassBackend,
+        accept_ownership_risk: bool,
+        silent: bool,
+    ) -> Result<(), String> {
+        let mut process = self
+            .process
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match self.exchange(HelperCommand::Ping, PING_TIMEOUT) {
+            Ok(HelperReply::Pong { .. }) => return Ok(()),
+            // An outdated helper answers, but not in our version; replace it
+            Ok(_) | Err(_) if fs::symlink_metadata(&self.socket_path).is_ok() => {
+                let _ = self.exchange(HelperCommand::Shutdown, PING_TIMEOUT);
+            }
+            _ => {}
+        }
+        if silent {
+            log::debug!("helper: not running, silent mode — skipping authentication");
+            return Err("ALFS_SILENT_AUTH_EXPIRED".to_string());
+        }
+        check_ownership(cli_path, accept_ownership_risk)?;
+        if let Some(mut previous) = process.take() {
+            let _ = previous.try_wait();
+        }
+        if let Some(parent) = self.socket_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create helper socket directory: {}", e))?;
+        }
+
+        let exe = std::env::current_exe()
+            .map_err(|e| format!("Cannot locate the anylinuxfs GUI binary: {}", e))?;
+        let socket_path = self.socket_path.to_string_lossy();
+        let cli_path_str = cli_path.to_string_lossy();
+        let mut audit = AuditScope::begin(
+            ElevationMode::Helper,
+            AuditMethod::SudoAskpass,
+            &exe,
+            &[HELPER_ARG, &socket_path, &cli_path_str],
+            None,
+            None,
+        );
+        let askpass = AskpassSession::start(askpass)?;
+        let mut cmd = Command::new("sudo");
+        cmd.arg("-A")
+            .arg("--")
+            .arg(&exe)
+            .arg(HELPER_ARG)
+            .arg(&self.socket_path)
+            .arg(cli_path)
+            // Safe: getuid cannot fail
+            .arg(unsafe { libc::getuid() }.to_string())
+            .arg(std::process::id().to_string())
+            .arg(if accept_ownership_risk { "1" } else { "0" });
+        askpass.apply(&mut cmd);
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::null());
+        cmd.stderr(Stdio::piped());
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to execute sudo: {}", e))?;
+
+        let start = Instant::now();
+        loop {
+            if let Ok(Some(status)) = child.try_wait() {
+                let mut stderr = String::new();
+                if let Some(ref mut err) = child.stderr {
+                    let _ = err.read_to_string(&mut stderr);
+                }
+                audit.exit_status(status.code());
+                return Err(if stderr.contains("incorrect password") {
+                    "Incorrect password".to_string()
+                } else if stderr.contains("no askpass program")
+                    || stderr.contains("no password was provided")
+                {
+                    audit.outcome(AuditOutcome::Cancelled);
+                    "Authentication cancelled".to_string()
+                } else {
+                    format!("Privileged helper failed to start: {}", stderr.trim())
+                });
+            }
+            if self.is_running() {
+                log::info!("helper: privileged helper started on {}", self.socket_path.display());
+                audit.outcome(AuditOutcome::Succeeded);
+                *process = Some(child);
+                return Ok(());
+            }
+            if start.elapsed() > Duration::from_secs(START_TIMEOUT_SECS) {
+                let _ = child.kill();
+                let _ = child.wait();
+                audit.outcome(AuditOutcome::TimedOut);
+                return Err("Privileged helper did not start in time".to_string());
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Stop the helper, e.g. when leaving Helper mode
+    pub fn shutdown(&self) {
+        let mut process = self
+            .process
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if self.exchange(HelperCommand::Shutdown, PING_TIMEOUT).is_ok() {
+            log::info!("helper: privileged helper stopped");
+        }
+        if let Some(mut child) = process.take() {
+            // Reap sudo once the helper has exited
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start_helper(
+        directory: &Path,
+        accept_ownership_risk: bool,
+    ) -> (HelperClient, std::thread::JoinHandle<()>) {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for anylinuxfs: echoes what it was given
+        let cli = directory.join("anylinuxfs");
+        fs::write(
+            &cli,
+            "#!/bin/sh\n\
+             if [ \"$1\" = mount ] && [ \"$2\" = --key-file ]; then printf 'key:'; cat; exit 0; fi\n\
+             echo \"args:$*\"\n\
+             echo \"pass:${ALFS_PASSPHRASE:-}\"\n\
+             if [ \"$1\" = unmount ]; then echo 'not mounted' >&2; exit 3; fi\n",
+        )
+        .unwrap();
+        fs::set_permissions(&cli, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let socket = directory.join("gui-helper.sock");
+        let owner_uid = unsafe { libc::getuid() };
+        let listener = bind_socket(&socket, owner_uid).unwrap();
+        let config = HelperConfig {
+            cli_path: cli,
+            owner_uid,
+            parent_pid: Some(std::process::id()),
+            accept_ownership_risk,
+        };
+        let server = std::thread::spawn(move || {
+            serve(listener, Arc::new(config), Arc::new(AtomicBool::new(false)))
+        });
+        (HelperClient::new(socket), server)
+    }
+
+    fn run(client: &HelperClient, args: &[&str], passphrase: Option<&str>, keyfile: Option<&[u8]>) -> Result<HelperOutput, String> {
+        // The helper is already running, so the CLI path and askpass go unused
+        client.run(
+            Path::new("/unused"),
+            args,
+            passphrase,
+            keyfile,
+            AskpassBackend::Auto,
+            true,
+            true,
+            Duration::from_secs(10),
+        )
+    }
+
+    #[test]
+    fn allowed_subcommands_run_with_their_secrets() {
+        let directory = tempfile::tempdir().unwrap();
+        let (client, _server) = start_helper(directory.path(), true);
+
+        let listed = run(&client, &["list", "--json"], None, None).unwrap();
+        assert_eq!(listed.status, 0);
+        assert_eq!(listed.stdout, "args:list --json\npass:\n");
+
+        let mounted = run(&client, &["mount", "/dev/disk4s1"], Some("open sesame"), None).unwrap();
+        assert_eq!(mounted.stdout, "args:mount /dev/disk4s1\npass:open sesame\n");
+
+        let keyed = run(&client, &["mount", "--key-file", "-", "/dev/disk4s1"], None, Some(b"\x00key")).unwrap();
+        assert_eq!(keyed.stdout, "key:\u{0}key");
+
+        let failed = run(&client, &["unmount", "/dev/disk4s1"], None, None).unwrap();
+        assert_eq!(failed.status, 3);
+        assert_eq!(failed.stderr, "not mounted\n");
+    }
+
+    #[test]
+    fn other_subcommands_and_protocol_versions_are_rejected() {
+        let directory = tempfile::tempdir().unwrap();
+        let (client, server) = start_helper(directory.path(), true);
+
+        let error = run(&client, &["shell"], None, None).unwrap_err();
+        assert_eq!(error, "The privileged helper does not run 'shell'");
+        assert!(run(&client, &[], None, None).is_err());
+
+        let mut stream = UnixStream::connect(directory.path().join("gui-helper.sock")).unwrap();
+        stream
+            .write_all(b"{\"version\":99,\"type\":\"run\",\"args\":[\"list\"]}\n")
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        let response = serde_json::from_str::<HelperResponse>(&line).unwrap();
+        assert_eq!(response.version, HELPER_PROTOCOL_VERSION);
+        assert!(matches!(response.reply, HelperReply::Rejected { .. }));
+
+        // A second helper must not take over the socket
+        let owner_uid = unsafe { libc::getuid() };
+        assert!(bind_socket(&directory.path().join("gui-helper.sock"), owner_uid).is_err());
+
+        client.shutdown();
+        server.join().unwrap();
+        assert!(!client.is_running());
+    }
+
+    #[test]
+    fn a_replaceable_cli_is_refused_without_an_accepted_risk() {
+        // The fake CLI lives in a temporary directory root does not own
+        let directory = tempfile::tempdir().unwrap();
+        let (client, server) = start_helper(directory.path(), false);
+
+        let error = run(&client, &["list"], None, None).unwrap_err();
+        assert!(error.starts_with("Refusing to run"), "{}", error);
+
+        client.shutdown();
+        server.join().unwrap();
+    }
+}
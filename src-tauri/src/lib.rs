@@ -1,12 +1,24 @@
+mod askpass;
+mod audit;
+mod backend;
 mod cache;
 mod cli;
 mod commands;
 mod elevation;
 mod error;
+mod helper;
+mod ownership;
 mod paths;
+mod polkit;
+mod secrets;
+mod storage;
+mod sudoers;
+mod timeouts;
 
 pub use error::{AppError, AppResult};
-pub use paths::{get_socket_path, get_log_path, get_log_paths, get_log_dir, COMMAND_TIMEOUT_SECS, MOUNT_TIMEOUT_SECS};
+pub use askpass::run_helper as run_askpass_helper;
+pub use helper::run_helper_process as run_elevation_helper;
+pub use paths::{get_socket_path, get_log_path, get_log_paths, get_log_dir};
 
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
@@ -14,9 +26,15 @@ use tauri::tray::TrayIconBuilder;
 use tauri::menu::{MenuBuilder, MenuItemBuilder, AboutMetadataBuilder, SubmenuBuilder};
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use secrets::SecretsState;
 use elevation::{
-    cancel_elevation_operation, get_elevation_policy, set_elevation_mode, ElevationState,
+    cancel_elevation_operation, get_elevation_policy, set_askpass_backend, set_elevation_mode,
+    set_helper_ownership_risk, set_operation_policies, set_terminal_command, ElevationState,
 };
+use askpass::answer_askpass;
+use audit::{query_audit_log, verify_audit_log};
+use polkit::{get_polkit_action, install_polkit_action, uninstall_polkit_action};
+use sudoers::{get_sudoers_rule, install_sudoers_rule, uninstall_sudoers_rule};
 
 struct UnmountMenuItem(tauri::menu::MenuItem<tauri::Wry>);
 
@@ -44,7 +62,7 @@ fn set_dock_visible(visible: bool) {
 }
 
 use commands::{
-    list_disks, mount_disk, unmount_disk, eject_disk, force_cleanup,
+    list_disks, mount_disk, remount_disk, unmount_disk, eject_disk, terminate_blocking_processes, force_cleanup,
     get_mount_status, check_cli,
     get_log_content, list_log_files, start_log_stream, start_disk_watcher, stop_watchers,
     get_config, update_config,
@@ -52,7 +70,17 @@ use commands::{
     list_images, install_image, uninstall_image,
     list_packages, add_packages, remove_packages,
     list_custom_actions, create_custom_action, update_custom_action, delete_custom_action,
-    WatcherState, PtyState,
+    list_favorites, add_favorite, remove_favorite,
+    get_filesystem_support, reload_filesystem_support, validate_mount_options,
+    list_vm_resource_overrides, forget_vm_resource_override,
+    list_saved_passphrases, forget_saved_passphrase, clear_saved_passphrases,
+    list_share_settings, set_share_settings, remove_share_settings, list_active_shares,
+    get_usage_alert_threshold, set_usage_alert_threshold,
+    start_health_monitor, recover_mount,
+    list_idle_policies, set_idle_policy, remove_idle_policy, set_mount_idle_timeout,
+    postpone_idle_unmount, start_idle_monitor, reconcile_previous_session,
+    WatcherState, PtyState, FavoritesState, FilesystemSupportState, VmResourcesState, SharesState,
+    UsageAlertState, IdlePolicyState, SessionJournal,
 };
 
 fn confirm_quit(app: &tauri::AppHandle) {
@@ -112,6 +140,27 @@ pub fn run() {
         .setup(|app| {
             let elevation_config = app.path().app_config_dir()?.join("preferences.toml");
             app.manage(Arc::new(ElevationState::load(elevation_config)));
+            askpass::install_broker(app.handle().clone());
+            let secrets = Arc::new(SecretsState::load(app.path().app_data_dir()?));
+            audit::init(app.path().app_data_dir()?.join("audit.jsonl"), secrets.clone());
+            app.manage(secrets);
+            let favorites_path = app.path().app_config_dir()?.join("favorites.toml");
+            app.manage(Arc::new(FavoritesState::load(favorites_path)));
+            let filesystems_path = app.path().app_config_dir()?.join("filesystems.toml");
+            app.manage(Arc::new(FilesystemSupportState::load(filesystems_path)));
+            let vm_resources_path = app.path().app_config_dir()?.join("vm_resources.toml");
+            app.manage(Arc::new(VmResourcesState::load(vm_resources_path)));
+            let shares_path = app.path().app_config_dir()?.join("shares.toml");
+            app.manage(Arc::new(SharesState::load(
+                shares_path,
+                commands::action::get_user_config_path(),
+            )));
+            let usage_alerts_path = app.path().app_config_dir()?.join("usage_alerts.toml");
+            app.manage(Arc::new(UsageAlertState::load(usage_alerts_path)));
+            let idle_policies_path = app.path().app_config_dir()?.join("idle_policies.toml");
+            app.manage(Arc::new(IdlePolicyState::load(idle_policies_path)));
+            let journal_path = app.path().app_data_dir()?.join("mount_journal.toml");
+            app.manage(Arc::new(SessionJournal::load(journal_path)));
 
             let show_item = MenuItemBuilder::with_id("show", "Show").build(app)?;
             let unmount_item = MenuItemBuilder::with_id("unmount", "Unmount")
@@ -239,8 +288,10 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             list_disks,
             mount_disk,
+            remount_disk,
             unmount_disk,
             eject_disk,
+            terminate_blocking_processes,
             force_cleanup,
             get_mount_status,
             check_cli,
@@ -265,10 +316,49 @@ pub fn run() {
             create_custom_action,
             update_custom_action,
             delete_custom_action,
+            list_favorites,
+            add_favorite,
+            remove_favorite,
+            get_filesystem_support,
+            reload_filesystem_support,
+            validate_mount_options,
+            list_vm_resource_overrides,
+            forget_vm_resource_override,
+            list_saved_passphrases,
+            forget_saved_passphrase,
+            clear_saved_passphrases,
+            list_share_settings,
+            set_share_settings,
+            remove_share_settings,
+            list_active_shares,
+            get_usage_alert_threshold,
+            set_usage_alert_threshold,
+            start_health_monitor,
+            recover_mount,
+            list_idle_policies,
+            set_idle_policy,
+            remove_idle_policy,
+            set_mount_idle_timeout,
+            postpone_idle_unmount,
+            start_idle_monitor,
+            reconcile_previous_session,
             set_tray_unmount_enabled,
             get_elevation_policy,
             set_elevation_mode,
+            set_askpass_backend,
+            set_operation_policies,
+            set_terminal_command,
+            set_helper_ownership_risk,
+            answer_askpass,
             cancel_elevation_operation,
+            get_sudoers_rule,
+            install_sudoers_rule,
+            uninstall_sudoers_rule,
+            get_polkit_action,
+            install_polkit_action,
+            uninstall_polkit_action,
+            query_audit_log,
+            verify_audit_log,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
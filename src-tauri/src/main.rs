@@ -2,5 +2,13 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // sudo starts this binary as its askpass helper for password dialogs
+    if let Some(code) = anylinuxfs_gui_lib::run_askpass_helper() {
+        std::process::exit(code);
+    }
+    // ...and as the privileged helper in Helper elevation mode
+    if let Some(code) = anylinuxfs_gui_lib::run_elevation_helper() {
+        std::process::exit(code);
+    }
     anylinuxfs_gui_lib::run()
 }
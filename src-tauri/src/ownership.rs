@@ -0,0 +1,83 @@
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Running a program as root that the user can replace is a root shell for
+/// anything running as that user. Lists the program or the first directory
+/// above it that someone other than root can modify; empty when it is safe.
+pub fn ownership_warnings(program: &Path) -> Vec<String> {
+    let resolved = fs::canonicalize(program).unwrap_or_else(|_| program.to_path_buf());
+    resolved
+        .ancestors()
+        .find(|path| {
+            fs::metadata(path)
+                .is_ok_and(|metadata| metadata.uid() != 0 || metadata.mode() & 0o022 != 0)
+        })
+        .map(|path| {
+            format!(
+                "{} can be modified without administrator rights; programs running as you could use it to gain root",
+                path.display()
+            )
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Refuse to run `program` as root while it has ownership warnings, unless
+/// the user accepted the risk
+pub fn check_ownership(program: &Path, accept_ownership_risk: bool) -> Result<(), String> {
+    let warnings = ownership_warnings(program);
+    if warnings.is_empty() || accept_ownership_risk {
+        return Ok(());
+    }
+    Err(format!(
+        "Refusing to run {} as root: {}",
+        program.display(),
+        warnings.join("; ")
+    ))
+}
+
+/// Gate for installing a root-owned `what` (a sudoers rule, a polkit
+/// action): the user must have reviewed exactly `generated`, and ownership
+/// warnings about the CLI it names block the install unless accepted
+pub fn check_install_allowed(
+    what: &str,
+    generated: &str,
+    reviewed: &str,
+    warnings: &[String],
+    accept_ownership_risk: bool,
+) -> Result<(), String> {
+    if generated != reviewed {
+        return Err(format!(
+            "The {} changed since it was shown; review it again before installing",
+            what
+        ));
+    }
+    if !warnings.is_empty() && !accept_ownership_risk {
+        return Err(format!("Refusing to install the {}: {}", what, warnings.join("; ")));
+    }
+    if !warnings.is_empty() {
+        log::warn!("Installing {} despite: {}", what, warnings.join("; "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_writable_programs_need_an_accepted_risk() {
+        // The temporary directory sits below a world-writable or user-owned
+        // directory, so a program there can be replaced without root
+        let directory = tempfile::tempdir().unwrap();
+        let program = directory.path().join("anylinuxfs");
+        fs::write(&program, "#!/bin/sh\n").unwrap();
+
+        assert_eq!(ownership_warnings(&program).len(), 1);
+        assert!(check_ownership(&program, false)
+            .unwrap_err()
+            .starts_with("Refusing to run"));
+        assert!(check_ownership(&program, true).is_ok());
+    }
+}
@@ -14,6 +14,15 @@ pub fn get_socket_path() -> PathBuf {
     PathBuf::from("/tmp/anylinuxfs.sock")
 }
 
+/// Socket of the privileged helper used by the `Helper` elevation mode, in
+/// the same cache directory as the CLI's socket
+pub fn get_helper_socket_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("anylinuxfs")
+        .join("gui-helper.sock")
+}
+
 /// Get the log directory path
 pub fn get_log_dir() -> PathBuf {
     if let Some(home) = dirs::home_dir() {
@@ -65,9 +74,3 @@ pub fn get_log_path() -> PathBuf {
     get_log_paths().into_iter().last()
         .unwrap_or_else(|| get_log_dir().join("anylinuxfs.log"))
 }
-
-/// Command timeout in seconds for blocking operations
-pub const COMMAND_TIMEOUT_SECS: u64 = 30;
-
-/// Mount timeout in seconds (longer for initial VM startup)
-pub const MOUNT_TIMEOUT_SECS: u64 = 60;
@@ -0,0 +1,309 @@
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use crate::cli;
+use crate::elevation::ElevationState;
+use crate::ownership::{self, ownership_warnings};
+
+/// Action pkexec uses for the CLI once the definition below is installed
+pub const ACTION_ID: &str = "com.anylinuxfs.gui.run-cli";
+
+/// polkit's own action for any other program run through pkexec
+const GENERIC_EXEC_ACTION_ID: &str = "org.freedesktop.policykit.exec";
+
+pub const ACTION_PATH: &str = "/usr/share/polkit-1/actions/com.anylinuxfs.gui.policy";
+
+/// pkexec's exit status when the authentication dialog was dismissed
+pub const PKEXEC_DISMISSED: i32 = 126;
+
+/// pkexec's exit status when polkit refused authorization or no
+/// authentication agent could ask
+pub const PKEXEC_NOT_AUTHORIZED: i32 = 127;
+
+const PKEXEC_PATHS: &[&str] = &["/usr/bin/pkexec", "/bin/pkexec"];
+
+const PKCHECK_PATHS: &[&str] = &["/usr/bin/pkcheck", "/bin/pkcheck"];
+
+/// Copy the action into place as root:0 0644. polkit only reads `.policy`
+/// files, so the staged copy is inert. The staged file is user-writable
+/// until it is copied, so the root-owned copy must match the reviewed action
+/// (`$3`, passed as an argument) byte for byte before it is moved into place.
+const INSTALL_SCRIPT: &str = r#"set -e
+install -m 0644 -o 0 -g 0 "$1" "$2.new"
+if ! printf '%s' "$3" | cmp -s - "$2.new"; then rm -f "$2.new"; echo "staged action was modified" >&2; exit 1; fi
+mv -f "$2.new" "$2"
+"#;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolkitActionStatus {
+    pub path: String,
+    /// Exact action definition, for review before installing
+    pub action: String,
+    /// The definition at `path` matches `action`
+    pub installed: bool,
+    pub pkexec_available: bool,
+    pub warnings: Vec<String>,
+}
+
+pub fn find_pkexec() -> Option<PathBuf> {
+    PKEXEC_PATHS.iter().map(PathBuf::from).find(|path| path.is_file())
+}
+
+/// The CLI path appears verbatim in the definition, so it must be absolute
+/// and free of characters XML would need escaped.
+fn validate_cli_path(cli_path: &Path) -> Result<&str, String> {
+    let path = cli_path
+        .to_str()
+        .ok_or_else(|| "anylinuxfs path is not valid UTF-8".to_string())?;
+    if !cli_path.is_absolute() {
+        return Err(format!("anylinuxfs path '{}' is not absolute", path));
+    }
+    let valid = path
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '_' | '-' | '+' | '@'));
+    if !valid {
+        return Err(format!(
+            "anylinuxfs path '{}' contains characters that cannot be used in a polkit action",
+            path
+        ));
+    }
+    Ok(path)
+}
+
+/// Build the action definition that lets pkexec run `cli_path`, and only
+/// that program, after one administrator authentication per session.
+pub fn generate_action(cli_path: &Path) -> Result<String, String> {
+    let path = validate_cli_path(cli_path)?;
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE policyconfig PUBLIC
+ "-//freedesktop//DTD PolicyKit Policy Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/PolicyKit/1/policyconfig.dtd">
+<!-- Installed by anylinuxfs GUI. Remove it from Preferences or delete {action_path} as root. -->
+<policyconfig>
+  <vendor>anylinuxfs</vendor>
+  <action id="{action_id}">
+    <description>Mount Linux filesystems with anylinuxfs</description>
+    <message>Authentication is required to mount Linux filesystems</message>
+    <defaults>
+      <allow_any>auth_admin</allow_any>
+      <allow_inactive>auth_admin</allow_inactive>
+      <allow_active>auth_admin_keep</allow_active>
+    </defaults>
+    <annotate key="org.freedesktop.policykit.exec.path">{path}</annotate>
+  </action>
+</policyconfig>
+"#,
+        action_path = ACTION_PATH,
+        action_id = ACTION_ID,
+        path = path,
+    ))
+}
+
+fn action_installed(cli_path: &Path) -> bool {
+    match (fs::read_to_string(ACTION_PATH), generate_action(cli_path)) {
+        (Ok(installed), Ok(action)) => installed == action,
+        _ => false,
+    }
+}
+
+/// Whether pkexec would run `program` right now without asking, e.g. within
+/// the `auth_admin_keep` window. Used for background refreshes, which must
+/// never show the authentication dialog.
+pub fn authorized_without_interaction(program: &Path) -> bool {
+    let Some(pkcheck) = PKCHECK_PATHS.iter().map(Path::new).find(|path| path.is_file()) else {
+        return false;
+    };
+    // pkexec checks the process that started it, i.e. this one
+    let action_id = if action_installed(program) {
+        ACTION_ID
+    } else {
+        GENERIC_EXEC_ACTION_ID
+    };
+    Command::new(pkcheck)
+        .args(["--action-id", action_id, "--process"])
+        .arg(std::process::id().to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn action_status() -> Result<PolkitActionStatus, String> {
+    let cli_path = cli::get_path()
+        .ok_or_else(|| "anylinuxfs CLI not found in PATH or standard locations".to_string())?;
+    Ok(PolkitActionStatus {
+        path: ACTION_PATH.to_string(),
+        action: generate_action(cli_path)?,
+        installed: action_installed(cli_path),
+        pkexec_available: find_pkexec().is_some(),
+        warnings: ownership_warnings(cli_path),
+    })
+}
+
+fn check_install_allowed(
+    status: &PolkitActionStatus,
+    reviewed_action: &str,
+    accept_ownership_risk: bool,
+) -> Result<(), String> {
+    ownership::check_install_allowed(
+        "polkit action",
+        &status.action,
+        reviewed_action,
+        &status.warnings,
+        accept_ownership_risk,
+    )
+}
+
+fn install_action(
+    elevation_state: &ElevationState,
+    reviewed_action: &str,
+    accept_ownership_risk: bool,
+) -> Result<PolkitActionStatus, String> {
+    let status = action_status()?;
+    check_install_allowed(&status, reviewed_action, accept_ownership_risk)?;
+
+    let mut staged = tempfile::Builder::new()
+        .prefix("anylinuxfs-polkit-")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temporary polkit action: {}", e))?;
+    staged
+        .write_all(status.action.as_bytes())
+        .map_err(|e| format!("Failed to write temporary polkit action: {}", e))?;
+    staged
+        .as_file_mut()
+        .sync_all()
+        .map_err(|e| format!("Failed to flush temporary polkit action: {}", e))?;
+
+    let staged_path = staged.path().to_string_lossy().to_string();
+    cli::execute_privileged(
+        Path::new("/bin/sh"),
+        &["-c", INSTALL_SCRIPT, "sh", &staged_path, ACTION_PATH, &status.action],
+        elevation_state,
+    )
+    .map_err(|e| format!("Failed to install polkit action: {}", e))?;
+    log::info!("Installed polkit action at {}", ACTION_PATH);
+
+    action_status()
+}
+
+fn uninstall_action(elevation_state: &ElevationState) -> Result<PolkitActionStatus, String> {
+    cli::execute_privileged(Path::new("/bin/rm"), &["-f", ACTION_PATH], elevation_state)
+        .map_err(|e| format!("Failed to remove polkit action: {}", e))?;
+    log::info!("Removed polkit action at {}", ACTION_PATH);
+    action_status()
+}
+
+/// Show the action that would be installed and whether it is in place
+#[tauri::command]
+pub async fn get_polkit_action() -> Result<PolkitActionStatus, String> {
+    tokio::task::spawn_blocking(action_status)
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// Install `reviewed_action`, the definition the user was shown. Ownership
+/// warnings block the install unless `accept_ownership_risk` is set.
+#[tauri::command]
+pub async fn install_polkit_action(
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+    reviewed_action: String,
+    accept_ownership_risk: bool,
+) -> Result<PolkitActionStatus, String> {
+    let elevation_state = elevation_state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        install_action(&elevation_state, &reviewed_action, accept_ownership_risk)
+    })
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[tauri::command]
+pub async fn uninstall_polkit_action(
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+) -> Result<PolkitActionStatus, String> {
+    let elevation_state = elevation_state.inner().clone();
+    tokio::task::spawn_blocking(move || uninstall_action(&elevation_state))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_is_limited_to_the_cli_path() {
+        let action = generate_action(Path::new("/usr/local/bin/anylinuxfs")).unwrap();
+
+        assert!(action.contains(&format!("<action id=\"{}\">", ACTION_ID)));
+        assert!(action.contains(
+            "<annotate key=\"org.freedesktop.policykit.exec.path\">/usr/local/bin/anylinuxfs</annotate>"
+        ));
+        assert_eq!(action.matches("<annotate ").count(), 1);
+        // Never grants access without authenticating
+        assert!(!action.contains("<allow_any>yes") && !action.contains("<allow_active>yes"));
+        assert!(!action.contains("allow_gui"));
+    }
+
+    #[test]
+    fn action_rejects_paths_xml_or_pkexec_would_misread() {
+        assert!(generate_action(Path::new("bin/anylinuxfs")).is_err());
+        assert!(generate_action(Path::new("/opt/a<b>/anylinuxfs")).is_err());
+        assert!(generate_action(Path::new("/opt/a&b/anylinuxfs")).is_err());
+        assert!(generate_action(Path::new("/opt/any linux/anylinuxfs")).is_err());
+        assert!(generate_action(Path::new("/opt/homebrew/bin/anylinuxfs")).is_ok());
+    }
+
+    #[test]
+    fn install_needs_the_reviewed_action_and_a_safe_binary() {
+        let action = generate_action(Path::new("/usr/local/bin/anylinuxfs")).unwrap();
+        let mut status = PolkitActionStatus {
+            path: ACTION_PATH.to_string(),
+            action: action.clone(),
+            installed: false,
+            pkexec_available: true,
+            warnings: Vec::new(),
+        };
+        assert!(check_install_allowed(&status, &action, false).is_ok());
+        let widened = action.replace("auth_admin_keep", "yes");
+        assert!(check_install_allowed(&status, &widened, false).is_err());
+
+        status.warnings.push("/usr/local/bin can be modified without administrator rights".to_string());
+        assert!(check_install_allowed(&status, &action, false).is_err());
+        assert!(check_install_allowed(&status, &action, true).is_ok());
+    }
+
+    #[test]
+    fn install_script_refuses_a_modified_staged_action() {
+        // The script without the root-only ownership change
+        let script = INSTALL_SCRIPT.replace(" -o 0 -g 0", "");
+        let directory = tempfile::tempdir().unwrap();
+        let staged = directory.path().join("staged");
+        let target = directory.path().join("com.anylinuxfs.gui.policy");
+        let run = |staged_contents: &str, reviewed: &str| {
+            fs::write(&staged, staged_contents).unwrap();
+            Command::new("/bin/sh")
+                .arg("-c")
+                .arg(&script)
+                .arg("sh")
+                .arg(&staged)
+                .arg(&target)
+                .arg(reviewed)
+                .status()
+                .unwrap()
+                .success()
+        };
+
+        let action = generate_action(Path::new("/usr/local/bin/anylinuxfs")).unwrap();
+        assert!(!run(&action.replace("auth_admin_keep", "yes"), &action));
+        assert!(!target.exists() && !directory.path().join("com.anylinuxfs.gui.policy.new").exists());
+        assert!(run(&action, &action));
+        assert_eq!(fs::read_to_string(&target).unwrap(), action);
+    }
+}
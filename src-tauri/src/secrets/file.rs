@@ -0,0 +1,205 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use super::SecretStore;
+use crate::storage::{write_private, write_private_toml};
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SealedSecret {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SealedFile {
+    #[serde(default)]
+    secrets: BTreeMap<String, SealedSecret>,
+}
+
+/// XChaCha20-Poly1305 sealed passphrases in a TOML file. The key lives in a
+/// separate owner-only file created on first use; the UUID is bound as
+/// associated data so entries cannot be swapped between partitions.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+    // Serialises read-modify-write of the sealed file
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: PathBuf, key_path: PathBuf) -> Self {
+        Self {
+            path,
+            key_path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn cipher(&self, create: bool) -> Result<Option<XChaCha20Poly1305>, String> {
+        match fs::read(&self.key_path) {
+            Ok(bytes) if bytes.len() == KEY_LEN => {
+                Ok(Some(XChaCha20Poly1305::new(Key::from_slice(&bytes))))
+            }
+            Ok(_) => Err(format!("{} is not a valid key file", self.key_path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if !create {
+                    return Ok(None);
+                }
+                let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+                write_private(&self.key_path, key.as_slice(), "secret store key")?;
+                Ok(Some(XChaCha20Poly1305::new(&key)))
+            }
+            Err(e) => Err(format!("Failed to read secret store key: {}", e)),
+        }
+    }
+
+    fn read(&self) -> Result<SealedFile, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| format!("Failed to parse {}: {}", self.path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SealedFile::default()),
+            Err(e) => Err(format!("Failed to read secret store: {}", e)),
+        }
+    }
+
+    fn write(&self, file: &SealedFile) -> Result<(), String> {
+        write_private_toml(&self.path, file, "secret store")
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn name(&self) -> &'static str {
+        "Encrypted file"
+    }
+
+    fn get(&self, uuid: &str) -> Result<Option<String>, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(sealed) = self.read()?.secrets.remove(uuid) else {
+            return Ok(None);
+        };
+        let Some(cipher) = self.cipher(false)? else {
+            return Err("Secret store key is missing".to_string());
+        };
+        let nonce = decode_hex(&sealed.nonce)
+            .filter(|nonce| nonce.len() == NONCE_LEN)
+            .ok_or_else(|| format!("Stored secret for {} is corrupt", uuid))?;
+        let ciphertext = decode_hex(&sealed.ciphertext)
+            .ok_or_else(|| format!("Stored secret for {} is corrupt", uuid))?;
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload { msg: &ciphertext, aad: uuid.as_bytes() },
+            )
+            .map_err(|_| format!("Stored secret for {} could not be decrypted", uuid))?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| format!("Stored secret for {} is corrupt", uuid))
+    }
+
+    fn set(&self, uuid: &str, secret: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let cipher = self.cipher(true)?.expect("key is created on demand");
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: secret.as_bytes(), aad: uuid.as_bytes() })
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+        let mut file = self.read()?;
+        file.secrets.insert(
+            uuid.to_string(),
+            SealedSecret {
+                nonce: encode_hex(&nonce),
+                ciphertext: encode_hex(&ciphertext),
+            },
+        );
+        self.write(&file)
+    }
+
+    fn delete(&self, uuid: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut file = self.read()?;
+        if file.secrets.remove(uuid).is_none() {
+            return Ok(false);
+        }
+        self.write(&file)?;
+        Ok(true)
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(self.read()?.secrets.into_keys().collect())
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretsState;
+
+    const UUID: &str = "6F1C2A4E-8D2B-4C7E-9A3F-1B2C3D4E5F60";
+
+    #[test]
+    fn secrets_round_trip_without_plaintext_on_disk() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("passphrases.toml");
+        let key_path = directory.path().join("passphrases.key");
+        let state = SecretsState::new(Box::new(EncryptedFileStore::new(path.clone(), key_path.clone())));
+
+        state.set(&UUID.to_lowercase(), "correct horse battery staple").unwrap();
+        assert_eq!(state.list().unwrap(), vec![UUID.to_string()]);
+        assert!(!fs::read_to_string(&path).unwrap().contains("horse"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for file in [&path, &key_path] {
+                assert_eq!(fs::metadata(file).unwrap().permissions().mode() & 0o777, 0o600);
+            }
+        }
+
+        let reopened = SecretsState::new(Box::new(EncryptedFileStore::new(path, key_path)));
+        assert_eq!(
+            reopened.get(UUID).unwrap().as_deref(),
+            Some("correct horse battery staple")
+        );
+        assert!(reopened.delete(UUID).unwrap());
+        assert_eq!(reopened.get(UUID).unwrap(), None);
+        assert!(!reopened.delete(UUID).unwrap());
+    }
+
+    #[test]
+    fn sealed_entries_are_bound_to_their_uuid() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("passphrases.toml");
+        let store = EncryptedFileStore::new(path.clone(), directory.path().join("passphrases.key"));
+        store.set(UUID, "secret").unwrap();
+
+        // Copy the sealed entry to another UUID; decryption must fail
+        let mut file = store.read().unwrap();
+        let sealed = file.secrets[UUID].clone();
+        file.secrets.insert("0A1B-2C3D".to_string(), sealed);
+        store.write(&file).unwrap();
+        assert!(store.get("0A1B-2C3D").is_err());
+        assert_eq!(store.get(UUID).unwrap().as_deref(), Some("secret"));
+    }
+}
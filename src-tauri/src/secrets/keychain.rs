@@ -0,0 +1,69 @@
+use security_framework::passwords::{
+    delete_generic_password, get_generic_password, set_generic_password,
+};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use super::{read_index, write_index, SecretStore, SERVICE};
+
+/// errSecItemNotFound
+const ITEM_NOT_FOUND: i32 = -25300;
+
+/// Generic passwords in the user's login keychain, one item per UUID
+pub struct KeychainStore {
+    index_path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl KeychainStore {
+    pub fn new(index_path: PathBuf) -> Self {
+        Self {
+            index_path,
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl SecretStore for KeychainStore {
+    fn name(&self) -> &'static str {
+        "Keychain"
+    }
+
+    fn get(&self, uuid: &str) -> Result<Option<String>, String> {
+        match get_generic_password(SERVICE, uuid) {
+            Ok(bytes) => String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|_| format!("Keychain item for {} is not valid text", uuid)),
+            Err(e) if e.code() == ITEM_NOT_FOUND => Ok(None),
+            Err(e) => Err(format!("Failed to read from Keychain: {}", e)),
+        }
+    }
+
+    fn set(&self, uuid: &str, secret: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        set_generic_password(SERVICE, uuid, secret.as_bytes())
+            .map_err(|e| format!("Failed to save to Keychain: {}", e))?;
+        let mut uuids = read_index(&self.index_path);
+        if uuids.insert(uuid.to_string()) {
+            write_index(&self.index_path, &uuids)?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, uuid: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let existed = match delete_generic_password(SERVICE, uuid) {
+            Ok(()) => true,
+            Err(e) if e.code() == ITEM_NOT_FOUND => false,
+            Err(e) => return Err(format!("Failed to remove from Keychain: {}", e)),
+        };
+        let mut uuids = read_index(&self.index_path);
+        if uuids.remove(uuid) {
+            write_index(&self.index_path, &uuids)?;
+        }
+        Ok(existed)
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        Ok(read_index(&self.index_path).into_iter().collect())
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::storage::write_private_toml;
+
+// Used on macOS only by tests
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+mod file;
+#[cfg(target_os = "macos")]
+mod keychain;
+#[cfg(target_os = "linux")]
+mod secret_service;
+
+/// Keychain service / secret-service attribute the passphrases are filed under
+const SERVICE: &str = "anylinuxfs-gui";
+
+/// Storage for disk unlock passphrases keyed by uppercased partition UUID.
+/// Implementations must never log secrets or write them anywhere unencrypted.
+pub trait SecretStore: Send + Sync {
+    /// Short backend name shown in the GUI
+    fn name(&self) -> &'static str;
+
+    fn get(&self, uuid: &str) -> Result<Option<String>, String>;
+
+    fn set(&self, uuid: &str, secret: &str) -> Result<(), String>;
+
+    /// Returns whether a secret was stored for `uuid`
+    fn delete(&self, uuid: &str) -> Result<bool, String>;
+
+    /// UUIDs with a stored secret
+    fn list(&self) -> Result<Vec<String>, String>;
+}
+
+/// The OS keyring where one is available, otherwise the encrypted file in
+/// `data_dir`. `secret-tool` (libsecret) is the Linux keyring.
+pub fn open(data_dir: &Path) -> Box<dyn SecretStore> {
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(keychain::KeychainStore::new(data_dir.join(INDEX_FILE)))
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        #[cfg(target_os = "linux")]
+        if secret_service::available() {
+            return Box::new(secret_service::SecretServiceStore::new(data_dir.join(INDEX_FILE)));
+        }
+        Box::new(file::EncryptedFileStore::new(
+            data_dir.join("passphrases.toml"),
+            data_dir.join("passphrases.key"),
+        ))
+    }
+}
+
+/// Store key of the audit log anchor (see `audit::AuditAnchor`). Not a
+/// partition UUID, so it never collides with a saved passphrase.
+const AUDIT_ANCHOR_KEY: &str = "AUDIT-LOG-HEAD";
+
+/// Keyrings cannot cheaply enumerate our items, so the OS backends keep the
+/// list of UUIDs (never the secrets) in this file
+const INDEX_FILE: &str = "saved_passphrases.toml";
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct Index {
+    #[serde(default)]
+    uuids: BTreeSet<String>,
+}
+
+fn read_index(path: &Path) -> BTreeSet<String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| toml::from_str::<Index>(&contents).ok())
+        .map(|index| index.uuids)
+        .unwrap_or_default()
+}
+
+fn write_index(path: &Path, uuids: &BTreeSet<String>) -> Result<(), String> {
+    write_private_toml(path, &Index { uuids: uuids.clone() }, "passphrase index")
+}
+
+/// Managed wrapper that normalises UUIDs before they reach the backend
+pub struct SecretsState {
+    store: Box<dyn SecretStore>,
+}
+
+impl SecretsState {
+    pub fn new(store: Box<dyn SecretStore>) -> Self {
+        Self { store }
+    }
+
+    pub fn load(data_dir: PathBuf) -> Self {
+        Self::new(open(&data_dir))
+    }
+
+    pub fn backend(&self) -> &'static str {
+        self.store.name()
+    }
+
+    pub fn get(&self, uuid: &str) -> Result<Option<String>, String> {
+        self.store.get(&uuid.to_uppercase())
+    }
+
+    pub fn set(&self, uuid: &str, secret: &str) -> Result<(), String> {
+        if secret.is_empty() {
+            return Err("Refusing to save an empty passphrase".to_string());
+        }
+        self.store.set(&uuid.to_uppercase(), secret)
+    }
+
+    pub fn delete(&self, uuid: &str) -> Result<bool, String> {
+        self.store.delete(&uuid.to_uppercase())
+    }
+
+    /// UUIDs with a saved passphrase
+    pub fn list(&self) -> Result<Vec<String>, String> {
+        Ok(self
+            .store
+            .list()?
+            .into_iter()
+            .filter(|uuid| uuid != AUDIT_ANCHOR_KEY)
+            .collect())
+    }
+
+    pub fn clear(&self) -> Result<usize, String> {
+        let uuids = self.list()?;
+        for uuid in &uuids {
+            self.store.delete(uuid)?;
+        }
+        Ok(uuids.len())
+    }
+
+    pub fn get_audit_anchor(&self) -> Result<Option<String>, String> {
+        self.store.get(AUDIT_ANCHOR_KEY)
+    }
+
+    pub fn set_audit_anchor(&self, head: &str) -> Result<(), String> {
+        self.store.set(AUDIT_ANCHOR_KEY, head)
+    }
+}
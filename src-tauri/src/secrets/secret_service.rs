@@ -0,0 +1,113 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use super::{read_index, write_index, SecretStore, SERVICE};
+
+/// Whether libsecret's `secret-tool` is installed
+pub fn available() -> bool {
+    Command::new("secret-tool")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// The freedesktop Secret Service (GNOME Keyring, KWallet) via `secret-tool`.
+/// Secrets go through stdin/stdout only, never the command line.
+pub struct SecretServiceStore {
+    index_path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl SecretServiceStore {
+    pub fn new(index_path: PathBuf) -> Self {
+        Self {
+            index_path,
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+fn attributes(uuid: &str) -> [&str; 4] {
+    ["service", SERVICE, "uuid", uuid]
+}
+
+impl SecretStore for SecretServiceStore {
+    fn name(&self) -> &'static str {
+        "Secret Service"
+    }
+
+    fn get(&self, uuid: &str) -> Result<Option<String>, String> {
+        let output = Command::new("secret-tool")
+            .arg("lookup")
+            .args(attributes(uuid))
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+        // lookup exits non-zero with no output when nothing matches
+        if !output.status.success() || output.stdout.is_empty() {
+            return Ok(None);
+        }
+        String::from_utf8(output.stdout)
+            .map(Some)
+            .map_err(|_| format!("Stored secret for {} is not valid text", uuid))
+    }
+
+    fn set(&self, uuid: &str, secret: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let label = format!("anylinuxfs passphrase for {}", uuid);
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label", &label])
+            .args(attributes(uuid))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(secret.as_bytes())
+                .map_err(|e| format!("Failed to pass secret to secret-tool: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "secret-tool could not save the passphrase: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let mut uuids = read_index(&self.index_path);
+        if uuids.insert(uuid.to_string()) {
+            write_index(&self.index_path, &uuids)?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, uuid: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let existed = self.get(uuid)?.is_some();
+        if existed {
+            let status = Command::new("secret-tool")
+                .arg("clear")
+                .args(attributes(uuid))
+                .stdin(Stdio::null())
+                .status()
+                .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+            if !status.success() {
+                return Err("secret-tool could not remove the passphrase".to_string());
+            }
+        }
+        let mut uuids = read_index(&self.index_path);
+        if uuids.remove(uuid) {
+            write_index(&self.index_path, &uuids)?;
+        }
+        Ok(existed)
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        Ok(read_index(&self.index_path).into_iter().collect())
+    }
+}
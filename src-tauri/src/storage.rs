@@ -0,0 +1,62 @@
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Atomically replace `path` with an owner-only file. `what` names the file
+/// in error messages, e.g. "favorites" or "mount journal".
+pub fn write_private(path: &Path, contents: &[u8], what: &str) -> Result<(), String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("The {} path has no parent directory", what))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {} directory: {}", what, e))?;
+    let mut temp = tempfile::NamedTempFile::new_in(parent)
+        .map_err(|e| format!("Failed to create temporary {} file: {}", what, e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        temp.as_file()
+            .set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to secure {} file: {}", what, e))?;
+    }
+
+    temp.write_all(contents)
+        .map_err(|e| format!("Failed to write {}: {}", what, e))?;
+    temp.as_file_mut()
+        .sync_all()
+        .map_err(|e| format!("Failed to flush {}: {}", what, e))?;
+    temp.persist(path)
+        .map_err(|e| format!("Failed to save {}: {}", what, e.error))?;
+    Ok(())
+}
+
+/// Encode `value` as TOML and write it with [`write_private`]
+pub fn write_private_toml<T: Serialize>(path: &Path, value: &T, what: &str) -> Result<(), String> {
+    let contents =
+        toml::to_string(value).map_err(|e| format!("Failed to encode {}: {}", what, e))?;
+    write_private(path, contents.as_bytes(), what)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_replaced_and_owner_only() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("nested").join("settings.toml");
+        write_private(&path, b"old = true\n", "settings").unwrap();
+        let settings = std::collections::BTreeMap::from([("threshold", 90)]);
+        write_private_toml(&path, &settings, "settings").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "threshold = 90\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use crate::cli;
+use crate::elevation::ElevationState;
+use crate::ownership::{self, ownership_warnings};
+
+/// Drop-in installed for password-free mounts. sudo skips files in
+/// sudoers.d whose name contains a dot, so the staged copy is inert.
+pub const SUDOERS_RULE_PATH: &str = "/etc/sudoers.d/anylinuxfs-gui";
+
+/// Subcommands the GUI runs with sudo
+pub const ALLOWED_SUBCOMMANDS: &[&str] = &["list", "mount", "unmount", "stop"];
+
+const VISUDO_PATHS: &[&str] = &["/usr/sbin/visudo", "/usr/bin/visudo", "/sbin/visudo"];
+
+/// Copy the validated rule into place as root:0 0440 and check the whole
+/// sudoers configuration once it is live, backing it out if that fails. The
+/// staged file is user-writable until it is copied, so the root-owned copy
+/// must match the reviewed rule (`$4`, passed as an argument) byte for byte.
+const INSTALL_SCRIPT: &str = r#"set -e
+install -m 0440 -o 0 -g 0 "$1" "$2.new"
+if ! printf '%s' "$4" | cmp -s - "$2.new"; then rm -f "$2.new"; echo "staged rule was modified" >&2; exit 1; fi
+if ! "$3" -cf "$2.new"; then rm -f "$2.new"; exit 1; fi
+mv -f "$2.new" "$2"
+if ! "$3" -c >/dev/null; then rm -f "$2"; exit 1; fi
+"#;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SudoersRuleStatus {
+    pub path: String,
+    /// Exact drop-in contents, for review before installing
+    pub rule: String,
+    /// sudo lets the GUI run `anylinuxfs list` without a password
+    pub active: bool,
+    pub warnings: Vec<String>,
+}
+
+/// A sudoers user name: no spaces, commas, aliases or `%group` syntax
+fn validate_user(user: &str) -> Result<(), String> {
+    if user.is_empty() || user.len() > 64 {
+        return Err("Invalid user name for sudoers rule".to_string());
+    }
+    if user == "ALL" || user.starts_with('-') {
+        return Err(format!("User name '{}' cannot be used in a sudoers rule", user));
+    }
+    if !user.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')) {
+        return Err(format!("User name '{}' contains characters sudoers would misread", user));
+    }
+    Ok(())
+}
+
+/// The CLI path appears verbatim in the rule, so it must be absolute and free
+/// of characters sudoers treats as separators, escapes or wildcards.
+fn validate_cli_path(cli_path: &Path) -> Result<&str, String> {
+    let path = cli_path
+        .to_str()
+        .ok_or_else(|| "anylinuxfs path is not valid UTF-8".to_string())?;
+    if !cli_path.is_absolute() {
+        return Err(format!("anylinuxfs path '{}' is not absolute", path));
+    }
+    let valid = path
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '_' | '-' | '+' | '@'));
+    if !valid {
+        return Err(format!(
+            "anylinuxfs path '{}' contains characters that cannot be used in a sudoers rule",
+            path
+        ));
+    }
+    Ok(path)
+}
+
+/// Build the drop-in granting `user` password-free access to the GUI's
+/// subcommands of `cli_path` and nothing else. Each subcommand is listed
+/// bare (`""` means no arguments) and with arguments, since a bare command
+/// in sudoers would allow any arguments at all.
+pub fn generate_rule(user: &str, cli_path: &Path) -> Result<String, String> {
+    validate_user(user)?;
+    let path = validate_cli_path(cli_path)?;
+
+    let commands = ALLOWED_SUBCOMMANDS
+        .iter()
+        .flat_map(|subcommand| {
+            [
+                format!("{} {} \"\"", path, subcommand),
+                format!("{} {} *", path, subcommand),
+            ]
+        })
+        .collect::<Vec<_>>()
+        .join(", \\\n    ");
+
+    Ok(format!(
+        "# Installed by anylinuxfs GUI for password-free mounts.\n\
+         # Remove it from Preferences or with: sudo rm {rule_path}\n\
+         Cmnd_Alias ANYLINUXFS_GUI = \\\n    {commands}\n\
+         Defaults!ANYLINUXFS_GUI env_keep += \"ALFS_PASSPHRASE\"\n\
+         {user} ALL = (root) NOPASSWD: ANYLINUXFS_GUI\n",
+        rule_path = SUDOERS_RULE_PATH,
+        commands = commands,
+        user = user,
+    ))
+}
+
+fn find_visudo() -> Result<PathBuf, String> {
+    VISUDO_PATHS
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+        .ok_or_else(|| "visudo not found; cannot validate the sudoers rule".to_string())
+}
+
+/// Check `rule` with `visudo -cf`. Ownership is not checked for `-f` files,
+/// so this works unprivileged on a temporary copy.
+fn check_rule_with(visudo: &Path, rule: &str) -> Result<(), String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("anylinuxfs-sudoers-")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temporary sudoers file: {}", e))?;
+    file.write_all(rule.as_bytes())
+        .map_err(|e| format!("Failed to write temporary sudoers file: {}", e))?;
+
+    let output = Command::new(visudo)
+        .arg("-cf")
+        .arg(file.path())
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run visudo: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // visudo names the temporary file in its messages; point at the rule instead
+    let file_name = file.path().to_string_lossy().to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let details = stderr
+        .lines()
+        .chain(stdout.lines())
+        .map(|line| line.trim().replace(&file_name, "rule"))
+        .filter(|line| !line.is_empty() && !line.ends_with("parsed OK"))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(if details.is_empty() {
+        "visudo rejected the sudoers rule".to_string()
+    } else {
+        format!("visudo rejected the sudoers rule: {}", details)
+    })
+}
+
+fn current_user() -> Result<String, String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .map_err(|_| "Cannot determine the current user name".to_string())
+}
+
+/// Whether sudo already allows `anylinuxfs list` without asking
+fn rule_active(cli_path: &Path) -> bool {
+    Command::new("sudo")
+        .args(["-n", "-l", "--"])
+        .arg(cli_path)
+        .arg("list")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn rule_status() -> Result<SudoersRuleStatus, String> {
+    let cli_path = cli::get_path()
+        .ok_or_else(|| "anylinuxfs CLI not found in PATH or standard locations".to_string())?;
+    let rule = generate_rule(&current_user()?, cli_path)?;
+    Ok(SudoersRuleStatus {
+        path: SUDOERS_RULE_PATH.to_string(),
+        rule,
+        active: rule_active(cli_path),
+        warnings: ownership_warnings(cli_path),
+    })
+}
+
+/// Install only the rule the user reviewed, and only over a CLI binary that
+/// unprivileged programs cannot replace unless the user accepted that risk
+fn check_install_allowed(
+    status: &SudoersRuleStatus,
+    reviewed_rule: &str,
+    accept_ownership_risk: bool,
+) -> Result<(), String> {
+    ownership::check_install_allowed(
+        "sudoers rule",
+        &status.rule,
+        reviewed_rule,
+        &status.warnings,
+        accept_ownership_risk,
+    )
+}
+
+fn install_rule(
+    elevation_state: &ElevationState,
+    reviewed_rule: &str,
+    accept_ownership_risk: bool,
+) -> Result<SudoersRuleStatus, String> {
+    let status = rule_status()?;
+    check_install_allowed(&status, reviewed_rule, accept_ownership_risk)?;
+    let visudo = find_visudo()?;
+    check_rule_with(&visudo, &status.rule)?;
+
+    let mut staged = tempfile::Builder::new()
+        .prefix("anylinuxfs-sudoers-")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temporary sudoers file: {}", e))?;
+    staged
+        .write_all(status.rule.as_bytes())
+        .map_err(|e| format!("Failed to write temporary sudoers file: {}", e))?;
+    staged
+        .as_file_mut()
+        .sync_all()
+        .map_err(|e| format!("Failed to flush temporary sudoers file: {}", e))?;
+
+    let staged_path = staged.path().to_string_lossy().to_string();
+    let visudo_path = visudo.to_string_lossy().to_string();
+    cli::execute_privileged(
+        Path::new("/bin/sh"),
+        &[
+            "-c",
+            INSTALL_SCRIPT,
+            "sh",
+            &staged_path,
+            SUDOERS_RULE_PATH,
+            &visudo_path,
+            &status.rule,
+        ],
+        elevation_state,
+    )
+    .map_err(|e| format!("Failed to install sudoers rule: {}", e))?;
+    log::info!("Installed sudoers rule at {}", SUDOERS_RULE_PATH);
+
+    rule_status()
+}
+
+fn uninstall_rule(elevation_state: &ElevationState) -> Result<SudoersRuleStatus, String> {
+    cli::execute_privileged(
+        Path::new("/bin/rm"),
+        &["-f", SUDOERS_RULE_PATH],
+        elevation_state,
+    )
+        .map_err(|e| format!("Failed to remove sudoers rule: {}", e))?;
+    log::info!("Removed sudoers rule at {}", SUDOERS_RULE_PATH);
+    rule_status()
+}
+
+/// Show the rule that would be installed and whether it is in effect
+#[tauri::command]
+pub async fn get_sudoers_rule() -> Result<SudoersRuleStatus, String> {
+    tokio::task::spawn_blocking(rule_status)
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// Install `reviewed_rule`, the rule text the user was shown. Ownership
+/// warnings block the install unless `accept_ownership_risk` is set.
+#[tauri::command]
+pub async fn install_sudoers_rule(
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+    reviewed_rule: String,
+    accept_ownership_risk: bool,
+) -> Result<SudoersRuleStatus, String> {
+    let elevation_state = elevation_state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        install_rule(&elevation_state, &reviewed_rule, accept_ownership_risk)
+    })
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[tauri::command]
+pub async fn uninstall_sudoers_rule(
+    elevation_state: tauri::State<'_, Arc<ElevationState>>,
+) -> Result<SudoersRuleStatus, String> {
+    let elevation_state = elevation_state.inner().clone();
+    tokio::task::spawn_blocking(move || uninstall_rule(&elevation_state))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn rule_is_limited_to_the_gui_subcommands() {
+        let rule = generate_rule("alice", Path::new("/opt/homebrew/bin/anylinuxfs")).unwrap();
+
+        let lines = rule.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines.last(),
+            Some(&"alice ALL = (root) NOPASSWD: ANYLINUXFS_GUI")
+        );
+        for subcommand in ALLOWED_SUBCOMMANDS {
+            assert!(rule.contains(&format!("/opt/homebrew/bin/anylinuxfs {} \"\"", subcommand)));
+            assert!(rule.contains(&format!("/opt/homebrew/bin/anylinuxfs {} *", subcommand)));
+        }
+        // A bare command path would allow every subcommand
+        assert!(!rule.contains("anylinuxfs,") && !rule.contains("anylinuxfs\n"));
+        assert!(!rule.contains("shell") && !rule.contains("apk"));
+    }
+
+    #[test]
+    fn rule_rejects_users_and_paths_sudoers_would_misread() {
+        let cli = Path::new("/usr/local/bin/anylinuxfs");
+        assert!(generate_rule("ALL", cli).is_err());
+        assert!(generate_rule("%admin", cli).is_err());
+        assert!(generate_rule("alice,bob", cli).is_err());
+        assert!(generate_rule("alice", Path::new("bin/anylinuxfs")).is_err());
+        assert!(generate_rule("alice", Path::new("/opt/any linux/anylinuxfs")).is_err());
+        assert!(generate_rule("alice", Path::new("/opt/*/anylinuxfs")).is_err());
+        assert!(generate_rule("alice", Path::new("/opt/a,b:c=d/anylinuxfs")).is_err());
+    }
+
+    #[test]
+    fn install_needs_the_reviewed_rule_and_a_safe_binary() {
+        let rule = generate_rule("alice", Path::new("/usr/local/bin/anylinuxfs")).unwrap();
+        let mut status = SudoersRuleStatus {
+            path: SUDOERS_RULE_PATH.to_string(),
+            rule: rule.clone(),
+            active: false,
+            warnings: Vec::new(),
+        };
+        assert!(check_install_allowed(&status, &rule, false).is_ok());
+        assert!(check_install_allowed(&status, "alice ALL = (root) NOPASSWD: ALL\n", false).is_err());
+
+        status.warnings.push("/usr/local/bin can be modified without administrator rights".to_string());
+        let error = check_install_allowed(&status, &rule, false).unwrap_err();
+        assert!(error.contains("/usr/local/bin can be modified"));
+        assert!(check_install_allowed(&status, &rule, true).is_ok());
+    }
+
+    #[test]
+    fn install_script_refuses_a_modified_staged_rule() {
+        // The script without the root-only ownership change, against a fake visudo
+        let script = INSTALL_SCRIPT.replace(" -o 0 -g 0", "");
+        let directory = tempfile::tempdir().unwrap();
+        let staged = directory.path().join("staged");
+        let target = directory.path().join("anylinuxfs-gui");
+        let run = |staged_contents: &str, reviewed: &str| {
+            fs::write(&staged, staged_contents).unwrap();
+            Command::new("/bin/sh")
+                .arg("-c")
+                .arg(&script)
+                .arg("sh")
+                .arg(&staged)
+                .arg(&target)
+                .arg("/usr/bin/true")
+                .arg(reviewed)
+                .status()
+                .unwrap()
+                .success()
+        };
+
+        let rule = generate_rule("alice", Path::new("/usr/local/bin/anylinuxfs")).unwrap();
+        assert!(!run("alice ALL = (root) NOPASSWD: ALL\n", &rule));
+        assert!(!target.exists() && !directory.path().join("anylinuxfs-gui.new").exists());
+        assert!(run(&rule, &rule));
+        assert_eq!(fs::read_to_string(&target).unwrap(), rule);
+    }
+
+    #[test]
+    fn visudo_errors_are_reported_without_the_temporary_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for visudo: accepts files that grant NOPASSWD
+        let directory = tempfile::tempdir().unwrap();
+        let visudo = directory.path().join("visudo");
+        fs::write(
+            &visudo,
+            "#!/bin/sh\n\
+             if grep -q NOPASSWD \"$2\"; then echo \"$2: parsed OK\"; exit 0; fi\n\
+             echo \"$2:1:1: syntax error\" >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&visudo, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let rule = generate_rule("alice", Path::new("/usr/local/bin/anylinuxfs")).unwrap();
+        assert!(check_rule_with(&visudo, &rule).is_ok());
+
+        let error = check_rule_with(&visudo, "alice ALL = ALL\n").unwrap_err();
+        assert_eq!(error, "visudo rejected the sudoers rule: rule:1:1: syntax error");
+
+        // The real parser must accept the generated rule where it is installed
+        if let Ok(visudo) = find_visudo() {
+            check_rule_with(&visudo, &rule).unwrap();
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::time::Duration;
+use crate::elevation::ElevationMode;
+
+/// Upper bound for a configured timeout, so a typo cannot park an operation
+/// for days
+const MAX_TIMEOUT_SECS: u64 = 6 * 60 * 60;
+
+const MAX_RETRIES: u32 = 10;
+
+/// Upper bound for a single wait between attempts
+const MAX_BACKOFF_MS: u64 = 5 * 60 * 1000;
+
+const DEFAULT_BACKOFF_MS: u64 = 1000;
+
+/// Failures that usually clear up on their own, e.g. a RAID set or LVM
+/// volume group that is still assembling or a VM port not yet released.
+/// A busy device is not among them: it stays busy until the user closes
+/// whatever holds it, and unmount and eject report that instead of retrying.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "temporarily unavailable",
+    "try again",
+    "connection refused",
+];
+
+/// Whether a failed command is worth retrying
+pub fn is_transient_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    // The friendly busy message ends in "try again", meant for the user
+    if message.contains("busy") {
+        return false;
+    }
+    TRANSIENT_ERRORS.iter().any(|pattern| message.contains(pattern))
+}
+
+/// Operations with their own entry in [`OperationPolicies`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    List,
+    Mount,
+    Unmount,
+    Eject,
+    Remount,
+    Stop,
+    /// One-off administrative changes such as installing the sudoers rule
+    Setup,
+}
+
+/// Time limit and retry behaviour of one operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationPolicy {
+    /// Limit for a single attempt
+    pub timeout_secs: u64,
+    /// Extra attempts after a transient failure
+    #[serde(default)]
+    pub retries: u32,
+    /// Wait before the first retry; doubles for every further one
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+}
+
+fn default_backoff_ms() -> u64 {
+    DEFAULT_BACKOFF_MS
+}
+
+impl Default for OperationPolicy {
+    fn default() -> Self {
+        Self::new(30, 0)
+    }
+}
+
+impl OperationPolicy {
+    const fn new(timeout_secs: u64, retries: u32) -> Self {
+        Self {
+            timeout_secs,
+            retries,
+            backoff_ms: DEFAULT_BACKOFF_MS,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Wait before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64 << retry.saturating_sub(1).min(16);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor).min(MAX_BACKOFF_MS))
+    }
+
+    /// Time every attempt and the waits between them may take together
+    pub fn budget(&self) -> Duration {
+        (1..=self.retries).fold(self.timeout() * (self.retries + 1), |total, retry| {
+            total + self.backoff(retry)
+        })
+    }
+
+    /// Run `attempt` (given the attempt number, from 0) until it succeeds, it
+    /// fails with an error `transient` rejects, or the retries are used up
+    pub fn run<T, E: Display>(
+        &self,
+        name: &str,
+        mut attempt: impl FnMut(u32) -> Result<T, E>,
+        transient: impl Fn(&E) -> bool,
+    ) -> Result<T, E> {
+        let mut number = 0;
+        loop {
+            match attempt(number) {
+                Err(error) if number < self.retries && transient(&error) => {
+                    number += 1;
+                    let wait = self.backoff(number);
+                    log::info!(
+                        "{} failed ({}), retrying in {} ms ({}/{})",
+                        name,
+                        error,
+                        wait.as_millis(),
+                        number,
+                        self.retries
+                    );
+                    std::thread::sleep(wait);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        if self.timeout_secs == 0 || self.timeout_secs > MAX_TIMEOUT_SECS {
+            return Err(format!(
+                "{} timeout must be between 1 and {} seconds",
+                name, MAX_TIMEOUT_SECS
+            ));
+        }
+        if self.retries > MAX_RETRIES {
+            return Err(format!("{} allows at most {} retries", name, MAX_RETRIES));
+        }
+        if self.backoff_ms > MAX_BACKOFF_MS {
+            return Err(format!(
+                "{} backoff must be at most {} ms",
+                name, MAX_BACKOFF_MS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Per-operation limits, stored with the elevation preferences. Operations
+/// missing from the file keep their defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OperationPolicies {
+    pub list: OperationPolicy,
+    /// Large RAID and LVM volumes can take minutes to assemble
+    pub mount: OperationPolicy,
+    pub unmount: OperationPolicy,
+    pub eject: OperationPolicy,
+    pub remount: OperationPolicy,
+    pub stop: OperationPolicy,
+    pub setup: OperationPolicy,
+    /// Used instead of the operation's own policy in Interactive Terminal
+    /// mode, where managed approval and a typed passphrase both wait on the user
+    pub interactive_elevation: OperationPolicy,
+    /// How long sudo may take with cached credentials or Touch ID before
+    /// the password dialog is used instead
+    pub native_authentication_secs: u64,
+}
+
+impl Default for OperationPolicies {
+    fn default() -> Self {
+        Self {
+            list: OperationPolicy::new(30, 1),
+            mount: OperationPolicy::new(60, 0),
+            unmount: OperationPolicy::new(30, 0),
+            eject: OperationPolicy::new(30, 0),
+            remount: OperationPolicy::new(30, 0),
+            stop: OperationPolicy::new(30, 1),
+            setup: OperationPolicy::new(120, 0),
+            interactive_elevation: OperationPolicy::new(600, 0),
+            native_authentication_secs: 10,
+        }
+    }
+}
+
+impl OperationPolicies {
+    pub fn get(&self, operation: Operation) -> OperationPolicy {
+        match operation {
+            Operation::List => self.list,
+            Operation::Mount => self.mount,
+            Operation::Unmount => self.unmount,
+            Operation::Eject => self.eject,
+            Operation::Remount => self.remount,
+            Operation::Stop => self.stop,
+            Operation::Setup => self.setup,
+        }
+    }
+
+    /// Policy for a privileged run of `operation` in `mode`
+    pub fn for_mode(&self, operation: Operation, mode: ElevationMode) -> OperationPolicy {
+        if mode == ElevationMode::InteractiveTerminal {
+            self.interactive_elevation
+        } else {
+            self.get(operation)
+        }
+    }
+
+    pub fn native_authentication(&self) -> Duration {
+        Duration::from_secs(self.native_authentication_secs)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.list.validate("List")?;
+        self.mount.validate("Mount")?;
+        self.unmount.validate("Unmount")?;
+        self.eject.validate("Eject")?;
+        self.remount.validate("Remount")?;
+        self.stop.validate("Stop")?;
+        self.setup.validate("Setup")?;
+        self.interactive_elevation.validate("Interactive Terminal")?;
+        OperationPolicy::new(self.native_authentication_secs, 0).validate("Native authentication")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_transient_failures_are_retried_with_backoff() {
+        let policy = OperationPolicy {
+            timeout_secs: 10,
+            retries: 2,
+            backoff_ms: 1,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(1));
+        assert_eq!(policy.backoff(2), Duration::from_millis(2));
+        assert_eq!(policy.budget(), Duration::from_millis(30_003));
+
+        let mut attempts = Vec::new();
+        let result: Result<(), String> = policy.run(
+            "mount",
+            |attempt| {
+                attempts.push(attempt);
+                Err("mdadm: Resource temporarily unavailable".to_string())
+            },
+            |error| is_transient_error(error),
+        );
+        assert!(result.is_err());
+        assert_eq!(attempts, vec![0, 1, 2]);
+
+        let mut attempts = 0;
+        let _: Result<(), String> = policy.run(
+            "mount",
+            |_| {
+                attempts += 1;
+                Err("Invalid operation or unsupported filesystem".to_string())
+            },
+            |error| is_transient_error(error),
+        );
+        assert_eq!(attempts, 1);
+        assert!(!is_transient_error("Device is busy - close any applications using it and try again"));
+    }
+
+    #[test]
+    fn partial_policies_keep_defaults_and_are_validated() {
+        let policies: OperationPolicies = toml::from_str(
+            "native_authentication_secs = 20\n\n[mount]\ntimeout_secs = 300\nretries = 2\n",
+        )
+        .unwrap();
+        assert_eq!(policies.mount.timeout_secs, 300);
+        assert_eq!(policies.mount.backoff_ms, DEFAULT_BACKOFF_MS);
+        assert_eq!(policies.list, OperationPolicies::default().list);
+        assert_eq!(policies.native_authentication(), Duration::from_secs(20));
+        assert!(policies.validate().is_ok());
+
+        let policies = OperationPolicies {
+            unmount: OperationPolicy::new(0, 0),
+            ..OperationPolicies::default()
+        };
+        assert!(policies.validate().is_err());
+        assert_eq!(
+            policies.for_mode(Operation::List, ElevationMode::InteractiveTerminal),
+            policies.interactive_elevation
+        );
+    }
+}
@@ -4,9 +4,30 @@ import type {
 	MountInfo,
 	AppConfig,
 	CliStatus,
+	AskpassBackend,
 	ElevationMode,
 	ElevationPolicy,
-	MountCommandResult
+	OperationPolicies,
+	SudoersRuleStatus,
+	PolkitActionStatus,
+	AuditEntry,
+	AuditQuery,
+	AuditVerification,
+	MountCommandResult,
+	MountRequest,
+	MountOptionError,
+	UnmountCommandResult,
+	RemountResult,
+	VmResources,
+	SavedPassphrases,
+	ShareSettings,
+	ActiveShare,
+	RecoveryResult,
+	IdlePolicy,
+	ReconcileReport,
+	UnlockMethod,
+	Favorite,
+	FilesystemEntry
 } from './types';
 
 export async function checkCli(): Promise<CliStatus> {
@@ -17,8 +38,13 @@ export async function listDisks(useSudo: boolean = false, silent: boolean = fals
 	return await invoke<DiskListResult>('list_disks', { useSudo, silent });
 }
 
-export async function mountDisk(device: string, passphrase?: string, readOnly?: boolean, extraOptions?: string, ignorePermissions?: boolean): Promise<MountCommandResult> {
-	return await invoke<MountCommandResult>('mount_disk', { device, passphrase: passphrase || null, readOnly: readOnly || false, extraOptions: extraOptions || null, ignorePermissions: ignorePermissions || false });
+export async function mountDisk(device: string, passphrase?: string, readOnly?: boolean, extraOptions?: string, ignorePermissions?: boolean, action?: string, image?: string, rawOptions?: string, resources?: VmResources, rememberPassphrase?: boolean, unlock?: UnlockMethod): Promise<MountCommandResult> {
+	const request: MountRequest = { device, passphrase: passphrase || null, read_only: readOnly || false, extra_options: extraOptions || null, ignore_permissions: ignorePermissions || false, action: action || null, image: image || null, raw_options: rawOptions || null, ram_mb: resources?.ram_mb ?? null, vcpus: resources?.vcpus ?? null, remember_passphrase: rememberPassphrase || false, unlock: unlock || null };
+	return await invoke<MountCommandResult>('mount_disk', { request });
+}
+
+export async function validateMountOptions(device: string, options: string, readOnly?: boolean): Promise<MountOptionError[]> {
+	return await invoke<MountOptionError[]>('validate_mount_options', { device, options, readOnly: readOnly || false });
 }
 
 export async function getElevationPolicy(): Promise<ElevationPolicy> {
@@ -29,16 +55,77 @@ export async function setElevationMode(mode: ElevationMode): Promise<ElevationPo
 	return await invoke<ElevationPolicy>('set_elevation_mode', { mode });
 }
 
+export async function setAskpassBackend(backend: AskpassBackend): Promise<ElevationPolicy> {
+	return await invoke<ElevationPolicy>('set_askpass_backend', { backend });
+}
+
+export async function setOperationPolicies(operations: OperationPolicies): Promise<ElevationPolicy> {
+	return await invoke<ElevationPolicy>('set_operation_policies', { operations });
+}
+
+export async function setTerminalCommand(command: string | null): Promise<ElevationPolicy> {
+	return await invoke<ElevationPolicy>('set_terminal_command', { command });
+}
+
+export async function setHelperOwnershipRisk(accept: boolean): Promise<ElevationPolicy> {
+	return await invoke<ElevationPolicy>('set_helper_ownership_risk', { accept });
+}
+
+export async function answerAskpass(id: number, password: string | null): Promise<void> {
+	await invoke('answer_askpass', { id, password });
+}
+
+export async function getSudoersRule(): Promise<SudoersRuleStatus> {
+	return await invoke<SudoersRuleStatus>('get_sudoers_rule');
+}
+
+/** Installs `reviewedRule`, the rule text shown to the user */
+export async function installSudoersRule(reviewedRule: string, acceptOwnershipRisk: boolean): Promise<SudoersRuleStatus> {
+	return await invoke<SudoersRuleStatus>('install_sudoers_rule', { reviewedRule, acceptOwnershipRisk });
+}
+
+export async function uninstallSudoersRule(): Promise<SudoersRuleStatus> {
+	return await invoke<SudoersRuleStatus>('uninstall_sudoers_rule');
+}
+
+export async function getPolkitAction(): Promise<PolkitActionStatus> {
+	return await invoke<PolkitActionStatus>('get_polkit_action');
+}
+
+export async function installPolkitAction(reviewedAction: string, acceptOwnershipRisk: boolean): Promise<PolkitActionStatus> {
+	return await invoke<PolkitActionStatus>('install_polkit_action', { reviewedAction, acceptOwnershipRisk });
+}
+
+export async function uninstallPolkitAction(): Promise<PolkitActionStatus> {
+	return await invoke<PolkitActionStatus>('uninstall_polkit_action');
+}
+
+export async function queryAuditLog(query?: AuditQuery): Promise<AuditEntry[]> {
+	return await invoke<AuditEntry[]>('query_audit_log', { query: query ?? null });
+}
+
+export async function verifyAuditLog(): Promise<AuditVerification> {
+	return await invoke<AuditVerification>('verify_audit_log');
+}
+
 export async function cancelElevationOperation(device: string): Promise<number> {
 	return await invoke<number>('cancel_elevation_operation', { device });
 }
 
-export async function unmountDisk(device?: string): Promise<string> {
-	return await invoke<string>('unmount_disk', { device: device || null });
+export async function remountDisk(device: string, readOnly: boolean, extraOptions?: string, passphrase?: string): Promise<RemountResult> {
+	return await invoke<RemountResult>('remount_disk', { device, readOnly, extraOptions: extraOptions || null, passphrase: passphrase || null });
+}
+
+export async function unmountDisk(device?: string): Promise<UnmountCommandResult> {
+	return await invoke<UnmountCommandResult>('unmount_disk', { device: device || null });
+}
+
+export async function ejectDisk(device: string): Promise<UnmountCommandResult> {
+	return await invoke<UnmountCommandResult>('eject_disk', { device });
 }
 
-export async function ejectDisk(device: string): Promise<string> {
-	return await invoke<string>('eject_disk', { device });
+export async function terminateBlockingProcesses(device: string | undefined, pids: number[], eject: boolean = false): Promise<UnmountCommandResult> {
+	return await invoke<UnmountCommandResult>('terminate_blocking_processes', { device: device || null, pids, eject });
 }
 
 export async function forceCleanup(): Promise<string> {
@@ -163,6 +250,106 @@ export async function deleteCustomAction(name: string): Promise<void> {
 	return await invoke<void>('delete_custom_action', { name });
 }
 
+export async function listFavorites(): Promise<Favorite[]> {
+	return await invoke<Favorite[]>('list_favorites');
+}
+
+export async function addFavorite(favorite: Favorite): Promise<Favorite[]> {
+	return await invoke<Favorite[]>('add_favorite', { favorite });
+}
+
+export async function removeFavorite(uuid: string): Promise<Favorite[]> {
+	return await invoke<Favorite[]>('remove_favorite', { uuid });
+}
+
 export async function setTrayUnmountEnabled(enabled: boolean): Promise<void> {
 	return await invoke<void>('set_tray_unmount_enabled', { enabled });
 }
+
+export async function getFilesystemSupport(): Promise<FilesystemEntry[]> {
+	return await invoke<FilesystemEntry[]>('get_filesystem_support');
+}
+
+export async function reloadFilesystemSupport(): Promise<FilesystemEntry[]> {
+	return await invoke<FilesystemEntry[]>('reload_filesystem_support');
+}
+
+export async function listVmResourceOverrides(): Promise<Record<string, VmResources>> {
+	return await invoke<Record<string, VmResources>>('list_vm_resource_overrides');
+}
+
+export async function forgetVmResourceOverride(uuid: string): Promise<Record<string, VmResources>> {
+	return await invoke<Record<string, VmResources>>('forget_vm_resource_override', { uuid });
+}
+
+export async function listSavedPassphrases(): Promise<SavedPassphrases> {
+	return await invoke<SavedPassphrases>('list_saved_passphrases');
+}
+
+export async function forgetSavedPassphrase(uuid: string): Promise<SavedPassphrases> {
+	return await invoke<SavedPassphrases>('forget_saved_passphrase', { uuid });
+}
+
+export async function clearSavedPassphrases(): Promise<SavedPassphrases> {
+	return await invoke<SavedPassphrases>('clear_saved_passphrases');
+}
+
+export async function listShareSettings(): Promise<ShareSettings[]> {
+	return await invoke<ShareSettings[]>('list_share_settings');
+}
+
+export async function setShareSettings(settings: ShareSettings): Promise<ShareSettings[]> {
+	return await invoke<ShareSettings[]>('set_share_settings', { settings });
+}
+
+export async function removeShareSettings(uuid: string): Promise<ShareSettings[]> {
+	return await invoke<ShareSettings[]>('remove_share_settings', { uuid });
+}
+
+export async function listActiveShares(): Promise<ActiveShare[]> {
+	return await invoke<ActiveShare[]>('list_active_shares');
+}
+
+export async function getUsageAlertThreshold(): Promise<number> {
+	return await invoke<number>('get_usage_alert_threshold');
+}
+
+export async function setUsageAlertThreshold(percent: number): Promise<number> {
+	return await invoke<number>('set_usage_alert_threshold', { percent });
+}
+
+export async function startHealthMonitor(): Promise<void> {
+	return await invoke<void>('start_health_monitor');
+}
+
+export async function recoverMount(device: string, mountPoint: string): Promise<RecoveryResult> {
+	return await invoke<RecoveryResult>('recover_mount', { device, mountPoint });
+}
+
+export async function startIdleMonitor(): Promise<void> {
+	return await invoke<void>('start_idle_monitor');
+}
+
+export async function listIdlePolicies(): Promise<IdlePolicy[]> {
+	return await invoke<IdlePolicy[]>('list_idle_policies');
+}
+
+export async function setIdlePolicy(policy: IdlePolicy): Promise<IdlePolicy[]> {
+	return await invoke<IdlePolicy[]>('set_idle_policy', { policy });
+}
+
+export async function removeIdlePolicy(uuid: string): Promise<IdlePolicy[]> {
+	return await invoke<IdlePolicy[]>('remove_idle_policy', { uuid });
+}
+
+export async function setMountIdleTimeout(device: string, minutes: number | null): Promise<void> {
+	return await invoke<void>('set_mount_idle_timeout', { device, minutes });
+}
+
+export async function postponeIdleUnmount(device: string, minutes?: number): Promise<void> {
+	return await invoke<void>('postpone_idle_unmount', { device, minutes: minutes ?? null });
+}
+
+export async function reconcilePreviousSession(): Promise<ReconcileReport> {
+	return await invoke<ReconcileReport>('reconcile_previous_session');
+}
@@ -4,7 +4,15 @@ export const Events = {
 	SHELL_OUTPUT: 'shell-output',
 	SHELL_EXIT: 'shell-exit',
 	DISKS_CHANGED: 'disks-changed',
-	STATUS_CHANGED: 'status-changed'
+	STATUS_CHANGED: 'status-changed',
+	FAVORITE_AUTO_MOUNT: 'favorite-auto-mount',
+	MOUNT_PROGRESS: 'mount-progress',
+	MOUNT_ALMOST_FULL: 'mount-almost-full',
+	MOUNT_UNHEALTHY: 'mount-unhealthy',
+	MOUNT_IDLE_WARNING: 'mount-idle-warning',
+	MOUNT_IDLE_UNMOUNTED: 'mount-idle-unmounted',
+	ASKPASS_REQUEST: 'askpass-request',
+	ASKPASS_DISMISSED: 'askpass-dismissed'
 } as const;
 
 // Timeouts (in milliseconds)
@@ -1,5 +1,5 @@
 import { writable, derived, get } from 'svelte/store';
-import type { Disk, DiskListResult } from '../types';
+import type { Disk, DiskListResult, DisksChangedEvent, MountOptionError, UnlockMethod } from '../types';
 import { cancelElevationOperation, listDisks, mountDisk, unmountDisk } from '../api';
 import { Timeouts, validateDevicePath } from '../constants';
 import { logAction, logError, notifyIfHidden } from '../logger';
@@ -13,11 +13,19 @@ interface DisksState {
 	mountingDevices: Set<string>;
 	cancellableMounts: Set<string>;
 	mountingMessages: Map<string, string>;
+	// Per-option problems from each device's last mount attempt
+	optionErrors: Map<string, MountOptionError[]>;
 	adminMode: boolean;
 	hasSupportedPartitions: boolean;
 	recentUnmount: boolean;
 }
 
+// Name a disk the way the user knows it: model, else a volume label
+function diskName(disk: Disk | undefined, device: string): string {
+	const label = disk?.partitions.find((p) => p.label)?.label;
+	return disk?.model || label || device;
+}
+
 function createDisksStore() {
 	// Track adminMode locally to avoid subscribe/unsubscribe overhead
 	let currentAdminMode = false;
@@ -29,6 +37,7 @@ function createDisksStore() {
 		mountingDevices: new Set(),
 		cancellableMounts: new Set(),
 		mountingMessages: new Map(),
+		optionErrors: new Map(),
 		adminMode: false,
 		hasSupportedPartitions: true,
 		recentUnmount: false
@@ -85,11 +94,50 @@ function createDisksStore() {
 				refreshPromise = null;
 			}
 		},
+		// Apply a disk watcher diff: drop removed disks and partitions right away,
+		// refresh for the details of added ones and name them in a notification
+		async applyChanges(changes: DisksChangedEvent) {
+			const removedDisks = new Set(changes.removed_disks.map((d) => d.device));
+			const removedPartitions = new Set(changes.removed_partitions.map((p) => p.device));
+			const before = get({ subscribe }).disks;
+
+			if (removedDisks.size > 0 || removedPartitions.size > 0) {
+				for (const device of removedDisks) {
+					const name = diskName(before.find((d) => d.device === device), device);
+					logAction('Disk removed', { device });
+					notifyIfHidden('Disk Removed', `${name} was disconnected.`);
+				}
+				update((s) => ({
+					...s,
+					disks: s.disks
+						.filter((d) => !removedDisks.has(d.device))
+						.map((d) => ({ ...d, partitions: d.partitions.filter((p) => !removedPartitions.has(p.device)) }))
+				}));
+			}
+
+			const onlyRemovals =
+				(removedDisks.size > 0 || removedPartitions.size > 0) &&
+				changes.added_disks.length === 0 &&
+				changes.changed_disks.length === 0 &&
+				changes.added_partitions.length === 0 &&
+				changes.changed_partitions.length === 0;
+			if (onlyRemovals) return;
+
+			// Silent so the watcher never raises an authentication dialog
+			await this.refresh(undefined, true);
+
+			const after = get({ subscribe }).disks;
+			for (const added of changes.added_disks) {
+				const name = diskName(after.find((d) => d.device === added.device), added.device);
+				logAction('Disk added', { device: added.device, partitions: added.partitions.length });
+				notifyIfHidden('Disk Connected', `${name} is ready to mount.`);
+			}
+		},
 		setAdminMode(enabled: boolean) {
 			currentAdminMode = enabled;
 			update((s) => ({ ...s, adminMode: enabled }));
 		},
-		async mount(device: string, passphrase?: string, readOnly?: boolean, extraOptions?: string, ignorePermissions?: boolean): Promise<'success' | 'encryption_required' | 'cancelled' | 'error'> {
+		async mount(device: string, passphrase?: string, readOnly?: boolean, extraOptions?: string, ignorePermissions?: boolean, rawOptions?: string, unlock?: UnlockMethod): Promise<'success' | 'encryption_required' | 'wrong_key' | 'cancelled' | 'invalid_options' | 'error'> {
 			// Reject if this specific device is already being mounted
 			const current = get({ subscribe });
 			if (current.mountingDevices.has(device)) return 'error';
@@ -108,7 +156,9 @@ function createDisksStore() {
 				const devices = new Set(s.mountingDevices);
 				const cancellableMounts = new Set(s.cancellableMounts);
 				const messages = new Map(s.mountingMessages);
+				const optionErrors = new Map(s.optionErrors);
 				devices.add(device);
+				optionErrors.delete(device);
 				if (elevationMode === 'interactive_terminal') cancellableMounts.add(device);
 				messages.set(
 					device,
@@ -121,25 +171,40 @@ function createDisksStore() {
 					mountingDevices: devices,
 					cancellableMounts,
 					mountingMessages: messages,
+					optionErrors,
 					error: null,
 					recentUnmount: false
 				};
 			});
 			try {
-				const result = await mountDisk(device, passphrase, readOnly, extraOptions, ignorePermissions);
+				const result = await mountDisk(device, passphrase, readOnly, extraOptions, ignorePermissions, undefined, undefined, rawOptions, undefined, undefined, unlock);
+				// Errors from a refused mount, or warnings about options passed through unchecked
+				if (result.option_errors.length > 0) {
+					update((s) => ({ ...s, optionErrors: new Map(s.optionErrors).set(device, result.option_errors) }));
+				}
 				if (result.outcome === 'mounted') {
-					logAction('Mount completed', { device });
-					notifyIfHidden('Mount Complete', `${device} mounted successfully.`);
+					logAction('Mount completed', { device, shared: result.shared });
+					// The message explains a mount that fell back to unshared
+					const unshared = result.message?.startsWith('Mounted without sharing');
+					notifyIfHidden('Mount Complete', unshared ? `${device}: ${result.message}.` : `${device} mounted successfully.`);
 					return 'success';
 				}
 				if (result.outcome === 'encryption_required') {
 					logAction('Encryption detected, passphrase needed', { device });
 					return 'encryption_required';
 				}
+				if (result.outcome === 'wrong_key') {
+					logAction('Unlock key rejected', { device });
+					return 'wrong_key';
+				}
 				if (result.outcome === 'cancelled') {
 					logAction('Mount cancelled', { device });
 					return 'cancelled';
 				}
+				if (result.outcome === 'invalid_options') {
+					logAction('Mount options rejected', { device, errors: result.option_errors.length });
+					return 'invalid_options';
+				}
 
 				const errorMessage = result.message || 'Mount failed';
 				logError('mount', new Error(errorMessage));
@@ -185,7 +250,13 @@ function createDisksStore() {
 			if (unmountTimeout) clearTimeout(unmountTimeout);
 			update((s) => ({ ...s, error: null, recentUnmount: true }));
 			try {
-				await unmountDisk(device);
+				const result = await unmountDisk(device);
+				if (result.outcome === 'busy') {
+					const holders = result.blocking_processes
+						.map((p) => `${p.command} (${p.pid})`)
+						.join(', ');
+					throw new Error(holders ? `Volume is in use by ${holders}` : result.message || 'Device is busy');
+				}
 				// Small delay to let socket file clean up
 				await new Promise((r) => setTimeout(r, Timeouts.UNMOUNT_CLEANUP_DELAY));
 				logAction('Unmount completed', { device: device || 'all' });
@@ -206,6 +277,17 @@ function createDisksStore() {
 				return false;
 			}
 		},
+		clearOptionErrors(device: string) {
+			update((s) => {
+				if (!s.optionErrors.has(device)) return s;
+				const optionErrors = new Map(s.optionErrors);
+				optionErrors.delete(device);
+				return { ...s, optionErrors };
+			});
+		},
+		setError(error: string) {
+			update((s) => ({ ...s, error }));
+		},
 		clearError() {
 			update((s) => ({ ...s, error: null }));
 		},
@@ -1,6 +1,13 @@
 import { writable } from 'svelte/store';
-import type { ElevationMode, ElevationPolicy } from '../types';
-import { getElevationPolicy, setElevationMode } from '../api';
+import type { AskpassBackend, ElevationMode, ElevationPolicy, OperationPolicies } from '../types';
+import {
+	getElevationPolicy,
+	setAskpassBackend,
+	setElevationMode,
+	setHelperOwnershipRisk,
+	setOperationPolicies,
+	setTerminalCommand
+} from '../api';
 import { parseError } from '../errors';
 
 interface ElevationState {
@@ -10,8 +17,26 @@ interface ElevationState {
 	error: string | null;
 }
 
+const operation = (timeout_secs: number, retries = 0) => ({ timeout_secs, retries, backoff_ms: 1000 });
+
 const defaultPolicy: ElevationPolicy = {
-	mode: 'native'
+	mode: 'native',
+	askpass: 'auto',
+	available_askpass: [],
+	operations: {
+		list: operation(30, 1),
+		mount: operation(60),
+		unmount: operation(30),
+		eject: operation(30),
+		remount: operation(30),
+		stop: operation(30, 1),
+		setup: operation(120),
+		interactive_elevation: operation(600),
+		native_authentication_secs: 10
+	},
+	terminal_command: null,
+	helper_warnings: [],
+	accept_helper_ownership_risk: false
 };
 
 function createElevationStore() {
@@ -52,6 +77,66 @@ function createElevationStore() {
 				return false;
 			}
 		},
+		async setAskpass(backend: AskpassBackend): Promise<boolean> {
+			update((state) => ({ ...state, saving: true, error: null }));
+			try {
+				const policy = await setAskpassBackend(backend);
+				update((state) => ({ ...state, policy, saving: false }));
+				return true;
+			} catch (error) {
+				update((state) => ({
+					...state,
+					saving: false,
+					error: parseError(error).message
+				}));
+				return false;
+			}
+		},
+		async setOperations(operations: OperationPolicies): Promise<boolean> {
+			update((state) => ({ ...state, saving: true, error: null }));
+			try {
+				const policy = await setOperationPolicies(operations);
+				update((state) => ({ ...state, policy, saving: false }));
+				return true;
+			} catch (error) {
+				update((state) => ({
+					...state,
+					saving: false,
+					error: parseError(error).message
+				}));
+				return false;
+			}
+		},
+		async setTerminal(command: string | null): Promise<boolean> {
+			update((state) => ({ ...state, saving: true, error: null }));
+			try {
+				const policy = await setTerminalCommand(command);
+				update((state) => ({ ...state, policy, saving: false }));
+				return true;
+			} catch (error) {
+				update((state) => ({
+					...state,
+					saving: false,
+					error: parseError(error).message
+				}));
+				return false;
+			}
+		},
+		async setHelperRisk(accept: boolean): Promise<boolean> {
+			update((state) => ({ ...state, saving: true, error: null }));
+			try {
+				const policy = await setHelperOwnershipRisk(accept);
+				update((state) => ({ ...state, policy, saving: false }));
+				return true;
+			} catch (error) {
+				update((state) => ({
+					...state,
+					saving: false,
+					error: parseError(error).message
+				}));
+				return false;
+			}
+		},
 		clearError() {
 			update((state) => ({ ...state, error: null }));
 		}
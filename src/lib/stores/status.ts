@@ -1,15 +1,33 @@
 import { writable, derived } from 'svelte/store';
 import { listen } from '@tauri-apps/api/event';
-import type { MountInfo } from '../types';
-import { getMountStatus } from '../api';
+import type { IdleUnmountEvent, IdleWarningEvent, MountAlmostFullEvent, MountInfo, MountUnhealthyEvent } from '../types';
+import { getMountStatus, postponeIdleUnmount } from '../api';
 import { Events } from '../constants';
-import { logError, logAction } from '../logger';
+import { logError, logAction, notifyIfHidden } from '../logger';
 import { parseError } from '../errors';
 
 interface StatusState {
 	mounts: MountInfo[];
 	loading: boolean;
 	error: string | null;
+	// Mounts past the "almost full" threshold, from `mount-almost-full`
+	almostFull: Map<string, MountAlmostFullEvent>;
+	// Mounts the health monitor reported as hung or dead, until recovered
+	unhealthy: Map<string, MountUnhealthyEvent>;
+	// Pending idle unmounts, with the time (ms since epoch) they are due
+	idleWarnings: Map<string, IdleWarning>;
+}
+
+export type IdleWarning = IdleWarningEvent & { deadline: number };
+
+// A warning still listed this long after its deadline means the volume was
+// used again and the unmount did not happen
+const IDLE_WARNING_GRACE_MS = 60000;
+
+function usedPercent(mount: MountInfo): number {
+	const usage = mount.usage;
+	if (!usage || usage.total_bytes === 0) return 0;
+	return (usage.used_bytes / usage.total_bytes) * 100;
 }
 
 // Longer polling interval since we now have push events
@@ -22,11 +40,14 @@ function createStatusStore() {
 	const { subscribe, set, update } = writable<StatusState>({
 		mounts: [],
 		loading: false,
-		error: null
+		error: null,
+		almostFull: new Map(),
+		unhealthy: new Map(),
+		idleWarnings: new Map()
 	});
 
 	let pollInterval: ReturnType<typeof setInterval> | null = null;
-	let unlisten: (() => void) | null = null;
+	let unlisteners: (() => void)[] = [];
 	let debounceTimeout: ReturnType<typeof setTimeout> | null = null;
 	let refreshInProgress = false;
 
@@ -36,7 +57,26 @@ function createStatusStore() {
 		update((s) => ({ ...s, loading: true }));
 		try {
 			const mounts = await getMountStatus();
-			update((s) => ({ ...s, mounts, loading: false, error: null }));
+			update((s) => {
+				// Drop alerts for mounts that are gone or no longer near the threshold
+				const almostFull = new Map(s.almostFull);
+				for (const [device, alert] of almostFull) {
+					const mount = mounts.find((m) => m.device === device);
+					if (!mount || (mount.usage && usedPercent(mount) < alert.threshold_percent)) {
+						almostFull.delete(device);
+					}
+				}
+				const unhealthy = new Map(s.unhealthy);
+				for (const device of unhealthy.keys()) {
+					if (!mounts.some((m) => m.device === device)) unhealthy.delete(device);
+				}
+				const idleWarnings = new Map(s.idleWarnings);
+				for (const [device, warning] of idleWarnings) {
+					const expired = Date.now() > warning.deadline + IDLE_WARNING_GRACE_MS;
+					if (expired || !mounts.some((m) => m.device === device)) idleWarnings.delete(device);
+				}
+				return { ...s, mounts, almostFull, unhealthy, idleWarnings, loading: false, error: null };
+			});
 		} catch (e) {
 			logError('status.refresh', e);
 			update((s) => ({ ...s, error: parseError(e).message, loading: false }));
@@ -74,10 +114,60 @@ function createStatusStore() {
 
 			// Listen for status change events (push updates)
 			try {
-				unlisten = await listen(Events.STATUS_CHANGED, () => {
-					logAction('Status changed event received');
-					this.refresh();
-				});
+				unlisteners.push(
+					await listen(Events.STATUS_CHANGED, () => {
+						logAction('Status changed event received');
+						this.refresh();
+					})
+				);
+				unlisteners.push(
+					await listen<MountAlmostFullEvent>(Events.MOUNT_ALMOST_FULL, (event) => {
+						const alert = event.payload;
+						const percent = Math.round(Math.max(alert.used_percent, alert.inode_used_percent));
+						logAction('Volume almost full', { device: alert.device, percent });
+						notifyIfHidden('Volume Almost Full', `${alert.mount_point} is ${percent}% full.`);
+						update((s) => ({ ...s, almostFull: new Map(s.almostFull).set(alert.device, alert) }));
+					})
+				);
+				unlisteners.push(
+					await listen<MountUnhealthyEvent>(Events.MOUNT_UNHEALTHY, (event) => {
+						const problem = event.payload;
+						logAction('Mount unhealthy', { device: problem.device, reason: problem.reason });
+						notifyIfHidden('Mount Not Responding', `${problem.mount_point}: ${problem.message}.`);
+						update((s) => ({ ...s, unhealthy: new Map(s.unhealthy).set(problem.device, problem) }));
+					})
+				);
+				unlisteners.push(
+					await listen<IdleWarningEvent>(Events.MOUNT_IDLE_WARNING, (event) => {
+						const warning = { ...event.payload, deadline: Date.now() + event.payload.unmount_in_seconds * 1000 };
+						const minutes = Math.max(1, Math.round(warning.unmount_in_seconds / 60));
+						logAction('Idle unmount pending', { device: warning.device, seconds: warning.unmount_in_seconds });
+						notifyIfHidden(
+							'Idle Volume',
+							`${warning.mount_point} has been idle for ${warning.idle_minutes} minutes and will be unmounted in ${minutes} minute${minutes === 1 ? '' : 's'}.`
+						);
+						update((s) => ({ ...s, idleWarnings: new Map(s.idleWarnings).set(warning.device, warning) }));
+					})
+				);
+				unlisteners.push(
+					await listen<IdleUnmountEvent>(Events.MOUNT_IDLE_UNMOUNTED, (event) => {
+						const result = event.payload;
+						update((s) => {
+							const idleWarnings = new Map(s.idleWarnings);
+							idleWarnings.delete(result.device);
+							return { ...s, idleWarnings };
+						});
+						if (result.outcome === 'unmounted' || result.outcome === 'ejected') {
+							logAction('Idle volume unmounted', { device: result.device });
+							notifyIfHidden('Idle Volume Unmounted', `${result.mount_point} was unmounted after being idle.`);
+						} else {
+							const reason = result.message ?? (result.outcome === 'busy' ? 'the volume is in use' : 'unmount failed');
+							logError('idleUnmount', new Error(`${result.device}: ${reason}`));
+							notifyIfHidden('Idle Unmount Failed', `${result.mount_point} was not unmounted: ${reason}.`);
+						}
+						this.refresh();
+					})
+				);
 			} catch (e) {
 				logError('status.startListening', e);
 			}
@@ -85,15 +175,30 @@ function createStatusStore() {
 			// Fallback polling at longer interval for orphan detection
 			pollInterval = setInterval(() => this.refresh(), FALLBACK_POLL_INTERVAL);
 		},
+		// Keep an idle volume mounted for another idle period
+		async postponeIdleUnmount(device: string) {
+			await postponeIdleUnmount(device);
+			logAction('Idle unmount postponed', { device });
+			update((s) => {
+				const idleWarnings = new Map(s.idleWarnings);
+				idleWarnings.delete(device);
+				return { ...s, idleWarnings };
+			});
+		},
+		clearUnhealthy(device: string) {
+			update((s) => {
+				const unhealthy = new Map(s.unhealthy);
+				unhealthy.delete(device);
+				return { ...s, unhealthy };
+			});
+		},
 		stopListening() {
 			if (debounceTimeout) {
 				clearTimeout(debounceTimeout);
 				debounceTimeout = null;
 			}
-			if (unlisten) {
-				unlisten();
-				unlisten = null;
-			}
+			for (const unlisten of unlisteners) unlisten();
+			unlisteners = [];
 			if (pollInterval) {
 				clearInterval(pollInterval);
 				pollInterval = null;
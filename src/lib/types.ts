@@ -32,8 +32,92 @@ export interface MountInfo {
 	device: string;
 	mount_point: string;
 	filesystem: string | null;
+	options: string[];
+	read_only: boolean;
 	ram_mb: number | null;
 	vcpus: number | null;
+	usage: MountUsage | null;
+}
+
+export interface MountUsage {
+	total_bytes: number;
+	used_bytes: number;
+	available_bytes: number;
+	total_inodes: number;
+	used_inodes: number;
+	available_inodes: number;
+}
+
+export interface MountAlmostFullEvent {
+	device: string;
+	mount_point: string;
+	used_percent: number;
+	inode_used_percent: number;
+	available_bytes: number;
+	threshold_percent: number;
+}
+
+export type UnhealthyReason = 'unresponsive' | 'inaccessible' | 'vm_not_running';
+
+export interface MountUnhealthyEvent {
+	device: string;
+	mount_point: string;
+	reason: UnhealthyReason;
+	message: string;
+	recovery_command: string;
+}
+
+export interface RecoveryStep {
+	name: string;
+	ok: boolean;
+	message: string;
+}
+
+export interface RecoveryResult {
+	outcome: 'recovered' | 'failed';
+	message: string | null;
+	steps: RecoveryStep[];
+}
+
+export interface IdlePolicy {
+	uuid: string;
+	label: string | null;
+	idle_minutes: number;
+}
+
+export interface IdleWarningEvent {
+	device: string;
+	mount_point: string;
+	uuid: string | null;
+	idle_minutes: number;
+	unmount_in_seconds: number;
+}
+
+export interface IdleUnmountEvent {
+	device: string;
+	mount_point: string;
+	/** null when the unmount failed */
+	outcome: UnmountOutcome | null;
+	message: string | null;
+}
+
+export interface AdoptedMount {
+	device: string;
+	mount_point: string;
+}
+
+/** What the startup reconciliation with the previous GUI session did */
+export interface ReconcileReport {
+	adopted: AdoptedMount[];
+	unmanaged: string[];
+	released: string[];
+	stopped: string[];
+	pending: string[];
+	running_terminal_sessions: number[];
+	removed_handoff_dirs: number;
+	removed_askpass_scripts: number;
+	removed_mount_points: string[];
+	errors: string[];
 }
 
 export interface AppConfig {
@@ -42,17 +126,155 @@ export interface AppConfig {
 	log_level: string | null;
 }
 
-export type ElevationMode = 'native' | 'interactive_terminal';
+export type ElevationMode = 'native' | 'interactive_terminal' | 'helper' | 'polkit';
+
+export type AskpassBackend = 'auto' | 'osascript' | 'zenity' | 'kdialog' | 'ssh_askpass' | 'built_in';
+
+/** Limit for one attempt, and retries with doubling backoff for transient failures */
+export interface OperationPolicy {
+	timeout_secs: number;
+	retries: number;
+	backoff_ms: number;
+}
+
+export interface OperationPolicies {
+	list: OperationPolicy;
+	mount: OperationPolicy;
+	unmount: OperationPolicy;
+	eject: OperationPolicy;
+	remount: OperationPolicy;
+	stop: OperationPolicy;
+	setup: OperationPolicy;
+	/** Replaces the others in Interactive Terminal mode */
+	interactive_elevation: OperationPolicy;
+	native_authentication_secs: number;
+}
+
+export type PolicyOperation = Exclude<keyof OperationPolicies, 'native_authentication_secs'>;
 
 export interface ElevationPolicy {
 	mode: ElevationMode;
+	askpass: AskpassBackend;
+	available_askpass: AskpassBackend[];
+	operations: OperationPolicies;
+	/** Replaces the detected terminal in Interactive Terminal mode */
+	terminal_command: string | null;
+	/** Why the helper would refuse to run the detected CLI as root */
+	helper_warnings: string[];
+	/** Start the helper despite helper_warnings */
+	accept_helper_ownership_risk: boolean;
+}
+
+export interface AskpassRequest {
+	id: number;
+	prompt: string;
+}
+
+export interface SudoersRuleStatus {
+	path: string;
+	rule: string;
+	active: boolean;
+	warnings: string[];
+}
+
+export interface PolkitActionStatus {
+	path: string;
+	action: string;
+	installed: boolean;
+	pkexec_available: boolean;
+	warnings: string[];
+}
+
+export type AuditMethod = 'sudo' | 'sudo_askpass' | 'terminal' | 'helper' | 'pkexec';
+
+export type AuditOutcome =
+	| 'succeeded'
+	| 'failed'
+	| 'authentication_required'
+	| 'cancelled'
+	| 'timed_out';
+
+/** One privileged command; `args` has secrets redacted */
+export interface AuditEntry {
+	seq: number;
+	/** Milliseconds since the Unix epoch */
+	timestamp: number;
+	mode: ElevationMode;
+	method: AuditMethod;
+	program: string;
+	subcommand: string | null;
+	args: string[];
+	secret: 'passphrase' | 'keyfile' | null;
+	duration_ms: number;
+	exit_status: number | null;
+	outcome: AuditOutcome;
+	prev_hash: string;
+	hash: string;
+}
+
+export interface AuditQuery {
+	since?: number;
+	until?: number;
+	subcommand?: string;
+	outcome?: AuditOutcome;
+	limit?: number;
+}
+
+export interface AuditVerification {
+	valid: boolean;
+	entries: number;
+	/** null when the chain is intact but does not end at the anchored head */
+	first_invalid_line: number | null;
+	message: string | null;
 }
 
-export type MountOutcome = 'mounted' | 'encryption_required' | 'cancelled' | 'timed_out' | 'failed';
+export type MountOutcome =
+	| 'mounted'
+	| 'encryption_required'
+	| 'wrong_key'
+	| 'cancelled'
+	| 'timed_out'
+	| 'invalid_options'
+	| 'failed';
+
+export interface MountOptionError {
+	option: string;
+	message: string;
+}
 
 export interface MountCommandResult {
 	outcome: MountOutcome;
 	message: string | null;
+	action: string | null;
+	captured_environment: Record<string, string>;
+	option_errors: MountOptionError[];
+	used_saved_passphrase: boolean;
+	shared: boolean;
+}
+
+export type RemountOutcome = 'remounted' | 'cycled' | 'unchanged' | 'unmounted';
+
+export interface RemountResult {
+	outcome: RemountOutcome;
+	message: string | null;
+	mount: MountInfo | null;
+	mount_outcome: MountOutcome | null;
+	option_errors: MountOptionError[];
+	blocking_processes: BlockingProcess[];
+}
+
+export type UnmountOutcome = 'unmounted' | 'ejected' | 'busy';
+
+export interface BlockingProcess {
+	pid: number;
+	command: string;
+	path: string;
+}
+
+export interface UnmountCommandResult {
+	outcome: UnmountOutcome;
+	message: string | null;
+	blocking_processes: BlockingProcess[];
 }
 
 export interface CliStatus {
@@ -63,3 +285,120 @@ export interface CliStatus {
 	cli_version: string | null;
 	gui_version: string;
 }
+
+/** Unlock method other than a typed passphrase */
+export type UnlockMethod =
+	| { method: 'keyfile'; path: string }
+	| { method: 'recovery_key'; key: string };
+
+export interface MountRequest {
+	device: string;
+	passphrase: string | null;
+	read_only: boolean;
+	extra_options: string | null;
+	ignore_permissions: boolean;
+	action: string | null;
+	image: string | null;
+	raw_options: string | null;
+	ram_mb: number | null;
+	vcpus: number | null;
+	remember_passphrase: boolean;
+	unlock: UnlockMethod | null;
+}
+
+export interface SavedPassphrases {
+	backend: string;
+	uuids: string[];
+}
+
+/** Per-partition VM size; unset fields use the global config */
+export type SquashMode = 'root_squash' | 'no_root_squash' | 'all_squash';
+
+export interface ShareSettings {
+	uuid: string;
+	label: string | null;
+	allowed_networks: string[];
+	read_only: boolean;
+	squash: SquashMode;
+	anon_uid: number | null;
+	anon_gid: number | null;
+}
+
+export interface ActiveShare {
+	device: string;
+	uuid: string;
+	label: string | null;
+	mount_point: string;
+	export_path: string;
+	allowed_networks: string[];
+	read_only: boolean;
+	addresses: string[];
+}
+
+export interface VmResources {
+	ram_mb?: number;
+	vcpus?: number;
+}
+
+export type SupportLevel = 'supported' | 'limited' | 'unsupported';
+
+export interface FilesystemEntry {
+	id: string;
+	aliases: string[];
+	exact: boolean;
+	support: SupportLevel;
+	note: string | null;
+	native: boolean;
+	image: 'alpine' | 'freebsd' | null;
+	packages: string[];
+	mount_options: string | null;
+}
+
+export interface Favorite {
+	uuid: string;
+	label: string | null;
+	read_only: boolean;
+	extra_options: string | null;
+	ignore_permissions: boolean;
+}
+
+export interface FavoriteAutoMountEvent {
+	uuid: string;
+	device: string;
+	label: string | null;
+	outcome: MountOutcome;
+	message: string | null;
+}
+
+export interface PartitionChange {
+	device: string;
+	disk: string;
+	uuid: string | null;
+	filesystem: string | null;
+	mount_point: string | null;
+}
+
+export interface DiskChange {
+	device: string;
+	partitions: string[];
+}
+
+/** All lists empty means the change is not visible to the host; refresh fully */
+export interface DisksChangedEvent {
+	added_disks: DiskChange[];
+	removed_disks: DiskChange[];
+	changed_disks: DiskChange[];
+	added_partitions: PartitionChange[];
+	removed_partitions: PartitionChange[];
+	changed_partitions: PartitionChange[];
+}
+
+export type MountProgressEvent = { device: string } & (
+	| { stage: 'vm_starting' }
+	| { stage: 'device_attached' }
+	| { stage: 'fs_check' }
+	| { stage: 'filesystem_mounted' }
+	| { stage: 'nfs_export_ready' }
+	| { stage: 'host_mount_done' }
+	| { stage: 'failed'; reason: string }
+);